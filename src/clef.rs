@@ -0,0 +1,73 @@
+//! Clefs and staff positions
+
+use crate::{midi::Octave, Natural};
+use core::fmt;
+
+/// A clef that fixes the notes on the lines of a five-line staff.
+///
+/// Staff positions count lines and spaces from the bottom line at `0` up to the top line at `8`,
+/// with negative positions and positions above `8` on ledger lines.
+/// ```
+/// use staff::midi::Octave;
+/// use staff::{Clef, Natural};
+///
+/// assert_eq!(Clef::Treble.position(Natural::B, Octave::FOUR), 4);
+/// assert_eq!(Clef::Bass.position(Natural::C, Octave::FOUR), 10);
+///
+/// assert_eq!(Clef::Alto.note(4), (Natural::C, Octave::FOUR));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Clef {
+    #[default]
+    Treble,
+    Bass,
+    Alto,
+    Tenor,
+}
+
+impl Clef {
+    /// Returns the note on the bottom line of the staff.
+    pub const fn bottom(self) -> (Natural, Octave) {
+        match self {
+            Self::Treble => (Natural::E, Octave::FOUR),
+            Self::Bass => (Natural::G, Octave::TWO),
+            Self::Alto => (Natural::F, Octave::THREE),
+            Self::Tenor => (Natural::D, Octave::THREE),
+        }
+    }
+
+    /// Returns the staff position of `natural` in `octave`.
+    pub const fn position(self, natural: Natural, octave: Octave) -> i8 {
+        let (bottom, bottom_octave) = self.bottom();
+        step(natural, octave) - step(bottom, bottom_octave)
+    }
+
+    /// Returns the natural and octave at a staff `position`.
+    pub fn note(self, position: i8) -> (Natural, Octave) {
+        let (bottom, octave) = self.bottom();
+        let step = step(bottom, octave) as i16 + position as i16;
+
+        let natural = Natural::C + step.rem_euclid(7) as u8;
+        let octave = Octave::new_unchecked(step.div_euclid(7) as i8);
+        (natural, octave)
+    }
+}
+
+impl fmt::Display for Clef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Treble => "treble",
+            Self::Bass => "bass",
+            Self::Alto => "alto",
+            Self::Tenor => "tenor",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Returns the number of diatonic steps from C0 to `natural` in `octave`.
+const fn step(natural: Natural, octave: Octave) -> i8 {
+    let from_c = (natural as u8 + 5) % 7;
+    octave.into_i8() * 7 + from_c as i8
+}
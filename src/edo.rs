@@ -0,0 +1,432 @@
+//! Arbitrary equal divisions of the octave
+//!
+//! Notes, intervals, scales and chords in `N` equal divisions of the octave (N-EDO),
+//! such as quarter tones in 24-EDO or the meantone-like 31-EDO.
+//!
+//! ```
+//! use staff::edo::{EdoChord, EdoNote, EdoPitch, EdoScale};
+//! use staff::midi::Octave;
+//! use staff::scale::ScaleIntervals;
+//!
+//! // C major in 31-EDO
+//! let root = EdoNote::<31>::new(EdoPitch::C, Octave::FOUR);
+//! let scale = EdoScale::from_scale(root, ScaleIntervals::major());
+//! let steps = scale.map(|note| note.pitch().steps());
+//! assert!(steps.eq([0, 5, 10, 13, 18, 23, 28]));
+//!
+//! // Its major third is about 13 cents flatter than in 12-TET
+//! let chord = EdoChord::major(root);
+//! let (midi, cents) = chord.into_iter().nth(1).unwrap().to_midi().unwrap();
+//! assert_eq!(midi.to_string(), "E4");
+//! assert!((cents + 12.9).abs() < 0.1);
+//! ```
+
+use crate::{
+    midi::{MidiNote, Octave},
+    scale::{Scale, ScaleIntervals},
+    set::Set,
+    Interval,
+};
+use core::{
+    fmt,
+    ops::{Add, Sub},
+};
+
+/// Interval of `N` equal divisions of the octave, in steps.
+///
+/// `N` must be from 1 to 127, so an octave fits in an [`EdoIntervals`] set.
+/// ```compile_fail
+/// use staff::edo::EdoInterval;
+///
+/// let interval = EdoInterval::<200>::new(1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdoInterval<const N: u8> {
+    steps: u8,
+}
+
+impl<const N: u8> EdoInterval<N> {
+    pub const UNISON: Self = Self::new(0);
+
+    pub const OCTAVE: Self = Self::new(N);
+
+    /// Fails to compile for a division that doesn't fit in an [`EdoIntervals`] set.
+    const DIVISION: () = assert!(N > 0 && N < 128, "EDO must have 1 to 127 steps");
+
+    pub const fn new(steps: u8) -> Self {
+        let () = Self::DIVISION;
+        Self { steps }
+    }
+
+    pub const fn steps(self) -> u8 {
+        self.steps
+    }
+
+    /// Returns the steps of the fifth closest to a just perfect fifth (3/2).
+    /// ```
+    /// use staff::edo::EdoInterval;
+    ///
+    /// assert_eq!(EdoInterval::<19>::fifth().steps(), 11);
+    /// assert_eq!(EdoInterval::<24>::fifth().steps(), 14);
+    /// ```
+    pub fn fifth() -> Self {
+        // log2(3 / 2)
+        Self::new((N as f64 * 0.584_962_500_721_156 + 0.5) as u8)
+    }
+
+    /// Map a 12-TET interval to this division by its position on the chain of fifths,
+    /// so sharps and flats keep their spelling.
+    /// ```
+    /// use staff::edo::EdoInterval;
+    /// use staff::Interval;
+    ///
+    /// // The major third is 4 fifths up and the minor third is 3 fifths down.
+    /// assert_eq!(EdoInterval::<31>::from_interval(Interval::MAJOR_THIRD).steps(), 10);
+    /// assert_eq!(EdoInterval::<31>::from_interval(Interval::MINOR_THIRD).steps(), 8);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the interval has more than 255 steps in this division.
+    pub fn from_interval(interval: Interval) -> Self {
+        let semitones = interval.semitones();
+        let octaves = semitones / Interval::OCTAVE.semitones();
+
+        let mut fifths = (semitones % 12) as i32 * 7 % 12;
+        if fifths > 6 {
+            fifths -= 12;
+        }
+        let steps = (fifths * Self::fifth().steps() as i32).rem_euclid(N as i32);
+
+        let steps = octaves as u16 * N as u16 + steps as u16;
+        Self::new(u8::try_from(steps).expect("EDO interval overflow"))
+    }
+
+    /// Returns the size of this interval in cents.
+    pub fn cents(self) -> f64 {
+        self.steps as f64 * 1200. / N as f64
+    }
+}
+
+impl<const N: u8> From<u8> for EdoInterval<N> {
+    fn from(steps: u8) -> Self {
+        Self::new(steps)
+    }
+}
+
+impl<const N: u8> From<EdoInterval<N>> for u8 {
+    fn from(interval: EdoInterval<N>) -> Self {
+        interval.steps()
+    }
+}
+
+impl<const N: u8> Add for EdoInterval<N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.steps + rhs.steps)
+    }
+}
+
+/// Pitch class of `N` equal divisions of the octave, in steps above C.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdoPitch<const N: u8> {
+    steps: u8,
+}
+
+impl<const N: u8> EdoPitch<N> {
+    pub const C: Self = Self::new(0);
+
+    pub const fn new(steps: u8) -> Self {
+        let () = EdoInterval::<N>::DIVISION;
+        Self { steps: steps % N }
+    }
+
+    pub const fn steps(self) -> u8 {
+        self.steps
+    }
+}
+
+impl<const N: u8> Add<EdoInterval<N>> for EdoPitch<N> {
+    type Output = Self;
+
+    fn add(self, interval: EdoInterval<N>) -> Self {
+        Self::new(((self.steps as u16 + interval.steps as u16) % N as u16) as u8)
+    }
+}
+
+/// Note of `N` equal divisions of the octave, in steps above C-1 (MIDI note 0).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdoNote<const N: u8> {
+    steps: u16,
+}
+
+impl<const N: u8> EdoNote<N> {
+    /// Create a new `EdoNote` from an `EdoPitch` and `Octave`.
+    pub const fn new(pitch: EdoPitch<N>, octave: Octave) -> Self {
+        Self::from_steps((octave.into_i8() + 1) as u16 * N as u16 + pitch.steps as u16)
+    }
+
+    pub const fn from_steps(steps: u16) -> Self {
+        let () = EdoInterval::<N>::DIVISION;
+        Self { steps }
+    }
+
+    /// Returns the note closest to `midi`, which is exact when `N` is a multiple of 12.
+    pub const fn from_midi(midi: MidiNote) -> Self {
+        let twelfths = midi.into_byte() as u32 * N as u32;
+        Self::from_steps(((twelfths * 2 + 12) / 24) as u16)
+    }
+
+    pub const fn steps(self) -> u16 {
+        self.steps
+    }
+
+    pub const fn pitch(self) -> EdoPitch<N> {
+        EdoPitch::new((self.steps % N as u16) as u8)
+    }
+
+    /// Returns the octave of this note, or `None` if it's above octave 9 like MIDI notes.
+    pub fn octave(self) -> Option<Octave> {
+        let octave = self.steps / N as u16;
+        (octave <= 10).then(|| Octave::new_unchecked(octave as i8 - 1))
+    }
+
+    /// Returns the closest 12-TET `MidiNote` and the signed deviation from it in cents,
+    /// preferring the lower note on a tie,
+    /// or `None` if the closest note is above the MIDI range.
+    /// ```
+    /// use staff::edo::{EdoNote, EdoPitch};
+    /// use staff::midi::Octave;
+    /// use staff::midi;
+    ///
+    /// // A quarter tone above C4
+    /// let note = EdoNote::<24>::new(EdoPitch::new(1), Octave::FOUR);
+    /// assert_eq!(note.to_midi(), Some((midi!(C, 4), 50.)));
+    ///
+    /// // Three quarter tones above C4
+    /// let note = EdoNote::<24>::new(EdoPitch::new(3), Octave::FOUR);
+    /// assert_eq!(note.to_midi(), Some((midi!(CSharp, 4), 50.)));
+    /// ```
+    pub fn to_midi(self) -> Option<(MidiNote, f64)> {
+        let twelfths = self.steps as u32 * 12;
+        let byte = (twelfths * 2 + N as u32 - 1) / (N as u32 * 2);
+        if byte > 127 {
+            return None;
+        }
+
+        let cents = self.steps as f64 * 1200. / N as f64 - byte as f64 * 100.;
+        Some((MidiNote::from_byte(byte as u8), cents))
+    }
+
+    /// Returns the frequency of this note in Hz with A4 tuned to 440 Hz.
+    #[cfg(feature = "std")]
+    pub fn frequency(self) -> f64 {
+        let cents = self.steps as f64 * 1200. / N as f64;
+        440. * 2f64.powf((cents - 6900.) / 1200.)
+    }
+}
+
+impl<const N: u8> Add<EdoInterval<N>> for EdoNote<N> {
+    type Output = Self;
+
+    fn add(self, interval: EdoInterval<N>) -> Self {
+        Self::from_steps(self.steps + interval.steps as u16)
+    }
+}
+
+/// Returns the interval from `rhs` up to this note.
+///
+/// # Panics
+/// Panics if `rhs` is above this note or the interval has more than 255 steps.
+impl<const N: u8> Sub for EdoNote<N> {
+    type Output = EdoInterval<N>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.steps
+            .checked_sub(rhs.steps)
+            .and_then(|steps| u8::try_from(steps).ok())
+            .map(EdoInterval::new)
+            .expect("EDO interval out of range")
+    }
+}
+
+impl<const N: u8> fmt::Display for EdoNote<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Notes above the MIDI range are written in steps, like 130\31
+        let Some((midi, cents)) = self.to_midi() else {
+            return write!(f, "{}\\{}", self.steps, N);
+        };
+        write!(f, "{}", midi)?;
+        if cents != 0. {
+            write!(f, "{:+.0}¢", cents)?;
+        }
+        Ok(())
+    }
+}
+
+/// Set of intervals of `N` equal divisions of the octave, up to 127 steps.
+pub type EdoIntervals<const N: u8> = Set<EdoInterval<N>, u128>;
+
+impl<const N: u8> EdoIntervals<N> {
+    /// Map a set of 12-TET intervals to this division, see [`EdoInterval::from_interval`].
+    pub fn from_intervals<I>(intervals: I) -> Self
+    where
+        I: IntoIterator<Item = Interval>,
+    {
+        intervals
+            .into_iter()
+            .map(EdoInterval::from_interval)
+            .collect()
+    }
+
+    /// Create a set of intervals from their steps.
+    /// ```
+    /// use staff::edo::{EdoIntervals, EdoNote, EdoScale};
+    ///
+    /// // Maqam rast with quarter tone thirds and sevenths
+    /// let intervals = EdoIntervals::<24>::from_steps([0, 4, 7, 10, 14, 18, 21]);
+    /// let scale = EdoScale::new(EdoNote::from_steps(120), intervals);
+    ///
+    /// let names = scale.map(|note| note.to_string());
+    /// assert!(names.eq(["C4", "D4", "D#4+50¢", "F4", "G4", "A4", "A#4+50¢"]));
+    /// ```
+    pub fn from_steps<I>(steps: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        steps.into_iter().map(EdoInterval::new).collect()
+    }
+}
+
+/// Scale of `N` equal divisions of the octave.
+pub type EdoScale<const N: u8> = Scale<EdoNote<N>, EdoIntervals<N>>;
+
+impl<const N: u8> EdoScale<N> {
+    /// Map the intervals of a 12-TET scale to this division.
+    ///
+    /// `Scale::major` and friends are left to [`DiatonicScale`](crate::scale::DiatonicScale),
+    /// so other scales are built from their [`ScaleIntervals`].
+    pub fn from_scale(root: EdoNote<N>, intervals: ScaleIntervals) -> Self {
+        Self::new(root, EdoIntervals::from_intervals(intervals))
+    }
+}
+
+/// Chord of `N` equal divisions of the octave made of the intervals from its root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdoChord<const N: u8> {
+    pub root: EdoNote<N>,
+    pub intervals: EdoIntervals<N>,
+}
+
+impl<const N: u8> EdoChord<N> {
+    pub fn new(root: EdoNote<N>) -> Self {
+        Self {
+            root,
+            intervals: EdoIntervals::default(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: EdoInterval<N>) -> Self {
+        self.intervals.push(interval);
+        self
+    }
+
+    /// Map the intervals of a 12-TET chord to this division.
+    pub fn from_intervals<I>(root: EdoNote<N>, intervals: I) -> Self
+    where
+        I: IntoIterator<Item = Interval>,
+    {
+        Self {
+            root,
+            intervals: EdoIntervals::from_intervals(intervals),
+        }
+    }
+
+    pub fn major(root: EdoNote<N>) -> Self {
+        Self::from_intervals(
+            root,
+            [
+                Interval::UNISON,
+                Interval::MAJOR_THIRD,
+                Interval::PERFECT_FIFTH,
+            ],
+        )
+    }
+
+    pub fn minor(root: EdoNote<N>) -> Self {
+        Self::from_intervals(
+            root,
+            [
+                Interval::UNISON,
+                Interval::MINOR_THIRD,
+                Interval::PERFECT_FIFTH,
+            ],
+        )
+    }
+}
+
+impl<const N: u8> IntoIterator for EdoChord<N> {
+    type Item = EdoNote<N>;
+
+    type IntoIter = EdoScale<N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Scale::new(self.root, self.intervals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EdoInterval, EdoNote};
+    use crate::{
+        midi::{MidiNote, Octave},
+        Interval,
+    };
+
+    #[test]
+    fn it_matches_12_tet_for_multiples_of_12() {
+        for semitones in 0..24 {
+            let interval = EdoInterval::<24>::from_interval(Interval::new(semitones));
+            assert_eq!(interval.steps(), semitones * 2);
+        }
+
+        for byte in 0..=127 {
+            let midi = MidiNote::from_byte(byte);
+            assert_eq!(EdoNote::<12>::from_midi(midi).to_midi(), Some((midi, 0.)));
+        }
+    }
+
+    #[test]
+    fn it_rounds_to_the_nearest_midi_note() {
+        let note = EdoNote::<19>::from_midi(MidiNote::from_byte(60)) + EdoInterval::new(11);
+        let (midi, cents) = note.to_midi().unwrap();
+
+        assert_eq!(midi, MidiNote::from_byte(67));
+        assert!((cents + 5.26).abs() < 0.01);
+    }
+
+    #[test]
+    fn it_stays_within_range() {
+        let note = EdoNote::<31>::from_midi(MidiNote::from_byte(127));
+        assert!(note.to_midi().is_some());
+        assert_eq!((note + EdoInterval::OCTAVE).to_midi(), None);
+        assert_eq!((note + EdoInterval::OCTAVE).to_string(), "359\\31");
+        assert_eq!(note.octave(), Some(Octave::new_unchecked(9)));
+        assert_eq!((note + EdoInterval::OCTAVE).octave(), None);
+
+        // Compound intervals are computed without overflowing
+        let interval = EdoInterval::<31>::from_interval(Interval::new(96));
+        assert_eq!(interval.steps(), 248);
+        assert_eq!(EdoNote::from_steps(248) - EdoNote::from_steps(0), interval);
+    }
+
+    #[test]
+    #[should_panic(expected = "EDO interval out of range")]
+    fn it_panics_on_negative_intervals() {
+        let _ = EdoNote::<19>::from_steps(0) - EdoNote::from_steps(1);
+    }
+}
@@ -64,8 +64,23 @@ impl Format for MidiNote {
 impl Format for Chord {
     fn fmt_with_octave(&self, f: &mut fmt::Formatter, show_octave: bool) -> fmt::Result {
         self.root.fmt_with_octave(f, show_octave)?;
+        Suffix(self).fmt(f)?;
 
-        let mut intervals: IntervalSet = self.clone().intervals().collect();
+        if let Some(bass) = self.bass {
+            f.write_char('/')?;
+            bass.fmt_with_octave(f, show_octave)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The suffix of a chord's name between its root and bass, such as `m7`.
+pub(crate) struct Suffix<'a>(pub &'a Chord);
+
+impl fmt::Display for Suffix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut intervals: IntervalSet = self.0.clone().intervals().collect();
 
         if intervals.maybe_remove(Interval::MAJOR_THIRD).is_none() {
             if intervals.maybe_remove(Interval::MINOR_THIRD).is_some() {
@@ -77,12 +92,8 @@ impl Format for Chord {
             }
         }
 
-        let mut has_fifth = true;
-        if intervals.maybe_remove(Interval::TRITONE).is_some() {
-            f.write_str("b5")?
-        } else if intervals.maybe_remove(Interval::PERFECT_FIFTH).is_none() {
-            has_fifth = false;
-        }
+        let is_flat_five = intervals.maybe_remove(Interval::TRITONE).is_some();
+        let has_fifth = is_flat_five || intervals.maybe_remove(Interval::PERFECT_FIFTH).is_some();
 
         if intervals.maybe_remove(Interval::MINOR_SEVENTH).is_some() {
             if intervals.maybe_remove(Interval::MAJOR_NINTH).is_some() {
@@ -114,9 +125,8 @@ impl Format for Chord {
             }
         }
 
-        if let Some(bass) = self.bass {
-            f.write_char('/')?;
-            bass.fmt_with_octave(f, show_octave)?;
+        if is_flat_five {
+            f.write_str("b5")?
         }
 
         if !intervals.maybe_remove(Interval::UNISON).is_some() {
@@ -33,10 +33,13 @@ impl Interval {
 
     pub const OCTAVE: Self = Self::new(12);
 
+    pub const MINOR_NINTH: Self = Self::new(13);
     pub const MAJOR_NINTH: Self = Self::new(14);
+    pub const AUGMENTED_NINTH: Self = Self::new(15);
 
     pub const MINOR_ELEVENTH: Self = Self::new(16);
     pub const MAJOR_ELEVENTH: Self = Self::new(17);
+    pub const AUGMENTED_ELEVENTH: Self = Self::new(18);
 
     pub const MINOR_THIRTEENTH: Self = Self::new(20);
     pub const MAJOR_THIRTEENTH: Self = Self::new(21);
@@ -92,9 +95,12 @@ impl fmt::Display for Interval {
             Interval::MAJOR_SIXTH => f.write_char('6'),
             Interval::MINOR_SEVENTH => f.write_str("m7"),
             Interval::MAJOR_SEVENTH => f.write_str("maj7"),
+            Interval::MINOR_NINTH => f.write_str("b9"),
             Interval::MAJOR_NINTH => f.write_char('9'),
+            Interval::AUGMENTED_NINTH => f.write_str("#9"),
             Interval::MINOR_ELEVENTH => f.write_str("11"),
             Interval::MAJOR_ELEVENTH => f.write_str("maj11"),
+            Interval::AUGMENTED_ELEVENTH => f.write_str("#11"),
             Interval::MINOR_THIRTEENTH => f.write_str("11"),
             Interval::MAJOR_THIRTEENTH => f.write_str("maj13"),
             i => panic!("{:?}", i),
@@ -0,0 +1,125 @@
+//! Circle of fifths layout
+
+use super::{Key, Mode};
+
+/// One of the 12 positions on the circle of fifths, with its major and relative minor key.
+///
+/// Positions where two spellings both fit in 7 sharps or flats
+/// (B/C♭, F♯/G♭ and D♭/C♯ major) also hold the enharmonic key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    /// Clockwise position from C major at the top, from 0 to 11.
+    pub position: u8,
+    pub major: Key,
+    pub enharmonic: Option<Key>,
+}
+
+impl Segment {
+    /// Returns the segment at `position` on the circle, wrapping past 11.
+    ///
+    /// The major key is spelled with at most 6 sharps or 5 flats.
+    pub fn new(position: u8) -> Self {
+        let position = position % 12;
+        let fifths = if position <= 6 {
+            position as i8
+        } else {
+            position as i8 - 12
+        };
+        let major = Key::from_fifths(fifths, Mode::Major);
+
+        Self {
+            position,
+            major,
+            enharmonic: major.enharmonic(),
+        }
+    }
+
+    /// Returns the segment containing `key` or its relative major.
+    /// ```
+    /// use staff::key::circle::Segment;
+    /// use staff::{Key, Pitch};
+    ///
+    /// let segment = Segment::from_key(Key::minor(Pitch::DSharp));
+    /// assert_eq!(segment.position, 6);
+    /// assert_eq!(segment.major.to_string(), "F♯ major");
+    /// assert_eq!(segment.enharmonic_minor().unwrap().to_string(), "e♭ minor");
+    /// ```
+    pub fn from_key(key: Key) -> Self {
+        Self::new(key.fifths().rem_euclid(12) as u8)
+    }
+
+    /// Returns the relative minor of this segment's major key.
+    pub fn minor(self) -> Key {
+        self.major.relative()
+    }
+
+    /// Returns the relative minor of this segment's enharmonic key.
+    pub fn enharmonic_minor(self) -> Option<Key> {
+        self.enharmonic.map(Key::relative)
+    }
+
+    /// Returns the angle of this segment in degrees, clockwise from the top.
+    pub fn angle(self) -> f64 {
+        self.position as f64 * 30.
+    }
+
+    /// Returns `true` if `key` is one of the major or minor keys in this segment.
+    pub fn contains(self, key: Key) -> bool {
+        [
+            Some(self.major),
+            Some(self.minor()),
+            self.enharmonic,
+            self.enharmonic_minor(),
+        ]
+        .contains(&Some(key))
+    }
+}
+
+/// Iterator over the circle of fifths, clockwise from C major.
+///
+/// Created by [`circle`].
+pub struct Circle {
+    position: u8,
+}
+
+impl Iterator for Circle {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position < 12 {
+            let segment = Segment::new(self.position);
+            self.position += 1;
+            Some(segment)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the 12 segments of the circle of fifths, clockwise from C major.
+/// ```
+/// use staff::key::circle;
+///
+/// let majors: Vec<_> = circle::circle().map(|segment| segment.major.to_string()).collect();
+/// assert_eq!(
+///     majors,
+///     [
+///         "C major", "G major", "D major", "A major", "E major", "B major",
+///         "F♯ major", "D♭ major", "A♭ major", "E♭ major", "B♭ major", "F major",
+///     ]
+/// );
+///
+/// let minors: Vec<_> = circle::circle().map(|segment| segment.minor().to_string()).collect();
+/// assert_eq!(minors[0], "a minor");
+/// assert_eq!(minors[7], "b♭ minor");
+///
+/// let overlaps: Vec<_> = circle::circle()
+///     .filter_map(|segment| segment.enharmonic)
+///     .map(|key| key.to_string())
+///     .collect();
+/// assert_eq!(overlaps, ["C♭ major", "G♭ major", "C♯ major"]);
+/// ```
+pub fn circle() -> Circle {
+    Circle { position: 0 }
+}
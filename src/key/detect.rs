@@ -0,0 +1,394 @@
+//! Key detection from pitch class histograms
+//!
+//! Each note adds its duration to a histogram of the 12 pitch classes,
+//! which is then correlated with a [`Profile`] rotated to each of the 24 major and minor keys.
+//!
+//! ```
+//! use staff::key::detect::{Histogram, Profile};
+//! use staff::{midi, Key, Pitch};
+//!
+//! // G major arpeggio and scale fragment
+//! let notes = [
+//!     (midi!(G, 3), 2.),
+//!     (midi!(B, 3), 1.),
+//!     (midi!(D, 4), 1.),
+//!     (midi!(FSharp, 4), 0.5),
+//!     (midi!(G, 4), 2.),
+//!     (midi!(A, 4), 0.5),
+//!     (midi!(C, 4), 0.5),
+//! ];
+//!
+//! let histogram: Histogram = notes.into_iter().collect();
+//! let estimates = histogram.keys(&Profile::KRUMHANSL);
+//!
+//! assert_eq!(estimates[0].key, Key::major(Pitch::G));
+//! assert_eq!(estimates.len(), 24);
+//! ```
+
+use super::Key;
+use crate::{
+    midi::{
+        message::{Message, NoteMessage},
+        MidiNote,
+    },
+    Pitch,
+};
+
+/// Key profiles for major and minor keys with a tonic of C,
+/// giving the expected weight of each pitch class.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile {
+    pub major: [f64; 12],
+    pub minor: [f64; 12],
+}
+
+impl Profile {
+    /// Krumhansl and Kessler probe-tone ratings.
+    pub const KRUMHANSL: Self = Self {
+        major: [
+            6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+        ],
+        minor: [
+            6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+        ],
+    };
+
+    /// Temperley's profiles from the Kostka-Payne corpus,
+    /// which weigh the leading tone and raised seventh of minor keys higher.
+    pub const TEMPERLEY: Self = Self {
+        major: [
+            0.748, 0.060, 0.488, 0.082, 0.670, 0.460, 0.096, 0.715, 0.104, 0.366, 0.057, 0.400,
+        ],
+        minor: [
+            0.712, 0.084, 0.474, 0.618, 0.049, 0.460, 0.105, 0.747, 0.404, 0.067, 0.133, 0.330,
+        ],
+    };
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::KRUMHANSL
+    }
+}
+
+/// A key and its correlation with a histogram, from -1 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Estimate {
+    pub key: Key,
+    pub score: f64,
+}
+
+/// Total duration of each pitch class.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Histogram {
+    pub weights: [f64; 12],
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `midi` held for `duration` to this histogram.
+    pub fn push(&mut self, midi: MidiNote, duration: f64) {
+        self.weights[midi.pitch().into_byte() as usize] += duration;
+    }
+
+    /// Create a histogram from a MIDI track of note messages and their time in ticks.
+    ///
+    /// Each note lasts from its `NoteOn` message until the next `NoteOff`
+    /// (or `NoteOn` with a velocity of 0) of the same note.
+    /// Notes still held at the end of the track are ignored.
+    /// ```
+    /// use staff::key::detect::Histogram;
+    /// use staff::midi::message::{Message, NoteMessage};
+    /// use staff::{midi, Pitch};
+    ///
+    /// let on = |note| Message::NoteOn(NoteMessage::new(0, note, 100));
+    /// let off = |note| Message::NoteOff(NoteMessage::new(0, note, 0));
+    ///
+    /// let track = [
+    ///     (0, on(midi!(C, 4))),
+    ///     (0, on(midi!(E, 4))),
+    ///     (480, off(midi!(E, 4))),
+    ///     (960, off(midi!(C, 4))),
+    /// ];
+    /// let histogram = Histogram::from_messages(track);
+    ///
+    /// assert_eq!(histogram.weights[Pitch::C.into_byte() as usize], 960.);
+    /// assert_eq!(histogram.weights[Pitch::E.into_byte() as usize], 480.);
+    /// ```
+    pub fn from_messages<'a, I>(messages: I) -> Self
+    where
+        I: IntoIterator<Item = (u64, Message<'a>)>,
+    {
+        let mut histogram = Self::new();
+        let mut starts: [Option<u64>; 128] = [None; 128];
+
+        for (tick, message) in messages {
+            match message {
+                Message::NoteOn(NoteMessage { note, velocity, .. }) if velocity > 0 => {
+                    let start = &mut starts[note.into_byte() as usize & 127];
+                    if start.is_none() {
+                        *start = Some(tick);
+                    }
+                }
+                Message::NoteOn(NoteMessage { note, .. })
+                | Message::NoteOff(NoteMessage { note, .. }) => {
+                    if let Some(start) = starts[note.into_byte() as usize & 127].take() {
+                        histogram.push(note, tick.saturating_sub(start) as f64);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        histogram
+    }
+
+    /// Returns the total duration of every pitch class.
+    pub fn total(&self) -> f64 {
+        self.weights.iter().sum()
+    }
+
+    /// Returns the Pearson correlation of this histogram with `profile` rotated to `tonic`.
+    pub fn correlation(&self, profile: &[f64; 12], tonic: Pitch) -> f64 {
+        let tonic = tonic.into_byte() as usize;
+        let rotated = |i: usize| profile[(i + 12 - tonic) % 12];
+
+        let mean_x = self.total() / 12.;
+        let mean_y = profile.iter().sum::<f64>() / 12.;
+
+        let (mut xy, mut xx, mut yy) = (0., 0., 0.);
+        for i in 0..12 {
+            let x = self.weights[i] - mean_x;
+            let y = rotated(i) - mean_y;
+            xy += x * y;
+            xx += x * x;
+            yy += y * y;
+        }
+
+        if xx == 0. || yy == 0. {
+            0.
+        } else {
+            xy / (xx * yy).sqrt()
+        }
+    }
+
+    /// Returns every major and minor key ranked by their correlation with `profile`,
+    /// from the most to the least likely.
+    pub fn keys(&self, profile: &Profile) -> Vec<Estimate> {
+        let mut estimates: Vec<_> = (0..12)
+            .map(Pitch::from_byte)
+            .flat_map(|tonic| {
+                [
+                    Estimate {
+                        key: Key::major(tonic),
+                        score: self.correlation(&profile.major, tonic),
+                    },
+                    Estimate {
+                        key: Key::minor(tonic),
+                        score: self.correlation(&profile.minor, tonic),
+                    },
+                ]
+            })
+            .collect();
+
+        estimates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        estimates
+    }
+
+    /// Returns the most likely key for `profile`, or `None` if this histogram is empty.
+    pub fn key(&self, profile: &Profile) -> Option<Estimate> {
+        if self.total() <= 0. {
+            return None;
+        }
+
+        self.keys(profile).into_iter().next()
+    }
+}
+
+impl Extend<(MidiNote, f64)> for Histogram {
+    fn extend<T: IntoIterator<Item = (MidiNote, f64)>>(&mut self, iter: T) {
+        for (midi, duration) in iter {
+            self.push(midi, duration);
+        }
+    }
+}
+
+impl FromIterator<(MidiNote, f64)> for Histogram {
+    fn from_iter<T: IntoIterator<Item = (MidiNote, f64)>>(iter: T) -> Self {
+        let mut histogram = Self::new();
+        histogram.extend(iter);
+        histogram
+    }
+}
+
+/// Returns the ranked keys of `notes` and their durations with `profile`.
+pub fn detect<I>(notes: I, profile: &Profile) -> Vec<Estimate>
+where
+    I: IntoIterator<Item = (MidiNote, f64)>,
+{
+    notes.into_iter().collect::<Histogram>().keys(profile)
+}
+
+/// A note with its start time and duration in the same unit, such as beats or ticks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedNote {
+    pub midi: MidiNote,
+    pub start: f64,
+    pub duration: f64,
+}
+
+impl TimedNote {
+    pub fn new(midi: MidiNote, start: f64, duration: f64) -> Self {
+        Self {
+            midi,
+            start,
+            duration,
+        }
+    }
+
+    /// Returns how long this note sounds between `start` and `end`.
+    fn overlap(&self, start: f64, end: f64) -> f64 {
+        ((self.start + self.duration).min(end) - self.start.max(start)).max(0.)
+    }
+}
+
+/// The most likely key of one window in a [`Windows`] curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Window {
+    pub start: f64,
+    pub end: f64,
+    pub estimate: Estimate,
+}
+
+/// Iterator over the key of each window of a piece, to follow modulations over time.
+///
+/// Created by [`Windows::new`].
+/// ```
+/// use staff::key::detect::{Profile, TimedNote, Windows};
+/// use staff::{midi, Key, Pitch};
+///
+/// // Four beats of C major, then four beats of A major
+/// let notes: Vec<_> = [
+///     midi!(C, 4), midi!(E, 4), midi!(G, 4), midi!(F, 4),
+///     midi!(A, 4), midi!(CSharp, 5), midi!(E, 5), midi!(D, 5),
+/// ]
+/// .into_iter()
+/// .enumerate()
+/// .map(|(i, midi)| TimedNote::new(midi, i as f64, 1.))
+/// .collect();
+///
+/// let keys: Vec<_> = Windows::new(&notes, 4., 4., Profile::TEMPERLEY)
+///     .map(|window| window.estimate.key)
+///     .collect();
+///
+/// assert_eq!(keys, [Key::major(Pitch::C), Key::major(Pitch::A)]);
+/// ```
+pub struct Windows<'a> {
+    notes: &'a [TimedNote],
+    profile: Profile,
+    size: f64,
+    hop: f64,
+    start: f64,
+    end: f64,
+}
+
+impl<'a> Windows<'a> {
+    /// Create a windowed key curve over `notes` with windows of `size`,
+    /// each starting `hop` after the last.
+    ///
+    /// Windows without any notes are skipped.
+    ///
+    /// # Panics
+    /// Panics if `size` or `hop` isn't positive.
+    pub fn new(notes: &'a [TimedNote], size: f64, hop: f64, profile: Profile) -> Self {
+        assert!(
+            size > 0. && hop > 0.,
+            "window size and hop must be positive"
+        );
+
+        let start = notes
+            .iter()
+            .map(|note| note.start)
+            .min_by(f64::total_cmp)
+            .unwrap_or(0.);
+        let end = notes
+            .iter()
+            .map(|note| note.start + note.duration)
+            .max_by(f64::total_cmp)
+            .unwrap_or(0.);
+
+        Self {
+            notes,
+            profile,
+            size,
+            hop,
+            start,
+            end,
+        }
+    }
+}
+
+impl Iterator for Windows<'_> {
+    type Item = Window;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.start < self.end {
+            let start = self.start;
+            let end = start + self.size;
+            self.start += self.hop;
+
+            let mut histogram = Histogram::new();
+            for note in self.notes {
+                histogram.push(note.midi, note.overlap(start, end));
+            }
+
+            if let Some(estimate) = histogram.key(&self.profile) {
+                return Some(Window {
+                    start,
+                    end,
+                    estimate,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::Octave;
+
+    #[test]
+    fn it_detects_a_minor_key() {
+        let notes = [
+            (MidiNote::new(Pitch::A, Octave::THREE), 2.),
+            (MidiNote::new(Pitch::C, Octave::FOUR), 1.),
+            (MidiNote::new(Pitch::E, Octave::FOUR), 1.),
+            (MidiNote::new(Pitch::GSharp, Octave::THREE), 0.5),
+            (MidiNote::new(Pitch::A, Octave::THREE), 1.),
+            (MidiNote::new(Pitch::D, Octave::FOUR), 0.5),
+            (MidiNote::new(Pitch::B, Octave::THREE), 0.5),
+        ];
+
+        for profile in [Profile::KRUMHANSL, Profile::TEMPERLEY] {
+            let estimate = detect(notes, &profile)[0];
+            assert_eq!(estimate.key, Key::minor(Pitch::A));
+        }
+    }
+
+    #[test]
+    fn it_returns_none_for_an_empty_histogram() {
+        assert_eq!(Histogram::new().key(&Profile::default()), None);
+        assert_eq!(Windows::new(&[], 4., 1., Profile::default()).next(), None);
+    }
+}
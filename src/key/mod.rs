@@ -0,0 +1,608 @@
+//! Key signatures
+
+use crate::{
+    note::{self, Accidental, Note},
+    scale::{ScaleIntervals, ScaleSet},
+    Interval, Natural, Pitch,
+};
+use core::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod detect;
+
+pub mod circle;
+
+mod signature;
+pub use signature::{Signature, SignatureAccidental};
+
+/// Mode of a [`Key`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+}
+
+impl Mode {
+    /// Every mode from the brightest to the darkest.
+    pub const ALL: [Self; 7] = [
+        Self::Lydian,
+        Self::Major,
+        Self::Mixolydian,
+        Self::Dorian,
+        Self::Minor,
+        Self::Phrygian,
+        Self::Locrian,
+    ];
+
+    /// Returns the number of fifths from the tonic of the major key with the same signature
+    /// to the tonic of this mode.
+    pub const fn fifths(self) -> i8 {
+        match self {
+            Self::Lydian => -1,
+            Self::Major => 0,
+            Self::Mixolydian => 1,
+            Self::Dorian => 2,
+            Self::Minor => 3,
+            Self::Phrygian => 4,
+            Self::Locrian => 5,
+        }
+    }
+
+    pub fn intervals(self) -> ScaleIntervals {
+        match self {
+            Self::Major => ScaleIntervals::major(),
+            Self::Minor => ScaleIntervals::natural_minor(),
+            Self::Dorian => ScaleIntervals::dorian(),
+            Self::Phrygian => ScaleIntervals::phrygian(),
+            Self::Lydian => ScaleIntervals::lydian(),
+            Self::Mixolydian => ScaleIntervals::mixolydian(),
+            Self::Locrian => ScaleIntervals::locrian(),
+        }
+    }
+
+    /// Returns the mode of the scale with `intervals`, if it's a mode of the major scale.
+    pub fn from_intervals(intervals: ScaleIntervals) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.intervals() == intervals)
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Dorian => "dorian",
+            Self::Phrygian => "phrygian",
+            Self::Lydian => "lydian",
+            Self::Mixolydian => "mixolydian",
+            Self::Locrian => "locrian",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A key made of its spelled tonic and mode.
+/// ```
+/// use staff::key::{Key, Mode};
+/// use staff::{Natural, Note};
+///
+/// let key = Key::new(Note::flat(Natural::E), Mode::Major);
+///
+/// assert_eq!(key.to_string(), "E♭ major");
+/// assert_eq!(key.relative().to_string(), "c minor");
+/// assert_eq!(key.flats(), 3);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Key {
+    pub tonic: Note,
+    pub mode: Mode,
+}
+
+impl Key {
+    pub const fn new(tonic: Note, mode: Mode) -> Self {
+        Self { tonic, mode }
+    }
+
+    /// Create a major key from its tonic `root`, spelled with at most 6 sharps or 5 flats.
+    /// ```
+    /// use staff::{Key, Pitch};
+    ///
+    /// let key = Key::major(Pitch::D);
+    /// assert_eq!(key.sharps(), 2)
+    /// ```
+    ///
+    /// ```
+    /// use staff::{Key, Pitch};
+    ///
+    /// let key = Key::major(Pitch::F);
+    /// assert_eq!(key.flats(), 1)
+    /// ```
+    pub fn major(root: Pitch) -> Self {
+        Self::from_fifths(Self::signature_of(root), Mode::Major)
+    }
+
+    /// Create a minor key from its tonic `root`, spelled like its relative major.
+    /// ```
+    /// use staff::{Key, Pitch};
+    ///
+    /// let key = Key::minor(Pitch::E);
+    /// assert_eq!(key.to_string(), "e minor")
+    /// ```
+    pub fn minor(root: Pitch) -> Self {
+        Self::from_fifths(
+            Self::signature_of(root + Interval::MINOR_THIRD),
+            Mode::Minor,
+        )
+    }
+
+    /// Create a key of `mode` from its signature in `fifths`,
+    /// where positive numbers are sharps and negative numbers are flats.
+    ///
+    /// # Panics
+    /// Panics if the tonic would need more than a double sharp or double flat.
+    pub fn from_fifths(fifths: i8, mode: Mode) -> Self {
+        let tonic = Note::from_fifths(fifths + mode.fifths()).expect("key out of range");
+        Self::new(tonic, mode)
+    }
+
+    /// Create a key of `mode` from its signature in `fifths`,
+    /// or its enharmonic equivalent if the tonic would need more than a double sharp or double flat.
+    fn from_fifths_or_enharmonic(fifths: i8, mode: Mode) -> Self {
+        [0, -12, 12]
+            .into_iter()
+            .find_map(|offset| Note::from_fifths(fifths + offset + mode.fifths()))
+            .map(|tonic| Self::new(tonic, mode))
+            .expect("key out of range")
+    }
+
+    /// Returns the signature of this key in fifths,
+    /// where positive numbers are sharps and negative numbers are flats.
+    pub const fn fifths(self) -> i8 {
+        self.tonic.fifths() - self.mode.fifths()
+    }
+
+    /// Returns the number of sharps in this key.
+    pub fn sharps(self) -> u8 {
+        self.fifths().max(0) as u8
+    }
+
+    /// Returns the number of flats in this key.
+    pub fn flats(self) -> u8 {
+        (-self.fifths()).max(0) as u8
+    }
+
+    pub fn is_sharp(self) -> bool {
+        self.fifths() >= 0
+    }
+
+    pub fn accidental(self, natural: Natural) -> Accidental {
+        let count = self.into_iter().filter(|n| *n == natural).count() as i8;
+        let semitones = if self.is_sharp() { count } else { -count };
+        Accidental::from_semitones(semitones).unwrap_or(Accidental::Natural)
+    }
+
+    /// Returns the key with the same signature and the other of major or minor.
+    ///
+    /// Every other mode returns its relative major key.
+    /// If its tonic would need more than a double sharp or double flat,
+    /// this returns the enharmonic key instead.
+    pub fn relative(self) -> Self {
+        let mode = match self.mode {
+            Mode::Major => Mode::Minor,
+            _ => Mode::Major,
+        };
+        Self::from_fifths_or_enharmonic(self.fifths(), mode)
+    }
+
+    /// Returns the key with the same tonic and the other of major or minor.
+    ///
+    /// Every other mode returns the major key on its tonic.
+    /// ```
+    /// use staff::{Key, Pitch};
+    ///
+    /// assert_eq!(Key::major(Pitch::C).parallel().to_string(), "c minor");
+    /// ```
+    pub fn parallel(self) -> Self {
+        let mode = match self.mode {
+            Mode::Major => Mode::Minor,
+            _ => Mode::Major,
+        };
+        Self::new(self.tonic, mode)
+    }
+
+    /// Returns the key of the same mode a fifth above.
+    ///
+    /// If its tonic would need more than a double sharp, this returns the enharmonic key instead.
+    /// ```
+    /// use staff::{Key, Natural, Note};
+    /// use staff::key::Mode;
+    ///
+    /// let key = Key::new(Note::double_sharp(Natural::B), Mode::Major);
+    /// assert_eq!(key.dominant(), Key::new(Note::sharp(Natural::G), Mode::Major));
+    /// ```
+    pub fn dominant(self) -> Self {
+        Self::from_fifths_or_enharmonic(self.fifths() + 1, self.mode)
+    }
+
+    /// Returns the key of the same mode a fifth below.
+    ///
+    /// If its tonic would need more than a double flat, this returns the enharmonic key instead.
+    pub fn subdominant(self) -> Self {
+        Self::from_fifths_or_enharmonic(self.fifths() - 1, self.mode)
+    }
+
+    /// Returns the shortest signed distance from this key's signature to `other`'s
+    /// on the circle of fifths, counting enharmonic signatures as equal.
+    /// ```
+    /// use staff::{Key, Pitch};
+    ///
+    /// assert_eq!(Key::major(Pitch::C).distance(Key::major(Pitch::E)), 4);
+    /// assert_eq!(Key::major(Pitch::B).distance(Key::major(Pitch::DSharp)), 4);
+    /// assert_eq!(Key::minor(Pitch::A).distance(Key::major(Pitch::F)), -1);
+    /// ```
+    pub fn distance(self, other: Self) -> i8 {
+        (other.fifths() - self.fifths() + 5).rem_euclid(12) - 5
+    }
+
+    /// Returns the enharmonic equivalent of this key, if its signature has at most 7 accidentals.
+    /// ```
+    /// use staff::{Key, Natural, Note};
+    /// use staff::key::Mode;
+    ///
+    /// let key = Key::new(Note::sharp(Natural::F), Mode::Major);
+    /// assert_eq!(key.enharmonic(), Some(Key::new(Note::flat(Natural::G), Mode::Major)));
+    ///
+    /// let key = Key::new(Note::D, Mode::Major);
+    /// assert_eq!(key.enharmonic(), None);
+    /// ```
+    pub fn enharmonic(self) -> Option<Self> {
+        let fifths = self.fifths();
+        let fifths = if fifths > 0 { fifths - 12 } else { fifths + 12 };
+        if fifths.abs() > 7 {
+            return None;
+        }
+
+        Note::from_fifths(fifths + self.mode.fifths()).map(|tonic| Self::new(tonic, self.mode))
+    }
+
+    /// Returns the scale of this key.
+    /// ```
+    /// use staff::{Key, Natural, Note, Pitch};
+    ///
+    /// let scale = Key::minor(Pitch::G).scale();
+    ///
+    /// assert_eq!(scale.degree(2), Some(Note::flat(Natural::B)));
+    /// ```
+    pub fn scale(self) -> ScaleSet<Note> {
+        ScaleSet::new(self.tonic, self.mode.intervals())
+    }
+
+    /// Returns the spelling of `pitch` in this key,
+    /// from the fifths between 5 below the tonic and 6 above it.
+    /// ```
+    /// use staff::{Key, Natural, Note, Pitch};
+    ///
+    /// assert_eq!(Key::minor(Pitch::A).spell(Pitch::GSharp), Note::sharp(Natural::G));
+    /// assert_eq!(Key::major(Pitch::F).spell(Pitch::ASharp), Note::flat(Natural::B));
+    /// ```
+    pub fn spell(self, pitch: Pitch) -> Note {
+        let lowest = self.tonic.fifths() - 5;
+        let fifths = (pitch.into_byte() as i8 * 7 - lowest).rem_euclid(12) + lowest;
+
+        // Keys with double sharps or flats can reach past the notes with an accidental
+        Note::from_fifths(fifths)
+            .or_else(|| Note::from_fifths(fifths - 12 * fifths.signum()))
+            .expect("pitch classes fit in 12 fifths")
+    }
+
+    /// Returns the number of fifths from C to `root`, with at most 6 sharps or 5 flats.
+    fn signature_of(root: Pitch) -> i8 {
+        let mut pitch = Pitch::C;
+        let mut alteration = 0;
+        while pitch != root {
+            pitch = pitch + Interval::PERFECT_FIFTH;
+            alteration += 1;
+        }
+
+        if alteration <= 6 {
+            alteration
+        } else {
+            alteration - 12
+        }
+    }
+}
+
+impl From<Key> for ScaleSet<Note> {
+    fn from(key: Key) -> Self {
+        key.scale()
+    }
+}
+
+impl IntoIterator for Key {
+    type Item = Natural;
+
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let (natural, step) = if self.is_sharp() {
+            (Natural::F, 4)
+        } else {
+            (Natural::B, 3)
+        };
+
+        Iter {
+            natural,
+            remaining: self.fifths().unsigned_abs(),
+            step,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = self.tonic.natural.to_char();
+        if self.mode == Mode::Minor {
+            f.write_char(letter.to_ascii_lowercase())?;
+        } else {
+            f.write_char(letter)?;
+        }
+
+        if !self.tonic.accidental.is_natural() {
+            self.tonic.accidental.fmt(f)?;
+        }
+
+        write!(f, " {}", self.mode)
+    }
+}
+
+impl Key {
+    /// Returns the major key with a signature of up to 7 sharps or flats in `fifths`.
+    fn from_signature(fifths: i8) -> Result<Self, Error> {
+        if !(-7..=7).contains(&fifths) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(Self::from_fifths(fifths, Mode::Major))
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    /// Parse a key from its tonic and mode, or from the signature of a major key.
+    ///
+    /// A tonic without a mode is a major key, or a minor key if it's lowercase.
+    /// Signatures are written as a count (`0`, `3b` or `2#`),
+    /// or as accidentals that aren't a note name (`##`),
+    /// so a lone `b` or `bb` is read as a minor tonic.
+    /// ```
+    /// use staff::{Key, Pitch};
+    ///
+    /// let key: Key = "Eb major".parse().unwrap();
+    /// assert_eq!(key.to_string(), "E♭ major");
+    ///
+    /// let key: Key = "c#".parse().unwrap();
+    /// assert_eq!(key.to_string(), "c♯ minor");
+    ///
+    /// let key: Key = "D dorian".parse().unwrap();
+    /// assert_eq!(key.fifths(), 0);
+    ///
+    /// let key: Key = "3b".parse().unwrap();
+    /// assert_eq!(key, Key::major(Pitch::DSharp));
+    ///
+    /// let key: Key = "bb".parse().unwrap();
+    /// assert_eq!(key.to_string(), "b♭ minor");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let digits = s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 {
+            let (count, accidental) = s.split_at(digits);
+            let count: i8 = count.parse().map_err(|_| Error::InvalidSignature)?;
+            let fifths = match accidental {
+                "" if count == 0 => 0,
+                "#" | "♯" => count,
+                "b" | "♭" => -count,
+                _ => return Err(Error::InvalidSignature),
+            };
+            return Self::from_signature(fifths);
+        }
+
+        let (tonic, mode) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let root: Note = match tonic.parse() {
+            Ok(root) => root,
+            Err(_) if mode.is_empty() && tonic.chars().all(|c| c == '#' || c == '♯') => {
+                return Self::from_signature(tonic.chars().count() as i8);
+            }
+            Err(_) if mode.is_empty() && tonic.chars().all(|c| c == 'b' || c == '♭') => {
+                return Self::from_signature(-(tonic.chars().count() as i8));
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let mode = mode.trim();
+
+        let mode = if mode.is_empty() {
+            if tonic.starts_with(|c: char| c.is_lowercase()) {
+                Mode::Minor
+            } else {
+                Mode::Major
+            }
+        } else {
+            ScaleIntervals::from_name(mode)
+                .and_then(Mode::from_intervals)
+                .ok_or(Error::UnknownMode)?
+        };
+
+        Ok(Self::new(root, mode))
+    }
+}
+
+/// Error parsing a key.
+#[derive(Debug)]
+pub enum Error {
+    Empty,
+    Note(note::Error),
+    UnknownMode,
+    InvalidSignature,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Empty key input"),
+            Self::Note(error) => write!(f, "Invalid tonic: {}", error),
+            Self::UnknownMode => f.write_str("Unknown mode, expected a mode of the major scale"),
+            Self::InvalidSignature => {
+                f.write_str("Invalid key signature, expected up to 7 sharps or flats")
+            }
+        }
+    }
+}
+
+impl From<note::Error> for Error {
+    fn from(error: note::Error) -> Self {
+        Self::Note(error)
+    }
+}
+
+pub struct Iter {
+    natural: Natural,
+    remaining: u8,
+    step: u8,
+}
+
+impl Iterator for Iter {
+    type Item = Natural;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining > 0 {
+            let natural = self.natural;
+
+            self.natural = natural + self.step;
+            self.remaining -= 1;
+
+            Some(natural)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_iters_sharps_for_d_major() {
+        let key = Key::major(Pitch::D);
+        let sharps: Vec<_> = key.into_iter().collect();
+        assert_eq!(sharps, [Natural::F, Natural::C]);
+    }
+
+    #[test]
+    fn it_iters_flats_for_e_flat_major() {
+        let key = Key::major(Pitch::DSharp);
+        let sharps: Vec<_> = key.into_iter().collect();
+        assert_eq!(sharps, [Natural::B, Natural::E, Natural::A]);
+    }
+
+    #[test]
+    fn it_returns_the_accidental_for_d_in_c_sharp_major() {
+        let key = Key::major(Pitch::CSharp);
+
+        let accidental = key.accidental(Natural::D);
+        assert_eq!(accidental, Accidental::Flat);
+    }
+
+    #[test]
+    fn it_parses_its_own_display() {
+        for fifths in -7..=7 {
+            for mode in Mode::ALL {
+                let key = Key::from_fifths(fifths, mode);
+                assert_eq!(key.to_string().parse::<Key>().unwrap(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn it_parses_signatures() {
+        assert_eq!("0".parse::<Key>().unwrap(), Key::major(Pitch::C));
+        assert_eq!("1b".parse::<Key>().unwrap(), Key::major(Pitch::F));
+        assert_eq!("##".parse::<Key>().unwrap(), Key::major(Pitch::D));
+        assert_eq!("♭♭".parse::<Key>().unwrap(), Key::major(Pitch::ASharp));
+
+        // Note names take precedence over flats
+        assert_eq!("b".parse::<Key>().unwrap(), Key::minor(Pitch::B));
+        assert_eq!("bb".parse::<Key>().unwrap(), Key::minor(Pitch::ASharp));
+    }
+
+    #[test]
+    fn it_rejects_invalid_keys() {
+        assert!(matches!("8#".parse::<Key>(), Err(Error::InvalidSignature)));
+        assert!(matches!("2##".parse::<Key>(), Err(Error::InvalidSignature)));
+        assert!(matches!(
+            "########".parse::<Key>(),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(matches!(
+            "A harmonic minor".parse::<Key>(),
+            Err(Error::UnknownMode)
+        ));
+        assert!(matches!("H major".parse::<Key>(), Err(Error::Note(_))));
+    }
+
+    #[test]
+    fn it_navigates_the_circle_of_fifths() {
+        let key = Key::new(Note::A, Mode::Minor);
+
+        assert_eq!(key.dominant(), Key::new(Note::E, Mode::Minor));
+        assert_eq!(key.subdominant(), Key::new(Note::D, Mode::Minor));
+        assert_eq!(key.relative(), Key::new(Note::C, Mode::Major));
+        assert_eq!(key.parallel(), Key::new(Note::A, Mode::Major));
+        assert_eq!(
+            Key::new(Note::D, Mode::Dorian).relative(),
+            Key::new(Note::C, Mode::Major)
+        );
+    }
+
+    #[test]
+    fn it_navigates_to_enharmonic_keys_at_the_edge() {
+        let key = Key::new(Note::double_flat(Natural::F), Mode::Major);
+        assert_eq!(
+            key.subdominant(),
+            Key::new(Note::flat(Natural::A), Mode::Major)
+        );
+        assert_eq!(
+            key.dominant(),
+            Key::new(Note::double_flat(Natural::C), Mode::Major)
+        );
+
+        let key = Key::new(Note::double_sharp(Natural::B), Mode::Lydian);
+        assert_eq!(
+            key.relative(),
+            Key::new(Note::sharp(Natural::G), Mode::Major)
+        );
+
+        let key = Key::new(Note::double_flat(Natural::D), Mode::Locrian);
+        assert_eq!(
+            key.relative(),
+            Key::new(Note::flat(Natural::D), Mode::Major)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Key signature layout on a staff
+
+use super::{Iter, Key};
+use crate::{note::Accidental, Clef, Natural};
+
+/// Staff positions of the sharps in treble clef, in the order they're written.
+const TREBLE_SHARPS: [i8; 7] = [8, 5, 9, 6, 3, 7, 4];
+
+/// Staff positions of the flats in treble clef, in the order they're written.
+const TREBLE_FLATS: [i8; 7] = [4, 7, 3, 6, 2, 5, 1];
+
+/// Tenor clef keeps its sharps inside the staff instead of following the treble pattern.
+const TENOR_SHARPS: [i8; 7] = [2, 6, 3, 7, 4, 8, 5];
+
+/// An accidental of a key signature and its position on the staff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignatureAccidental {
+    pub natural: Natural,
+    pub accidental: Accidental,
+    /// Staff position counted from the bottom line, see [`Clef`].
+    pub position: i8,
+}
+
+/// Iterator over the accidentals of a key signature in the order they're written.
+///
+/// Created by [`Key::signature`].
+pub struct Signature {
+    naturals: Iter,
+    positions: [i8; 7],
+    accidental: Accidental,
+    index: usize,
+}
+
+impl Iterator for Signature {
+    type Item = SignatureAccidental;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let natural = self.naturals.next()?;
+        let position = self.positions[self.index % 7];
+
+        // Keys past 7 sharps or flats double the accidentals from the start
+        let accidental = if self.index < 7 {
+            self.accidental
+        } else if self.accidental == Accidental::Sharp {
+            Accidental::DoubleSharp
+        } else {
+            Accidental::DoubleFlat
+        };
+        self.index += 1;
+
+        Some(SignatureAccidental {
+            natural,
+            accidental,
+            position,
+        })
+    }
+}
+
+impl Key {
+    /// Returns the accidentals of this key's signature with their positions on a staff with `clef`.
+    /// ```
+    /// use staff::{Clef, Key, Natural, Pitch};
+    ///
+    /// let positions: Vec<_> = Key::major(Pitch::A)
+    ///     .signature(Clef::Treble)
+    ///     .map(|accidental| (accidental.natural, accidental.position))
+    ///     .collect();
+    ///
+    /// assert_eq!(positions, [(Natural::F, 8), (Natural::C, 5), (Natural::G, 9)]);
+    ///
+    /// let positions: Vec<_> = Key::major(Pitch::ASharp)
+    ///     .signature(Clef::Bass)
+    ///     .map(|accidental| accidental.position)
+    ///     .collect();
+    ///
+    /// assert_eq!(positions, [2, 5]);
+    /// ```
+    pub fn signature(self, clef: Clef) -> Signature {
+        let (positions, offset) = match (clef, self.is_sharp()) {
+            (Clef::Tenor, true) => (TENOR_SHARPS, 0),
+            (clef, true) => (TREBLE_SHARPS, offset(clef)),
+            (clef, false) => (TREBLE_FLATS, offset(clef)),
+        };
+
+        Signature {
+            naturals: self.into_iter(),
+            positions: positions.map(|position| position + offset),
+            accidental: if self.is_sharp() {
+                Accidental::Sharp
+            } else {
+                Accidental::Flat
+            },
+            index: 0,
+        }
+    }
+}
+
+/// Returns the staff offset of `clef` from the treble clef layout.
+fn offset(clef: Clef) -> i8 {
+    match clef {
+        Clef::Treble => 0,
+        Clef::Bass => -2,
+        Clef::Alto => -1,
+        Clef::Tenor => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::Octave;
+
+    #[test]
+    fn it_places_each_accidental_on_its_own_note() {
+        for clef in [Clef::Treble, Clef::Bass, Clef::Alto, Clef::Tenor] {
+            for fifths in [-7, 7] {
+                for accidental in Key::from_fifths(fifths, crate::key::Mode::Major).signature(clef)
+                {
+                    let (natural, octave) = clef.note(accidental.position);
+                    assert_eq!(natural, accidental.natural);
+                    assert!(octave >= Octave::TWO && octave <= Octave::FIVE);
+                }
+            }
+        }
+    }
+}
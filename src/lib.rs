@@ -18,6 +18,8 @@
 //! - `std`: Enables std, otherwise this crate will use `#![no_std]`
 //! - `parse` Enables the `staff::parse` module.
 //! - `fretboard` Enables the `staff::fretboard` module.
+//! - `musicxml` Enables the `staff::musicxml` module.
+//! - `mxl` Enables reading compressed MusicXML (`.mxl`) files in `staff::musicxml`.
 //! - `render` Enables the `staff::render` module.
 //!
 //! # Examples
@@ -61,6 +63,11 @@ pub use chord::Chord;
 
 pub mod time;
 
+pub mod clef;
+pub use clef::Clef;
+
+pub mod edo;
+
 pub mod fmt;
 pub use fmt::Format;
 
@@ -71,7 +78,7 @@ pub mod fretboard;
 mod interval;
 pub use interval::Interval;
 
-mod key;
+pub mod key;
 pub use crate::key::Key;
 
 mod natural;
@@ -79,6 +86,10 @@ pub use natural::Natural;
 
 pub mod midi;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "musicxml")))]
+#[cfg(feature = "musicxml")]
+pub mod musicxml;
+
 pub mod note;
 pub use note::Note;
 
@@ -92,9 +103,17 @@ pub mod render;
 pub mod scale;
 pub use scale::Scale;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod score;
+
 pub mod set;
 pub use set::Set;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod tuning;
+
 #[cfg_attr(docsrs, doc(cfg(feature = "synth")))]
 #[cfg(feature = "synth")]
 pub mod synth;
@@ -0,0 +1,589 @@
+//! MIDI 1.0 messages
+//!
+//! ```
+//! use staff::midi::message::{Message, NoteMessage};
+//! use staff::midi;
+//!
+//! let (message, len) = Message::decode(&[0x91, 60, 100]).unwrap();
+//! assert_eq!(message, Message::NoteOn(NoteMessage::new(1, midi!(C, 4), 100)));
+//! assert_eq!(len, 3);
+//!
+//! // A note on with a velocity of 0 is a note off
+//! let (message, _) = Message::decode(&[0x91, 60, 0]).unwrap();
+//! assert_eq!(message, Message::NoteOff(NoteMessage::new(1, midi!(C, 4), 0)));
+//!
+//! let bend = Message::PitchBend { channel: 0, value: 0x2000 };
+//! assert!(bend.bytes().eq([0xE0, 0x00, 0x40]));
+//! ```
+
+use super::MidiNote;
+use core::{fmt, iter::FusedIterator};
+
+mod parser;
+pub use parser::Parser;
+
+/// Status byte of the start of a system exclusive message.
+const SYSEX: u8 = 0xF0;
+
+/// Status byte of the end of a system exclusive message.
+const EOX: u8 = 0xF7;
+
+/// A note message on a channel from 0 to 15.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteMessage {
+    pub channel: u8,
+    pub note: MidiNote,
+    pub velocity: u8,
+}
+
+impl NoteMessage {
+    pub const fn new(channel: u8, note: MidiNote, velocity: u8) -> Self {
+        Self {
+            channel,
+            note,
+            velocity,
+        }
+    }
+}
+
+/// A MIDI 1.0 message.
+///
+/// Channels are numbered from 0 to 15 and data values are 7 bits,
+/// so encoding a message ignores any higher bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Message<'a> {
+    NoteOff(NoteMessage),
+    NoteOn(NoteMessage),
+    /// Polyphonic key pressure of a held note.
+    PolyPressure {
+        channel: u8,
+        note: MidiNote,
+        pressure: u8,
+    },
+    /// Control change for controllers 0 to 119.
+    ControlChange {
+        channel: u8,
+        control: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    /// Pressure of every held note on a channel.
+    ChannelPressure {
+        channel: u8,
+        pressure: u8,
+    },
+    PitchBend {
+        channel: u8,
+        /// 14-bit bend from 0 to 16383, centered at 8192.
+        value: u16,
+    },
+    /// Channel mode message, sent as a control change of controllers 120 to 127.
+    ChannelMode {
+        channel: u8,
+        mode: ChannelMode,
+    },
+    /// System exclusive data between the `0xF0` status and the `0xF7` end,
+    /// starting with the manufacturer ID.
+    SysEx(&'a [u8]),
+    /// MIDI time code quarter frame, with the message type and value as its data byte.
+    TimeCodeQuarterFrame(u8),
+    /// 14-bit song position in sixteenth notes from the start.
+    SongPosition(u16),
+    SongSelect(u8),
+    TuneRequest,
+    RealTime(RealTime),
+}
+
+impl<'a> Message<'a> {
+    /// Decode the message at the start of `bytes`,
+    /// returning it with the number of bytes it takes.
+    ///
+    /// A note on with a velocity of 0 decodes as a [`Message::NoteOff`].
+    /// ```
+    /// use staff::midi::message::{Error, Message, RealTime};
+    ///
+    /// let bytes = [0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7, 0xFA];
+    /// let (message, len) = Message::decode(&bytes).unwrap();
+    /// assert_eq!(message, Message::SysEx(&[0x7E, 0x7F, 0x06, 0x01]));
+    ///
+    /// let (message, _) = Message::decode(&bytes[len..]).unwrap();
+    /// assert_eq!(message, Message::RealTime(RealTime::Start));
+    ///
+    /// assert_eq!(
+    ///     Message::decode(&[0xB0, 7]),
+    ///     Err(Error::Incomplete { expected: 3, found: 2 })
+    /// );
+    /// ```
+    pub fn decode(bytes: &'a [u8]) -> Result<(Self, usize), Error> {
+        let status = *bytes.first().ok_or(Error::Empty)?;
+        if status < 0x80 {
+            return Err(Error::MissingStatus(status));
+        }
+
+        if let Some(real_time) = RealTime::from_status(status) {
+            return Ok((Self::RealTime(real_time), 1));
+        }
+
+        if status == SYSEX {
+            let end = bytes[1..]
+                .iter()
+                .position(|byte| *byte >= 0x80)
+                .ok_or(Error::UnterminatedSysEx)?
+                + 1;
+            return match bytes[end] {
+                EOX => Ok((Self::SysEx(&bytes[1..end]), end + 1)),
+                byte => Err(Error::InvalidData(byte)),
+            };
+        }
+
+        let len = Self::data_len(status).ok_or(Error::UndefinedStatus(status))?;
+        if bytes.len() <= len {
+            return Err(Error::Incomplete {
+                expected: len + 1,
+                found: bytes.len(),
+            });
+        }
+
+        let mut data = [0; 2];
+        for (byte, slot) in bytes[1..=len].iter().zip(&mut data) {
+            if *byte >= 0x80 {
+                return Err(Error::InvalidData(*byte));
+            }
+            *slot = *byte;
+        }
+
+        Ok((Self::from_data(status, data), len + 1))
+    }
+
+    /// Returns the message of a `status` other than system exclusive and its `data`.
+    pub(crate) fn from_data(status: u8, data: [u8; 2]) -> Self {
+        match status {
+            0xF1 => return Self::TimeCodeQuarterFrame(data[0]),
+            0xF2 => return Self::SongPosition(u14(data)),
+            0xF3 => return Self::SongSelect(data[0]),
+            0xF0..=0xFF => {
+                return RealTime::from_status(status).map_or(Self::TuneRequest, Self::RealTime)
+            }
+            _ => {}
+        }
+
+        let channel = status & 0x0F;
+        let note = |velocity| NoteMessage::new(channel, MidiNote::from_byte(data[0]), velocity);
+
+        match status >> 4 {
+            0x8 => Self::NoteOff(note(data[1])),
+            0x9 if data[1] == 0 => Self::NoteOff(note(0)),
+            0x9 => Self::NoteOn(note(data[1])),
+            0xA => Self::PolyPressure {
+                channel,
+                note: MidiNote::from_byte(data[0]),
+                pressure: data[1],
+            },
+            0xB => match ChannelMode::from_control(data[0], data[1]) {
+                Some(mode) => Self::ChannelMode { channel, mode },
+                None => Self::ControlChange {
+                    channel,
+                    control: data[0],
+                    value: data[1],
+                },
+            },
+            0xC => Self::ProgramChange {
+                channel,
+                program: data[0],
+            },
+            0xD => Self::ChannelPressure {
+                channel,
+                pressure: data[0],
+            },
+            _ => Self::PitchBend {
+                channel,
+                value: u14(data),
+            },
+        }
+    }
+
+    /// Returns the number of data bytes after a `status` byte,
+    /// or `None` for undefined statuses and system exclusive messages.
+    pub fn data_len(status: u8) -> Option<usize> {
+        match status {
+            0x80..=0xBF | 0xE0..=0xEF | 0xF2 => Some(2),
+            0xC0..=0xDF | 0xF1 | 0xF3 => Some(1),
+            0xF6 | 0xF8 | 0xFA..=0xFC | 0xFE | 0xFF => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Returns the status byte of this message.
+    pub fn status(&self) -> u8 {
+        let channel_status = |kind: u8, channel: u8| kind << 4 | (channel & 0x0F);
+        match *self {
+            Self::NoteOff(note) => channel_status(0x8, note.channel),
+            Self::NoteOn(note) => channel_status(0x9, note.channel),
+            Self::PolyPressure { channel, .. } => channel_status(0xA, channel),
+            Self::ControlChange { channel, .. } | Self::ChannelMode { channel, .. } => {
+                channel_status(0xB, channel)
+            }
+            Self::ProgramChange { channel, .. } => channel_status(0xC, channel),
+            Self::ChannelPressure { channel, .. } => channel_status(0xD, channel),
+            Self::PitchBend { channel, .. } => channel_status(0xE, channel),
+            Self::SysEx(_) => SYSEX,
+            Self::TimeCodeQuarterFrame(_) => 0xF1,
+            Self::SongPosition(_) => 0xF2,
+            Self::SongSelect(_) => 0xF3,
+            Self::TuneRequest => 0xF6,
+            Self::RealTime(real_time) => real_time.status(),
+        }
+    }
+
+    /// Returns the channel of a channel voice or mode message.
+    pub fn channel(&self) -> Option<u8> {
+        match *self {
+            Self::NoteOff(note) | Self::NoteOn(note) => Some(note.channel),
+            Self::PolyPressure { channel, .. }
+            | Self::ControlChange { channel, .. }
+            | Self::ProgramChange { channel, .. }
+            | Self::ChannelPressure { channel, .. }
+            | Self::PitchBend { channel, .. }
+            | Self::ChannelMode { channel, .. } => Some(channel),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the bytes of this message.
+    /// ```
+    /// use staff::midi::message::{ChannelMode, Message};
+    ///
+    /// let message = Message::ChannelMode { channel: 2, mode: ChannelMode::AllNotesOff };
+    /// assert!(message.bytes().eq([0xB2, 123, 0]));
+    ///
+    /// let sysex = Message::SysEx(&[0x43, 0x10]);
+    /// assert_eq!(sysex.bytes().len(), 4);
+    /// ```
+    pub fn bytes(&self) -> Bytes<'a> {
+        let status = self.status();
+        let (head, head_len, data): ([u8; 3], u8, &'a [u8]) = match *self {
+            Self::NoteOff(note) | Self::NoteOn(note) => {
+                ([status, note.note.into_byte(), note.velocity], 3, &[])
+            }
+            Self::PolyPressure { note, pressure, .. } => {
+                ([status, note.into_byte(), pressure], 3, &[])
+            }
+            Self::ControlChange { control, value, .. } => ([status, control, value], 3, &[]),
+            Self::ChannelMode { mode, .. } => {
+                let (control, value) = mode.control();
+                ([status, control, value], 3, &[])
+            }
+            Self::ProgramChange { program: byte, .. }
+            | Self::ChannelPressure { pressure: byte, .. }
+            | Self::TimeCodeQuarterFrame(byte)
+            | Self::SongSelect(byte) => ([status, byte, 0], 2, &[]),
+            Self::PitchBend { value, .. } | Self::SongPosition(value) => {
+                ([status, value as u8, (value >> 7) as u8], 3, &[])
+            }
+            Self::SysEx(data) => ([status, 0, 0], 1, data),
+            Self::TuneRequest | Self::RealTime(_) => ([status, 0, 0], 1, &[]),
+        };
+
+        let mut head = head;
+        for byte in &mut head[1..] {
+            *byte &= 0x7F;
+        }
+        Bytes {
+            head,
+            head_len,
+            data,
+            is_sysex: status == SYSEX,
+            index: 0,
+        }
+    }
+}
+
+/// Returns the 14-bit value of a least and most significant data byte.
+fn u14(data: [u8; 2]) -> u16 {
+    data[0] as u16 | (data[1] as u16) << 7
+}
+
+/// A channel mode message, sent as a control change of controllers 120 to 127.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelMode {
+    AllSoundOff,
+    ResetAllControllers,
+    /// Whether the instrument's keyboard plays its own sounds.
+    LocalControl(bool),
+    AllNotesOff,
+    OmniOff,
+    OmniOn,
+    /// Mono mode with a number of channels, or 0 for as many as there are voices.
+    MonoOn(u8),
+    PolyOn,
+}
+
+impl ChannelMode {
+    /// Returns the channel mode of a control change of `control` to `value`,
+    /// or `None` if `control` is below 120.
+    pub fn from_control(control: u8, value: u8) -> Option<Self> {
+        let mode = match control {
+            120 => Self::AllSoundOff,
+            121 => Self::ResetAllControllers,
+            122 => Self::LocalControl(value >= 64),
+            123 => Self::AllNotesOff,
+            124 => Self::OmniOff,
+            125 => Self::OmniOn,
+            126 => Self::MonoOn(value),
+            127 => Self::PolyOn,
+            _ => return None,
+        };
+        Some(mode)
+    }
+
+    /// Returns the controller and value of this channel mode.
+    pub fn control(self) -> (u8, u8) {
+        match self {
+            Self::AllSoundOff => (120, 0),
+            Self::ResetAllControllers => (121, 0),
+            Self::LocalControl(is_on) => (122, if is_on { 127 } else { 0 }),
+            Self::AllNotesOff => (123, 0),
+            Self::OmniOff => (124, 0),
+            Self::OmniOn => (125, 0),
+            Self::MonoOn(channels) => (126, channels),
+            Self::PolyOn => (127, 0),
+        }
+    }
+}
+
+/// A system real-time message of a single status byte,
+/// which can be sent between the bytes of any other message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RealTime {
+    /// Sent 24 times per quarter note.
+    TimingClock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+    Reset,
+}
+
+impl RealTime {
+    /// Returns the real-time message of a `status` byte.
+    pub fn from_status(status: u8) -> Option<Self> {
+        let real_time = match status {
+            0xF8 => Self::TimingClock,
+            0xFA => Self::Start,
+            0xFB => Self::Continue,
+            0xFC => Self::Stop,
+            0xFE => Self::ActiveSensing,
+            0xFF => Self::Reset,
+            _ => return None,
+        };
+        Some(real_time)
+    }
+
+    pub fn status(self) -> u8 {
+        match self {
+            Self::TimingClock => 0xF8,
+            Self::Start => 0xFA,
+            Self::Continue => 0xFB,
+            Self::Stop => 0xFC,
+            Self::ActiveSensing => 0xFE,
+            Self::Reset => 0xFF,
+        }
+    }
+}
+
+/// Error decoding a [`Message`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    Empty,
+    /// A data byte where a status byte was expected.
+    MissingStatus(u8),
+    /// A status byte without a message, such as `0xF4` or a lone `0xF7`.
+    UndefinedStatus(u8),
+    /// A byte with its top bit set in the data of a message.
+    InvalidData(u8),
+    /// Fewer bytes than the message needs, including its status.
+    Incomplete {
+        expected: usize,
+        found: usize,
+    },
+    /// A system exclusive message without its `0xF7` end.
+    UnterminatedSysEx,
+    /// A system exclusive message with more data than the buffer of a [`Parser`].
+    SysExOverflow {
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Empty message"),
+            Self::MissingStatus(byte) => write!(f, "Data byte {:#04X} without a status", byte),
+            Self::UndefinedStatus(status) => write!(f, "Undefined status byte {:#04X}", status),
+            Self::InvalidData(byte) => write!(f, "Invalid data byte {:#04X}", byte),
+            Self::Incomplete { expected, found } => {
+                write!(f, "Expected {} bytes but found {}", expected, found)
+            }
+            Self::UnterminatedSysEx => f.write_str("System exclusive message without an end"),
+            Self::SysExOverflow { capacity } => {
+                write!(f, "System exclusive message over {} bytes", capacity)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Iterator over the bytes of a [`Message`].
+///
+/// Created by [`Message::bytes`].
+#[derive(Clone, Debug)]
+pub struct Bytes<'a> {
+    head: [u8; 3],
+    head_len: u8,
+    /// System exclusive data after the head.
+    data: &'a [u8],
+    /// Whether to end with `0xF7` after the data.
+    is_sysex: bool,
+    index: usize,
+}
+
+impl Iterator for Bytes<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let head_len = self.head_len as usize;
+        let byte = if self.index < head_len {
+            self.head[self.index]
+        } else if let Some(byte) = self.data.get(self.index - head_len) {
+            byte & 0x7F
+        } else if self.is_sysex && self.index == head_len + self.data.len() {
+            EOX
+        } else {
+            return None;
+        };
+
+        self.index += 1;
+        Some(byte)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.head_len as usize + self.data.len() + self.is_sysex as usize;
+        let remaining = len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Bytes<'_> {}
+
+impl FusedIterator for Bytes<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_every_message() {
+        let note = NoteMessage::new(15, MidiNote::from_byte(127), 1);
+        let messages = [
+            Message::NoteOff(note),
+            Message::NoteOn(note),
+            Message::PolyPressure {
+                channel: 3,
+                note: MidiNote::from_byte(64),
+                pressure: 90,
+            },
+            Message::ControlChange {
+                channel: 0,
+                control: 64,
+                value: 127,
+            },
+            Message::ProgramChange {
+                channel: 9,
+                program: 0,
+            },
+            Message::ChannelPressure {
+                channel: 1,
+                pressure: 42,
+            },
+            Message::PitchBend {
+                channel: 4,
+                value: 0x3FFF,
+            },
+            Message::ChannelMode {
+                channel: 5,
+                mode: ChannelMode::LocalControl(true),
+            },
+            Message::ChannelMode {
+                channel: 5,
+                mode: ChannelMode::MonoOn(4),
+            },
+            Message::SysEx(&[0x41, 0x10, 0x42]),
+            Message::SysEx(&[]),
+            Message::TimeCodeQuarterFrame(0x35),
+            Message::SongPosition(1000),
+            Message::SongSelect(3),
+            Message::TuneRequest,
+            Message::RealTime(RealTime::TimingClock),
+            Message::RealTime(RealTime::Reset),
+        ];
+
+        for message in messages {
+            let mut buf = [0; 8];
+            let len = message.bytes().len();
+            for (slot, byte) in buf.iter_mut().zip(message.bytes()) {
+                *slot = byte;
+            }
+
+            assert_eq!(Message::decode(&buf[..len]), Ok((message, len)));
+        }
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        assert_eq!(Message::decode(&[]), Err(Error::Empty));
+        assert_eq!(Message::decode(&[0x40]), Err(Error::MissingStatus(0x40)));
+        assert_eq!(Message::decode(&[0xF4]), Err(Error::UndefinedStatus(0xF4)));
+        assert_eq!(Message::decode(&[0xF7]), Err(Error::UndefinedStatus(0xF7)));
+        assert_eq!(
+            Message::decode(&[0x90, 60, 0x90]),
+            Err(Error::InvalidData(0x90))
+        );
+        assert_eq!(
+            Message::decode(&[0xF2, 1]),
+            Err(Error::Incomplete {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Message::decode(&[0xF0, 0x41, 0x10]),
+            Err(Error::UnterminatedSysEx)
+        );
+        assert_eq!(
+            Message::decode(&[0xF0, 0x41, 0xF8, 0xF7]),
+            Err(Error::InvalidData(0xF8))
+        );
+    }
+
+    #[test]
+    fn it_decodes_channel_modes_from_control_changes() {
+        let (message, _) = Message::decode(&[0xB3, 123, 0]).unwrap();
+        assert_eq!(
+            message,
+            Message::ChannelMode {
+                channel: 3,
+                mode: ChannelMode::AllNotesOff
+            }
+        );
+        assert_eq!(message.channel(), Some(3));
+    }
+}
@@ -0,0 +1,232 @@
+use super::{Error, Message, RealTime, EOX, SYSEX};
+
+/// Incremental parser of [`Message`]s from a stream of bytes,
+/// such as from a serial port or a MIDI device.
+///
+/// Channel messages can leave out their status with running status,
+/// and real-time messages are parsed as they arrive, even in the middle of another message.
+/// A status byte before the end of a message drops the unfinished message,
+/// and data bytes without a status are skipped until the next status.
+///
+/// System exclusive data is kept in a buffer of `N` bytes,
+/// so the parser doesn't allocate.
+/// ```
+/// use staff::midi::message::{Message, Parser};
+///
+/// let mut parser = Parser::<64>::new();
+/// let mut statuses = Vec::new();
+/// let mut push = |bytes: &[u8]| {
+///     parser.push_bytes(bytes, |message| statuses.push(message.unwrap().status()))
+/// };
+///
+/// // A note on and a note off with running status, with a clock tick in the middle
+/// push(&[0x90, 60, 0xF8, 100]);
+/// push(&[60, 0]);
+/// assert_eq!(statuses, [0xF8, 0x90, 0x80]);
+///
+/// // System exclusive messages can span chunks
+/// parser.push_bytes(&[0xF0, 0x7E, 0x7F], |_| unreachable!());
+/// parser.push_bytes(&[0x09, 0x01], |_| unreachable!());
+/// assert_eq!(parser.push(0xF7), Ok(Some(Message::SysEx(&[0x7E, 0x7F, 0x09, 0x01]))));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Parser<const N: usize = 256> {
+    /// Status of the message being read, kept after channel messages for running status.
+    status: Option<u8>,
+    data: [u8; 2],
+    /// Number of data bytes read of the current message.
+    len: u8,
+    /// Number of bytes of system exclusive data read, if one is being read.
+    sysex: Option<usize>,
+    /// Whether the current system exclusive message is longer than the buffer.
+    is_overflow: bool,
+    buf: [u8; N],
+}
+
+impl<const N: usize> Parser<N> {
+    pub const fn new() -> Self {
+        Self {
+            status: None,
+            data: [0; 2],
+            len: 0,
+            sysex: None,
+            is_overflow: false,
+            buf: [0; N],
+        }
+    }
+
+    /// Parse the next `byte`,
+    /// returning the message it completes or an error for a byte that was skipped.
+    ///
+    /// A system exclusive message with more than `N` bytes of data is dropped
+    /// with [`Error::SysExOverflow`] at its end.
+    pub fn push(&mut self, byte: u8) -> Result<Option<Message<'_>>, Error> {
+        if byte >= 0xF8 {
+            // Real-time messages don't interrupt the message being read
+            return match RealTime::from_status(byte) {
+                Some(real_time) => Ok(Some(Message::RealTime(real_time))),
+                None => Err(Error::UndefinedStatus(byte)),
+            };
+        }
+
+        if byte >= 0x80 {
+            return self.push_status(byte);
+        }
+
+        if let Some(len) = &mut self.sysex {
+            match self.buf.get_mut(*len) {
+                Some(slot) => {
+                    *slot = byte;
+                    *len += 1;
+                }
+                None => self.is_overflow = true,
+            }
+            return Ok(None);
+        }
+
+        let status = self.status.ok_or(Error::MissingStatus(byte))?;
+        self.data[self.len as usize] = byte;
+        self.len += 1;
+        if (self.len as usize) < Message::data_len(status).unwrap_or(0) {
+            return Ok(None);
+        }
+
+        self.len = 0;
+        if status >= 0xF0 {
+            // System common messages can't use running status
+            self.status = None;
+        }
+        Ok(Some(Message::from_data(status, self.data)))
+    }
+
+    /// Parse each of `bytes`, calling `f` with each message or error.
+    pub fn push_bytes<F>(&mut self, bytes: &[u8], mut f: F)
+    where
+        F: FnMut(Result<Message<'_>, Error>),
+    {
+        for byte in bytes {
+            match self.push(*byte) {
+                Ok(Some(message)) => f(Ok(message)),
+                Ok(None) => {}
+                Err(error) => f(Err(error)),
+            }
+        }
+    }
+
+    /// Drop the message being read and the running status.
+    pub fn reset(&mut self) {
+        self.status = None;
+        self.len = 0;
+        self.sysex = None;
+        self.is_overflow = false;
+    }
+
+    fn push_status(&mut self, status: u8) -> Result<Option<Message<'_>>, Error> {
+        if status == EOX {
+            let len = self.sysex.ok_or(Error::UndefinedStatus(status))?;
+            let is_overflow = self.is_overflow;
+            self.reset();
+
+            if is_overflow {
+                return Err(Error::SysExOverflow { capacity: N });
+            }
+            return Ok(Some(Message::SysEx(&self.buf[..len])));
+        }
+
+        self.reset();
+        match status {
+            SYSEX => self.sysex = Some(0),
+            0xF6 => return Ok(Some(Message::TuneRequest)),
+            0xF4 | 0xF5 => return Err(Error::UndefinedStatus(status)),
+            _ => self.status = Some(status),
+        }
+        Ok(None)
+    }
+}
+
+impl<const N: usize> Default for Parser<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::{
+        message::{ChannelMode, NoteMessage},
+        MidiNote,
+    };
+
+    fn parse<const N: usize>(parser: &mut Parser<N>, bytes: &[u8]) -> [Option<u8>; 8] {
+        let mut statuses = [None; 8];
+        let mut index = 0;
+        parser.push_bytes(bytes, |message| {
+            statuses[index] = message.ok().map(|message| message.status());
+            index += 1;
+        });
+        statuses
+    }
+
+    #[test]
+    fn it_uses_running_status() {
+        let mut parser = Parser::<0>::new();
+        let c4 = MidiNote::from_byte(60);
+
+        assert_eq!(parser.push(0x93), Ok(None));
+        assert_eq!(parser.push(60), Ok(None));
+        assert_eq!(
+            parser.push(100),
+            Ok(Some(Message::NoteOn(NoteMessage::new(3, c4, 100))))
+        );
+        assert_eq!(parser.push(60), Ok(None));
+        assert_eq!(
+            parser.push(0),
+            Ok(Some(Message::NoteOff(NoteMessage::new(3, c4, 0))))
+        );
+
+        // Running status continues after real-time messages
+        assert!(parser.push(0xFE).unwrap().is_some());
+        assert_eq!(parser.push(0xB3), Ok(None));
+        assert_eq!(parser.push(121), Ok(None));
+        assert_eq!(
+            parser.push(0),
+            Ok(Some(Message::ChannelMode {
+                channel: 3,
+                mode: ChannelMode::ResetAllControllers
+            }))
+        );
+
+        // System common messages clear running status
+        assert_eq!(parser.push(0xF3), Ok(None));
+        assert_eq!(parser.push(2), Ok(Some(Message::SongSelect(2))));
+        assert_eq!(parser.push(2), Err(Error::MissingStatus(2)));
+    }
+
+    #[test]
+    fn it_resynchronizes_after_garbage() {
+        let mut parser = Parser::<4>::new();
+
+        // Stray data, an interrupted note and an undefined status before a program change
+        assert_eq!(
+            parse(&mut parser, &[0x12, 0x34, 0x90, 60, 0xF5, 0xC0, 5]),
+            [None, None, None, Some(0xC0), None, None, None, None]
+        );
+
+        // A status in the middle of system exclusive data drops it
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 1, 2, 0xF6, 0xF7, 0xF8]),
+            [Some(0xF6), None, Some(0xF8), None, None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn it_drops_sysex_over_its_capacity() {
+        let mut parser = Parser::<2>::new();
+        parser.push_bytes(&[0xF0, 1, 2, 3], |_| unreachable!());
+        assert_eq!(parser.push(0xF7), Err(Error::SysExOverflow { capacity: 2 }));
+
+        parser.push_bytes(&[0xF0, 1, 2], |_| unreachable!());
+        assert_eq!(parser.push(0xF7), Ok(Some(Message::SysEx(&[1, 2]))));
+    }
+}
@@ -13,6 +13,10 @@ impl MidiSet {
         with_midi(self.low, self.high, midi, |set, midi| set.contains(midi))
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.low.bits == 0 && self.high.bits == 0
+    }
+
     pub fn push(&mut self, midi: MidiNote) {
         self.inner(midi, |set, midi| set.push(midi))
     }
@@ -33,10 +37,10 @@ fn with_midi<T, U, F>(low: T, high: T, midi: MidiNote, f: F) -> U
 where
     F: FnOnce(T, MidiNote) -> U,
 {
-    if midi <= MidiNote::from_byte(63) {
+    if midi < MidiNote::from_byte(64) {
         f(low, midi)
     } else {
-        let byte = midi.into_byte() - 63;
+        let byte = midi.into_byte() - 64;
         f(high, MidiNote::from(byte))
     }
 }
@@ -64,7 +68,7 @@ impl Iterator for MidiSet {
         self.low.next().or_else(|| {
             self.high
                 .next()
-                .map(|midi| MidiNote::from(midi.into_byte() + 63))
+                .map(|midi| MidiNote::from(midi.into_byte() + 64))
         })
     }
 }
@@ -1,6 +1,6 @@
 //! Midi notes, octaves, and sets
 
-use crate::{Interval, Pitch};
+use crate::{note::Note, Interval, Pitch};
 use core::fmt;
 use core::ops::{Add, Sub};
 
@@ -9,9 +9,16 @@ pub use octave::Octave;
 
 pub mod message;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod smf;
+
 mod midi_set;
 pub use midi_set::MidiSet;
 
+mod tracker;
+pub use tracker::{ChannelNotes, NoteSpan, NoteTracker};
+
 /// MIDI note represented as a byte.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -33,6 +40,20 @@ impl MidiNote {
         )
     }
 
+    /// Create a new `MidiNote` from a spelled `Note` and `Octave`,
+    /// where the octave follows the natural letter.
+    /// ```
+    /// use staff::midi::{MidiNote, Octave};
+    /// use staff::{midi, Natural, Note};
+    ///
+    /// let note = MidiNote::from_note(Note::sharp(Natural::B), Octave::THREE);
+    /// assert_eq!(note, midi!(C, 4));
+    /// ```
+    pub const fn from_note(note: Note, octave: Octave) -> Self {
+        let natural = Self::new(Pitch::natural(note.natural), octave);
+        Self::from_byte((natural.into_byte() as i16 + note.accidental.semitones() as i16) as u8)
+    }
+
     /// Create a new `MidiNote` from a byte.
     pub const fn from_byte(byte: u8) -> Self {
         Self(byte)
@@ -68,11 +89,32 @@ impl MidiNote {
         Octave::from_midi(self)
     }
 
+    /// Returns the frequency of this note in Hz with A4 tuned to 440 Hz.
+    ///
+    /// See [`tuning`](crate::tuning) for other reference pitches and tuning systems.
     #[cfg(feature = "std")]
     pub fn frequency(self) -> f64 {
-        let a_midi = 69;
-        let a_freq = 440.;
-        a_freq * 2f64.powf((self.into_byte() as i8 - a_midi) as f64 / 12.)
+        use crate::tuning::{EqualTemperament, Tuning};
+
+        EqualTemperament::default().frequency(self)
+    }
+
+    /// Returns the `MidiNote` closest to `frequency` in Hz and its deviation in cents
+    /// with A4 tuned to 440 Hz.
+    /// ```
+    /// use staff::midi::MidiNote;
+    /// use staff::midi;
+    ///
+    /// let deviation = MidiNote::from_frequency(261.).unwrap();
+    ///
+    /// assert_eq!(deviation.note, midi!(C, 4));
+    /// assert!(deviation.cents < 0.);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_frequency(frequency: f64) -> Option<crate::tuning::Deviation> {
+        use crate::tuning::{EqualTemperament, Tuning};
+
+        EqualTemperament::default().nearest(frequency)
     }
 
     /// Return the byte representation of `self`.
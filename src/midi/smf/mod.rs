@@ -0,0 +1,466 @@
+//! Standard MIDI Files (`.mid`)
+//!
+//! ```
+//! use staff::midi::smf::{EventKind, MetaEvent, Smf, Timing};
+//! use staff::time::tempo::Ticks;
+//!
+//! let bytes = [
+//!     b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0x01, 0xE0, // Format 0 at 480 PPQ
+//!     b'M', b'T', b'r', b'k', 0, 0, 0, 19,
+//!     0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 120 BPM
+//!     0x00, 0x90, 60, 100, // C4 on
+//!     0x83, 0x60, 60, 0, // Running status C4 off, 480 ticks later
+//!     0x00, 0xFF, 0x2F, 0x00, // End of track
+//! ];
+//!
+//! let smf = Smf::parse(&bytes).unwrap();
+//! assert_eq!(smf.header.timing, Timing::Metrical(480));
+//!
+//! let events: Vec<_> = smf.events().collect();
+//! assert_eq!(events[0].kind, &EventKind::Meta(MetaEvent::Tempo(500_000)));
+//! assert_eq!(events[2].ticks, Ticks(480));
+//! ```
+
+use super::{message::Message, MidiNote};
+use crate::{
+    key::{Key, Mode},
+    time::{self, tempo::Ticks, DurationKind, Fraction},
+};
+use std::borrow::Cow;
+
+mod read;
+pub use read::{Error, ErrorKind};
+
+mod write;
+pub use write::{Sequence, SequenceTrack};
+
+/// A Standard MIDI File with a header and tracks of events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Smf {
+    pub header: Header,
+    pub tracks: Vec<Track>,
+}
+
+impl Smf {
+    /// Parse a Standard MIDI File from its `bytes`,
+    /// skipping any chunks that aren't tracks.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        read::parse(bytes)
+    }
+
+    /// Returns the bytes of this file, using running status for channel events
+    /// and ending each track with an end of track event if it doesn't have one.
+    ///
+    /// # Panics
+    /// Panics if a delta time or the data of an event is longer than `0x0FFFFFFF`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        write::write(self)
+    }
+
+    /// Returns the events of every track merged in order of their absolute time,
+    /// with events at the same time in track order.
+    ///
+    /// Tracks of a [`Format::Sequential`] file play one after another,
+    /// so each starts at the end of the one before it.
+    pub fn events(&self) -> Merged<'_> {
+        let mut start = Ticks::default();
+        let cursors = self
+            .tracks
+            .iter()
+            .map(|track| {
+                let ticks = start + Ticks(track.events.first().map_or(0, |e| e.delta as u64));
+                if self.header.format == Format::Sequential {
+                    start += track.length();
+                }
+                (0, ticks)
+            })
+            .collect();
+
+        Merged {
+            tracks: &self.tracks,
+            cursors,
+        }
+    }
+}
+
+/// The header chunk of a [`Smf`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    pub format: Format,
+    pub timing: Timing,
+}
+
+/// How the tracks of a [`Smf`] are played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Format {
+    /// Format 0, with a single track.
+    Single,
+    /// Format 1, with tracks played at the same time.
+    Parallel,
+    /// Format 2, with independent tracks played one after another.
+    Sequential,
+}
+
+/// The unit of delta times in a [`Smf`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Timing {
+    /// Ticks per quarter note.
+    Metrical(u16),
+    /// Subdivisions of SMPTE frames.
+    Timecode { fps: u8, subframes: u8 },
+}
+
+/// A track chunk of events.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Track {
+    pub events: Vec<TrackEvent>,
+}
+
+impl Track {
+    /// Returns the name in the first track name event of this track.
+    pub fn name(&self) -> Option<Cow<'_, str>> {
+        self.events.iter().find_map(|event| match &event.kind {
+            EventKind::Meta(MetaEvent::Text(TextKind::TrackName, text)) => {
+                Some(String::from_utf8_lossy(text))
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the sum of the delta times of this track.
+    pub fn length(&self) -> Ticks {
+        Ticks(self.events.iter().map(|event| event.delta as u64).sum())
+    }
+
+    /// Returns each event with its absolute time from the start of the track.
+    pub fn absolute(&self) -> impl Iterator<Item = (Ticks, &TrackEvent)> + '_ {
+        self.events.iter().scan(Ticks::default(), |ticks, event| {
+            *ticks += Ticks(event.delta as u64);
+            Some((*ticks, event))
+        })
+    }
+}
+
+/// An event with its delta time in ticks after the previous event of its track.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackEvent {
+    pub delta: u32,
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventKind {
+    Midi(ChannelEvent),
+    /// A system exclusive message after its `0xF0` status, usually ending with `0xF7`.
+    SysEx(Vec<u8>),
+    /// Bytes sent as they are after an `0xF7` status,
+    /// such as the next packet of a divided system exclusive message.
+    Escape(Vec<u8>),
+    Meta(MetaEvent),
+}
+
+/// A channel message, such as a note on or a control change, as its status and data bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelEvent {
+    pub status: u8,
+    /// Data bytes, where the second is zero for messages with one data byte.
+    pub data: [u8; 2],
+}
+
+impl ChannelEvent {
+    /// Create a note on event on `channel` from 0 to 15.
+    pub fn note_on(channel: u8, note: MidiNote, velocity: u8) -> Self {
+        Self {
+            status: 0x90 | (channel & 0x0F),
+            data: [note.into_byte() & 0x7F, velocity & 0x7F],
+        }
+    }
+
+    /// Create a note off event on `channel` from 0 to 15.
+    pub fn note_off(channel: u8, note: MidiNote, velocity: u8) -> Self {
+        Self {
+            status: 0x80 | (channel & 0x0F),
+            data: [note.into_byte() & 0x7F, velocity & 0x7F],
+        }
+    }
+
+    /// Returns the channel from 0 to 15.
+    pub fn channel(self) -> u8 {
+        self.status & 0x0F
+    }
+
+    /// Returns the number of data bytes after a `status` byte.
+    pub fn data_len(status: u8) -> usize {
+        match status >> 4 {
+            0xC | 0xD => 1,
+            _ => 2,
+        }
+    }
+
+    /// Create the event of a channel voice or mode `message`,
+    /// or `None` if it's a system message.
+    pub fn from_message(message: &Message) -> Option<Self> {
+        message.channel()?;
+
+        let mut bytes = message.bytes();
+        let status = bytes.next()?;
+        Some(Self {
+            status,
+            data: [bytes.next()?, bytes.next().unwrap_or(0)],
+        })
+    }
+
+    /// Returns the message of this event,
+    /// where a note on with a velocity of 0 is a [`Message::NoteOff`].
+    pub fn message(self) -> Message<'static> {
+        Message::from_data(self.status, [self.data[0] & 0x7F, self.data[1] & 0x7F])
+    }
+}
+
+/// An event with information about a track that isn't sent to instruments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetaEvent {
+    /// Number of a sequence, or `None` for the number of the track in the file.
+    SequenceNumber(Option<u16>),
+    /// Text, usually in ASCII or Latin-1.
+    Text(TextKind, Vec<u8>),
+    /// Channel of the following meta and system exclusive events.
+    ChannelPrefix(u8),
+    /// Output port of the track.
+    Port(u8),
+    EndOfTrack,
+    /// Microseconds per quarter note.
+    Tempo(u32),
+    /// SMPTE time of the start of the track in hours, minutes, seconds, frames and subframes.
+    SmpteOffset([u8; 5]),
+    TimeSignature {
+        numerator: u8,
+        /// Power of two of the denominator, such as 3 for eighths.
+        denominator: u8,
+        /// MIDI clocks per metronome click.
+        clocks: u8,
+        /// Notated 32nd notes per 24 MIDI clocks.
+        thirty_seconds: u8,
+    },
+    KeySignature {
+        /// Sharps as positive numbers and flats as negative numbers.
+        fifths: i8,
+        minor: bool,
+    },
+    SequencerSpecific(Vec<u8>),
+    Unknown {
+        kind: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl MetaEvent {
+    /// Create a tempo event of `bpm` quarter notes per minute.
+    pub fn from_bpm(bpm: f64) -> Self {
+        Self::Tempo((60_000_000. / bpm).round().clamp(1., 0xFF_FFFF as f64) as u32)
+    }
+
+    /// Create a time signature event of `meter`, with a metronome click on every beat,
+    /// or `None` if its unit is a breve.
+    /// ```
+    /// use staff::midi::smf::MetaEvent;
+    /// use staff::time::{DurationKind, TimeSignature};
+    ///
+    /// let meter = TimeSignature::new(DurationKind::Eigth, 6);
+    /// let meta = MetaEvent::from_time_signature(meter).unwrap();
+    /// assert_eq!(
+    ///     meta,
+    ///     MetaEvent::TimeSignature {
+    ///         numerator: 6,
+    ///         denominator: 3,
+    ///         clocks: 36,
+    ///         thirty_seconds: 8
+    ///     }
+    /// );
+    /// assert_eq!(meta.time_signature(), Some(meter));
+    /// ```
+    pub fn from_time_signature(meter: time::TimeSignature) -> Option<Self> {
+        let unit = meter.unit.length();
+        if unit.numer() != 1 {
+            return None;
+        }
+
+        // 24 MIDI clocks per quarter note, or 96 per whole note
+        let units = if meter.kind() == time::MeterKind::Compound {
+            3
+        } else {
+            1
+        };
+        let clocks = (96 * units / unit.denom()).clamp(1, u8::MAX as u64) as u8;
+        Some(Self::TimeSignature {
+            numerator: meter.beats,
+            denominator: unit.denom().trailing_zeros() as u8,
+            clocks,
+            thirty_seconds: 8,
+        })
+    }
+
+    /// Create a key signature event of `key`,
+    /// or `None` if neither it nor its enharmonic key has at most 7 sharps or flats.
+    ///
+    /// Modes other than minor are written as their signature in major,
+    /// so D dorian is written as C major,
+    /// and signatures with more than 7 sharps or flats are written as their enharmonic,
+    /// so G♯ major is written as A♭ major.
+    /// ```
+    /// use staff::key::Mode;
+    /// use staff::midi::smf::MetaEvent;
+    /// use staff::{Key, Natural, Note};
+    ///
+    /// let key = Key::new(Note::sharp(Natural::G), Mode::Major);
+    /// assert_eq!(
+    ///     MetaEvent::from_key(key),
+    ///     Some(MetaEvent::KeySignature { fifths: -4, minor: false })
+    /// );
+    /// ```
+    pub fn from_key(key: Key) -> Option<Self> {
+        let fifths = match key.fifths() {
+            fifths if fifths > 7 => fifths - 12,
+            fifths if fifths < -7 => fifths + 12,
+            fifths => fifths,
+        };
+        if fifths.abs() > 7 {
+            return None;
+        }
+
+        Some(Self::KeySignature {
+            fifths,
+            minor: key.mode == Mode::Minor,
+        })
+    }
+
+    /// Returns the tempo of a tempo event in beats per minute.
+    pub fn bpm(&self) -> Option<f64> {
+        match self {
+            Self::Tempo(micros) if *micros > 0 => Some(60_000_000. / *micros as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the meter of a time signature event.
+    pub fn time_signature(&self) -> Option<time::TimeSignature> {
+        match self {
+            Self::TimeSignature {
+                numerator,
+                denominator,
+                ..
+            } if *numerator > 0 && *denominator < 64 => {
+                let unit = DurationKind::from_length(Fraction::new(1, 1 << denominator))?;
+                Some(time::TimeSignature::new(unit, *numerator))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the key of a key signature event.
+    pub fn key(&self) -> Option<Key> {
+        match self {
+            Self::KeySignature { fifths, minor } if (-7..=7).contains(fifths) => {
+                let mode = if *minor { Mode::Minor } else { Mode::Major };
+                Some(Key::from_fifths(*fifths, mode))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the text of a text event, replacing bytes that aren't UTF-8.
+    pub fn text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Self::Text(_, text) => Some(String::from_utf8_lossy(text)),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of a [`MetaEvent::Text`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextKind {
+    Text,
+    Copyright,
+    TrackName,
+    InstrumentName,
+    Lyric,
+    Marker,
+    CuePoint,
+    ProgramName,
+    DeviceName,
+}
+
+impl TextKind {
+    /// Every text kind, in order of their meta event types from 1 to 9.
+    pub const ALL: [Self; 9] = [
+        Self::Text,
+        Self::Copyright,
+        Self::TrackName,
+        Self::InstrumentName,
+        Self::Lyric,
+        Self::Marker,
+        Self::CuePoint,
+        Self::ProgramName,
+        Self::DeviceName,
+    ];
+
+    /// Returns the meta event type of this kind.
+    pub fn meta_type(self) -> u8 {
+        self as u8 + 1
+    }
+}
+
+/// An event of a [`Smf`] at its absolute time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MergedEvent<'a> {
+    pub ticks: Ticks,
+    /// Index of the event's track.
+    pub track: usize,
+    pub kind: &'a EventKind,
+}
+
+/// Iterator over the events of every track of a [`Smf`] in order of their absolute time.
+///
+/// Created by [`Smf::events`].
+pub struct Merged<'a> {
+    tracks: &'a [Track],
+    /// Index and absolute time of the next event of each track.
+    cursors: Vec<(usize, Ticks)>,
+}
+
+impl<'a> Iterator for Merged<'a> {
+    type Item = MergedEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (track, (index, ticks)) = self
+            .cursors
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(track, (index, _))| *index < self.tracks[*track].events.len())
+            .min_by_key(|(_, (_, ticks))| *ticks)?;
+
+        let events = &self.tracks[track].events;
+        let next = events
+            .get(index + 1)
+            .map_or(ticks, |event| ticks + Ticks(event.delta as u64));
+        self.cursors[track] = (index + 1, next);
+
+        Some(MergedEvent {
+            ticks,
+            track,
+            kind: &events[index].kind,
+        })
+    }
+}
@@ -0,0 +1,471 @@
+use super::{
+    ChannelEvent, EventKind, Format, Header, MetaEvent, Smf, TextKind, Timing, Track, TrackEvent,
+};
+use core::fmt;
+
+/// Error parsing a Standard MIDI File, at the offset of the byte that caused it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    /// Offset in bytes from the start of the file.
+    pub offset: usize,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The file ended in the middle of a chunk or event.
+    UnexpectedEof,
+    /// The file doesn't start with an `MThd` chunk.
+    MissingHeader,
+    /// The header chunk is shorter than 6 bytes.
+    InvalidHeaderLength(u32),
+    InvalidFormat(u16),
+    /// A format 0 file without exactly one track.
+    InvalidTrackCount(u16),
+    /// The file has fewer tracks than its header lists.
+    MissingTracks {
+        expected: u16,
+        found: u16,
+    },
+    /// A variable-length quantity longer than 4 bytes.
+    InvalidVariableLength,
+    /// A data byte at the start of an event without a previous status to repeat.
+    MissingStatus,
+    /// A status byte that can't start an event in a file.
+    InvalidStatus(u8),
+    /// A data byte with its top bit set.
+    InvalidData(u8),
+    /// A meta event with the wrong length or value for its type.
+    InvalidMeta {
+        kind: u8,
+        len: u32,
+    },
+    /// An event that runs past the end of its track chunk.
+    TrackOverrun,
+    /// A track chunk that doesn't end with an end of track event.
+    MissingEndOfTrack,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => f.write_str("Unexpected end of file"),
+            Self::MissingHeader => f.write_str("Missing MThd header chunk"),
+            Self::InvalidHeaderLength(len) => write!(f, "Invalid header length {}", len),
+            Self::InvalidFormat(format) => write!(f, "Invalid format {}", format),
+            Self::InvalidTrackCount(count) => {
+                write!(f, "Format 0 file with {} tracks, expected 1", count)
+            }
+            Self::MissingTracks { expected, found } => {
+                write!(f, "Expected {} tracks but found {}", expected, found)
+            }
+            Self::InvalidVariableLength => f.write_str("Variable-length quantity over 4 bytes"),
+            Self::MissingStatus => f.write_str("Data byte without a running status"),
+            Self::InvalidStatus(status) => write!(f, "Invalid status byte {:#04X}", status),
+            Self::InvalidData(byte) => write!(f, "Invalid data byte {:#04X}", byte),
+            Self::InvalidMeta { kind, len } => {
+                write!(f, "Invalid meta event {:#04X} of length {}", kind, len)
+            }
+            Self::TrackOverrun => f.write_str("Event runs past the end of its track"),
+            Self::MissingEndOfTrack => f.write_str("Track doesn't end with an end of track event"),
+        }
+    }
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<Smf, Error> {
+    let mut reader = Reader {
+        bytes,
+        pos: 0,
+        end: bytes.len(),
+        overrun: ErrorKind::UnexpectedEof,
+    };
+
+    if reader.take(4).ok() != Some(b"MThd") {
+        return Err(reader.error_at(0, ErrorKind::MissingHeader));
+    }
+    let len = reader.u32()?;
+    if len < 6 {
+        return Err(reader.error_at(4, ErrorKind::InvalidHeaderLength(len)));
+    }
+    let header_end = reader.pos + len as usize;
+
+    let format = match reader.u16()? {
+        0 => Format::Single,
+        1 => Format::Parallel,
+        2 => Format::Sequential,
+        format => return Err(reader.error_at(8, ErrorKind::InvalidFormat(format))),
+    };
+    let count = reader.u16()?;
+    if format == Format::Single && count != 1 {
+        return Err(reader.error_at(10, ErrorKind::InvalidTrackCount(count)));
+    }
+
+    let division = reader.u16()?;
+    let timing = if division & 0x8000 == 0 {
+        Timing::Metrical(division)
+    } else {
+        // The top byte is the negative frames per second
+        Timing::Timecode {
+            fps: ((division >> 8) as i8).unsigned_abs(),
+            subframes: division as u8,
+        }
+    };
+    reader.seek(header_end)?;
+
+    let mut tracks = Vec::with_capacity(count as usize);
+    while tracks.len() < count as usize && reader.pos < bytes.len() {
+        let id = reader.take(4)?;
+        let len = reader.u32()? as usize;
+        let start = reader.pos;
+        let end = start
+            .checked_add(len)
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| reader.error_at(bytes.len(), ErrorKind::UnexpectedEof))?;
+
+        // Chunks of other types are skipped
+        if id == b"MTrk" {
+            let mut track_reader = Reader {
+                bytes,
+                pos: start,
+                end,
+                overrun: ErrorKind::TrackOverrun,
+            };
+            tracks.push(track_reader.track()?);
+        }
+        reader.pos = end;
+    }
+
+    if tracks.len() < count as usize {
+        return Err(reader.error(ErrorKind::MissingTracks {
+            expected: count,
+            found: tracks.len() as u16,
+        }));
+    }
+
+    Ok(Smf {
+        header: Header { format, timing },
+        tracks,
+    })
+}
+
+/// Reader of the bytes of a file up to `end`.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    end: usize,
+    /// Error for reading past `end`.
+    overrun: ErrorKind,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, kind: ErrorKind) -> Error {
+        self.error_at(self.pos, kind)
+    }
+
+    fn error_at(&self, offset: usize, kind: ErrorKind) -> Error {
+        Error { kind, offset }
+    }
+
+    /// Returns the error of reading past the end.
+    fn eof(&self) -> Error {
+        self.error_at(self.end, self.overrun)
+    }
+
+    fn seek(&mut self, pos: usize) -> Result<(), Error> {
+        if pos > self.end {
+            return Err(self.eof());
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.end)
+            .ok_or_else(|| self.eof())?;
+
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a variable-length quantity of 7 bits per byte,
+    /// where every byte but the last has its top bit set.
+    fn variable(&mut self) -> Result<u32, Error> {
+        let start = self.pos;
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error_at(start, ErrorKind::InvalidVariableLength))
+    }
+
+    fn data(&mut self) -> Result<u8, Error> {
+        let byte = self.u8()?;
+        if byte & 0x80 != 0 {
+            return Err(self.error_at(self.pos - 1, ErrorKind::InvalidData(byte)));
+        }
+        Ok(byte)
+    }
+
+    fn track(&mut self) -> Result<Track, Error> {
+        let mut events = Vec::new();
+        // Running status is kept through meta and system exclusive events like most readers
+        let mut running = None;
+
+        loop {
+            if self.pos == self.end {
+                return Err(self.error(ErrorKind::MissingEndOfTrack));
+            }
+
+            let delta = self.variable()?;
+            let start = self.pos;
+            let status = self.u8()?;
+
+            let kind = match status {
+                0xFF => {
+                    let kind = self.u8()?;
+                    let len = self.variable()?;
+                    let data = self.take(len as usize)?;
+                    let meta = meta(kind, data).ok_or_else(|| {
+                        self.error_at(start, ErrorKind::InvalidMeta { kind, len })
+                    })?;
+                    EventKind::Meta(meta)
+                }
+                0xF0 => {
+                    let len = self.variable()?;
+                    EventKind::SysEx(self.take(len as usize)?.to_vec())
+                }
+                0xF7 => {
+                    let len = self.variable()?;
+                    EventKind::Escape(self.take(len as usize)?.to_vec())
+                }
+                0xF1..=0xFE => return Err(self.error_at(start, ErrorKind::InvalidStatus(status))),
+                0x80..=0xEF => {
+                    running = Some(status);
+                    let mut data = [self.data()?, 0];
+                    if ChannelEvent::data_len(status) == 2 {
+                        data[1] = self.data()?;
+                    }
+                    EventKind::Midi(ChannelEvent { status, data })
+                }
+                first => {
+                    let status =
+                        running.ok_or_else(|| self.error_at(start, ErrorKind::MissingStatus))?;
+                    let mut data = [first, 0];
+                    if ChannelEvent::data_len(status) == 2 {
+                        data[1] = self.data()?;
+                    }
+                    EventKind::Midi(ChannelEvent { status, data })
+                }
+            };
+
+            let is_end = kind == EventKind::Meta(MetaEvent::EndOfTrack);
+            events.push(TrackEvent { delta, kind });
+            if is_end {
+                // Anything after the end of track is padding
+                return Ok(Track { events });
+            }
+        }
+    }
+}
+
+/// Returns the meta event of `kind` with `data`, or `None` if it's invalid.
+fn meta(kind: u8, data: &[u8]) -> Option<MetaEvent> {
+    let meta = match (kind, data) {
+        (0x00, []) => MetaEvent::SequenceNumber(None),
+        (0x00, [high, low]) => MetaEvent::SequenceNumber(Some(u16::from_be_bytes([*high, *low]))),
+        (0x01..=0x09, text) => MetaEvent::Text(TextKind::ALL[kind as usize - 1], text.to_vec()),
+        (0x20, [channel]) if *channel < 16 => MetaEvent::ChannelPrefix(*channel),
+        (0x21, [port]) => MetaEvent::Port(*port),
+        (0x2F, []) => MetaEvent::EndOfTrack,
+        (0x51, [a, b, c]) => MetaEvent::Tempo(u32::from_be_bytes([0, *a, *b, *c])),
+        (0x54, [a, b, c, d, e]) => MetaEvent::SmpteOffset([*a, *b, *c, *d, *e]),
+        (0x58, [numerator, denominator, clocks, thirty_seconds]) => MetaEvent::TimeSignature {
+            numerator: *numerator,
+            denominator: *denominator,
+            clocks: *clocks,
+            thirty_seconds: *thirty_seconds,
+        },
+        (0x59, [fifths, minor @ (0 | 1)]) => MetaEvent::KeySignature {
+            fifths: *fifths as i8,
+            minor: *minor == 1,
+        },
+        (0x7F, data) => MetaEvent::SequencerSpecific(data.to_vec()),
+        (0x00 | 0x20 | 0x21 | 0x2F | 0x51 | 0x54 | 0x58 | 0x59, _) => return None,
+        (kind, data) => MetaEvent::Unknown {
+            kind,
+            data: data.to_vec(),
+        },
+    };
+    Some(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::message::Message,
+        time::{tempo::Ticks, DurationKind, TimeSignature},
+        Key, Pitch,
+    };
+
+    fn file(format: u16, tracks: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = b"MThd\0\0\0\x06".to_vec();
+        bytes.extend(format.to_be_bytes());
+        bytes.extend((tracks.len() as u16).to_be_bytes());
+        bytes.extend(96u16.to_be_bytes());
+        for track in tracks {
+            bytes.extend(b"MTrk");
+            bytes.extend((track.len() as u32).to_be_bytes());
+            bytes.extend(*track);
+        }
+        bytes
+    }
+
+    #[test]
+    fn it_merges_parallel_tracks() {
+        let conductor: &[u8] = &[
+            0x00, 0xFF, 0x03, 0x04, b'S', b'o', b'n', b'g', // Track name
+            0x00, 0xFF, 0x58, 0x04, 0x06, 0x03, 0x24, 0x08, // 6/8
+            0x00, 0xFF, 0x59, 0x02, 0xFD, 0x01, // c minor
+            0x81, 0x40, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 60 BPM at 192 ticks
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let notes: &[u8] = &[
+            0x00, 0xF0, 0x03, 0x7E, 0x7F, 0xF7, // Sysex
+            0x60, 0x91, 0x3C, 0x40, // C4 on channel 2 at 96 ticks
+            0x81, 0x00, 0x3C, 0x00, // Off with running status at 224 ticks
+            0x00, 0xC1, 0x05, // Program change
+            0x00, 0xFF, 0x2F, 0x00, 0x00, // Padding after the end
+        ];
+        let smf = Smf::parse(&file(1, &[conductor, notes])).unwrap();
+        assert_eq!(smf.header.format, Format::Parallel);
+        assert_eq!(smf.tracks[0].name().as_deref(), Some("Song"));
+
+        let meta = |index: usize| match &smf.tracks[0].events[index].kind {
+            EventKind::Meta(meta) => meta.clone(),
+            kind => panic!("expected a meta event, found {:?}", kind),
+        };
+        assert_eq!(
+            meta(1).time_signature(),
+            Some(TimeSignature::new(DurationKind::Eigth, 6))
+        );
+        assert_eq!(meta(2).key(), Some(Key::minor(Pitch::C)));
+        assert_eq!(meta(3).bpm(), Some(60.));
+
+        let merged: Vec<_> = smf
+            .events()
+            .map(|event| (event.ticks.0, event.track))
+            .collect();
+        assert_eq!(
+            merged,
+            [
+                (0, 0),
+                (0, 0),
+                (0, 0),
+                (0, 1),
+                (96, 1),
+                (192, 0),
+                (192, 0),
+                (224, 1),
+                (224, 1),
+                (224, 1)
+            ]
+        );
+
+        let EventKind::Midi(on) = smf.tracks[1].events[1].kind else {
+            panic!("expected a note on")
+        };
+        assert_eq!(on.channel(), 1);
+        assert!(matches!(on.message(), Message::NoteOn(note) if note.velocity == 0x40));
+        assert_eq!(
+            smf.tracks[1].events[3].kind,
+            EventKind::Midi(ChannelEvent {
+                status: 0xC1,
+                data: [5, 0]
+            })
+        );
+    }
+
+    #[test]
+    fn it_plays_sequential_tracks_in_order() {
+        let track: &[u8] = &[0x10, 0x90, 0x3C, 0x40, 0x10, 0xFF, 0x2F, 0x00];
+        let smf = Smf::parse(&file(2, &[track, track])).unwrap();
+
+        let ticks: Vec<_> = smf.events().map(|event| event.ticks).collect();
+        assert_eq!(ticks, [Ticks(16), Ticks(32), Ticks(48), Ticks(64)]);
+    }
+
+    #[test]
+    fn it_reports_errors_with_offsets() {
+        let error = |format, track: &[u8]| Smf::parse(&file(format, &[track])).unwrap_err();
+
+        assert_eq!(
+            error(0, &[0x00, 0x3C, 0x40]),
+            Error {
+                kind: ErrorKind::MissingStatus,
+                offset: 23
+            }
+        );
+        assert_eq!(
+            error(0, &[0x00, 0x90, 0x3C, 0x40]).kind,
+            ErrorKind::MissingEndOfTrack
+        );
+        assert_eq!(
+            error(0, &[0xFF, 0xFF, 0xFF, 0xFF, 0x00]),
+            Error {
+                kind: ErrorKind::InvalidVariableLength,
+                offset: 22
+            }
+        );
+        assert_eq!(
+            error(0, &[0x00, 0xFF, 0x51, 0x02, 0x07, 0xA1]).kind,
+            ErrorKind::InvalidMeta { kind: 0x51, len: 2 }
+        );
+        assert_eq!(error(0, &[0x00, 0x90, 0x3C]).kind, ErrorKind::TrackOverrun);
+        assert_eq!(error(3, &[]).kind, ErrorKind::InvalidFormat(3));
+
+        let mut truncated = file(1, &[&[0x00, 0xFF, 0x2F, 0x00]]);
+        truncated[11] = 2;
+        assert_eq!(
+            Smf::parse(&truncated).unwrap_err().kind,
+            ErrorKind::MissingTracks {
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            Smf::parse(b"RIFF").unwrap_err().kind,
+            ErrorKind::MissingHeader
+        );
+    }
+}
@@ -0,0 +1,564 @@
+use super::{
+    ChannelEvent, EventKind, Format, Header, MetaEvent, Smf, TextKind, Timing, Track, TrackEvent,
+};
+use crate::{
+    midi::MidiNote,
+    time::{
+        quantize::PerformedNote,
+        tempo::{Position, Ramp, TempoMap, Ticks},
+        Duration,
+    },
+    Chord, Key,
+};
+use std::borrow::Cow;
+
+/// Largest value of a variable-length quantity.
+const MAX_VARIABLE: u32 = 0x0FFF_FFFF;
+
+pub(super) fn write(smf: &Smf) -> Vec<u8> {
+    let mut bytes = b"MThd\0\0\0\x06".to_vec();
+    let format: u16 = match smf.header.format {
+        Format::Single => 0,
+        Format::Parallel => 1,
+        Format::Sequential => 2,
+    };
+    bytes.extend(format.to_be_bytes());
+    bytes.extend((smf.tracks.len() as u16).to_be_bytes());
+
+    let division = match smf.header.timing {
+        Timing::Metrical(ppq) => ppq & 0x7FFF,
+        // The top byte is the negative frames per second
+        Timing::Timecode { fps, subframes } => {
+            u16::from_be_bytes([(fps as i8).wrapping_neg() as u8, subframes])
+        }
+    };
+    bytes.extend(division.to_be_bytes());
+
+    for track in &smf.tracks {
+        bytes.extend(b"MTrk");
+        let start = bytes.len();
+        bytes.extend([0; 4]);
+        write_track(track, &mut bytes);
+
+        let len = (bytes.len() - start - 4) as u32;
+        bytes[start..start + 4].copy_from_slice(&len.to_be_bytes());
+    }
+
+    bytes
+}
+
+fn write_track(track: &Track, bytes: &mut Vec<u8>) {
+    let mut running = None;
+    for event in &track.events {
+        variable(event.delta, bytes);
+
+        match &event.kind {
+            EventKind::Midi(midi) => {
+                // Repeat the status of the previous channel event with running status
+                if running != Some(midi.status) {
+                    bytes.push(midi.status);
+                    running = Some(midi.status);
+                }
+                let len = ChannelEvent::data_len(midi.status);
+                bytes.extend(&midi.data[..len]);
+            }
+            EventKind::SysEx(data) | EventKind::Escape(data) => {
+                running = None;
+                let status = if matches!(event.kind, EventKind::SysEx(_)) {
+                    0xF0
+                } else {
+                    0xF7
+                };
+                bytes.push(status);
+                variable(data.len() as u32, bytes);
+                bytes.extend(data);
+            }
+            EventKind::Meta(meta) => {
+                running = None;
+                let (kind, data) = meta_bytes(meta);
+                bytes.extend([0xFF, kind]);
+                variable(data.len() as u32, bytes);
+                bytes.extend(data.iter());
+            }
+        }
+    }
+
+    let is_ended = matches!(
+        track.events.last(),
+        Some(TrackEvent {
+            kind: EventKind::Meta(MetaEvent::EndOfTrack),
+            ..
+        })
+    );
+    if !is_ended {
+        bytes.extend([0x00, 0xFF, 0x2F, 0x00]);
+    }
+}
+
+/// Write a variable-length quantity of 7 bits per byte,
+/// where every byte but the last has its top bit set.
+fn variable(value: u32, bytes: &mut Vec<u8>) {
+    assert!(
+        value <= MAX_VARIABLE,
+        "variable-length quantity {} over 4 bytes",
+        value
+    );
+
+    let mut shift = 21;
+    while shift > 0 && value >> shift == 0 {
+        shift -= 7;
+    }
+    while shift > 0 {
+        bytes.push((value >> shift) as u8 | 0x80);
+        shift -= 7;
+    }
+    bytes.push(value as u8 & 0x7F);
+}
+
+/// Returns the type and data of a `meta` event.
+fn meta_bytes(meta: &MetaEvent) -> (u8, Cow<'_, [u8]>) {
+    match meta {
+        MetaEvent::SequenceNumber(None) => (0x00, Cow::Borrowed(&[])),
+        MetaEvent::SequenceNumber(Some(number)) => {
+            (0x00, Cow::Owned(number.to_be_bytes().to_vec()))
+        }
+        MetaEvent::Text(kind, text) => (kind.meta_type(), Cow::Borrowed(text)),
+        MetaEvent::ChannelPrefix(channel) => (0x20, Cow::Owned(vec![*channel])),
+        MetaEvent::Port(port) => (0x21, Cow::Owned(vec![*port])),
+        MetaEvent::EndOfTrack => (0x2F, Cow::Borrowed(&[])),
+        MetaEvent::Tempo(micros) => (0x51, Cow::Owned(micros.to_be_bytes()[1..].to_vec())),
+        MetaEvent::SmpteOffset(offset) => (0x54, Cow::Borrowed(offset)),
+        MetaEvent::TimeSignature {
+            numerator,
+            denominator,
+            clocks,
+            thirty_seconds,
+        } => (
+            0x58,
+            Cow::Owned(vec![*numerator, *denominator, *clocks, *thirty_seconds]),
+        ),
+        MetaEvent::KeySignature { fifths, minor } => {
+            (0x59, Cow::Owned(vec![*fifths as u8, *minor as u8]))
+        }
+        MetaEvent::SequencerSpecific(data) => (0x7F, Cow::Borrowed(data)),
+        MetaEvent::Unknown { kind, data } => (*kind, Cow::Borrowed(data)),
+    }
+}
+
+/// Notes on tracks with a tempo map and key, to write as a format 1 [`Smf`].
+///
+/// The first track of the file is a conductor track with the title, key, tempo and time signatures,
+/// followed by a track for each [`SequenceTrack`].
+/// ```
+/// use staff::midi::smf::{Sequence, SequenceTrack, Smf};
+/// use staff::time::tempo::TempoMap;
+/// use staff::time::{Duration, DurationKind};
+/// use staff::{midi, Chord, Key, Pitch, Scale};
+///
+/// let sequence = Sequence::new(TempoMap::new(480, 96.)).with_key(Key::major(Pitch::D));
+/// let chords = sequence.progression([Chord::major(midi!(D, 3)), Chord::major(midi!(A, 3))], 80);
+/// let scale = sequence.scale(
+///     Scale::major(midi!(D, 4)),
+///     Duration::from(DurationKind::Eigth),
+///     100,
+/// );
+///
+/// let sequence = sequence
+///     .with_track(SequenceTrack::new(0).with_name("Chords").with_notes(chords))
+///     .with_track(SequenceTrack::new(1).with_program(73).with_notes(scale));
+///
+/// let smf = Smf::parse(&sequence.to_bytes()).unwrap();
+/// assert_eq!(smf.tracks.len(), 3);
+/// assert_eq!(smf.tracks[1].name().as_deref(), Some("Chords"));
+/// assert_eq!(smf, sequence.to_smf());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sequence {
+    /// Tempo and time signature changes, and the PPQ of the file.
+    pub tempo: TempoMap,
+    pub key: Option<Key>,
+    pub title: Option<String>,
+    pub tracks: Vec<SequenceTrack>,
+}
+
+impl Sequence {
+    pub fn new(tempo: TempoMap) -> Self {
+        Self {
+            tempo,
+            key: None,
+            title: None,
+            tracks: Vec::new(),
+        }
+    }
+
+    pub fn with_key(mut self, key: Key) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_track(mut self, track: SequenceTrack) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Returns the notes of `chords` from the first bar, lasting a bar each.
+    pub fn progression(
+        &self,
+        chords: impl IntoIterator<Item = Chord>,
+        velocity: u8,
+    ) -> Vec<PerformedNote> {
+        chords
+            .into_iter()
+            .zip(1..)
+            .flat_map(|(chord, bar)| {
+                let onset = self.tempo.ticks(Position::new(bar, 1, 0));
+                let length = self.tempo.ticks(Position::new(bar + 1, 1, 0)) - onset;
+                chord
+                    .into_iter()
+                    .map(move |midi| PerformedNote::new(midi, onset, length, velocity))
+            })
+            .collect()
+    }
+
+    /// Returns `notes` one after another from the start, each lasting `duration`,
+    /// such as the notes of a scale.
+    pub fn scale(
+        &self,
+        notes: impl IntoIterator<Item = MidiNote>,
+        duration: Duration,
+        velocity: u8,
+    ) -> Vec<PerformedNote> {
+        let length = Ticks::from_fraction(duration.length(), self.tempo.ppq());
+        notes
+            .into_iter()
+            .zip(0..)
+            .map(|(midi, index)| {
+                PerformedNote::new(midi, Ticks(length.0 * index), length, velocity)
+            })
+            .collect()
+    }
+
+    /// Returns the Standard MIDI File of this sequence.
+    ///
+    /// Linear tempo ramps are written as a tempo event on every beat.
+    pub fn to_smf(&self) -> Smf {
+        let mut conductor = Vec::new();
+        if let Some(title) = &self.title {
+            conductor.push((
+                Ticks(0),
+                MetaEvent::Text(TextKind::TrackName, title.as_bytes().to_vec()),
+            ));
+        }
+        if let Some(meta) = self.key.and_then(MetaEvent::from_key) {
+            conductor.push((Ticks(0), meta));
+        }
+        for change in self.tempo.meters() {
+            let at = self.tempo.ticks(Position::new(change.bar, 1, 0));
+            if let Some(meta) = MetaEvent::from_time_signature(change.meter) {
+                conductor.push((at, meta));
+            }
+        }
+
+        let ppq = self.tempo.ppq() as u64;
+        for (index, change) in self.tempo.tempos().iter().enumerate() {
+            conductor.push((change.at, MetaEvent::from_bpm(change.bpm)));
+            if let (Ramp::Linear, Some(next)) = (change.ramp, self.tempo.tempos().get(index + 1)) {
+                let beats = (change.at.0 / ppq + 1..).map(|beat| Ticks(beat * ppq));
+                for at in beats.take_while(|at| *at < next.at) {
+                    conductor.push((at, MetaEvent::from_bpm(self.tempo.tempo(at))));
+                }
+            }
+        }
+        conductor.sort_by_key(|(at, _)| *at);
+
+        let mut tracks = vec![track(
+            conductor
+                .into_iter()
+                .map(|(at, meta)| (at, EventKind::Meta(meta))),
+        )];
+        tracks.extend(self.tracks.iter().map(SequenceTrack::to_track));
+
+        Smf {
+            header: Header {
+                format: Format::Parallel,
+                timing: Timing::Metrical(self.tempo.ppq()),
+            },
+            tracks,
+        }
+    }
+
+    /// Returns the bytes of the Standard MIDI File of this sequence.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_smf().to_bytes()
+    }
+}
+
+/// Notes on a channel of a [`Sequence`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequenceTrack {
+    pub name: Option<String>,
+    /// Channel from 0 to 15.
+    pub channel: u8,
+    /// General MIDI program from 0 to 127 to change to at the start.
+    pub program: Option<u8>,
+    pub notes: Vec<PerformedNote>,
+}
+
+impl SequenceTrack {
+    /// Create an empty track on `channel` from 0 to 15.
+    ///
+    /// # Panics
+    /// Panics if `channel` is over 15.
+    pub fn new(channel: u8) -> Self {
+        assert!(channel < 16, "channel must be from 0 to 15");
+
+        Self {
+            name: None,
+            channel,
+            program: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_program(mut self, program: u8) -> Self {
+        self.program = Some(program & 0x7F);
+        self
+    }
+
+    pub fn with_notes(mut self, notes: impl IntoIterator<Item = PerformedNote>) -> Self {
+        self.notes.extend(notes);
+        self
+    }
+
+    /// Returns a track of note on and off events,
+    /// with note offs before note ons at the same tick.
+    pub fn to_track(&self) -> Track {
+        let mut events = Vec::new();
+        if let Some(name) = &self.name {
+            let meta = MetaEvent::Text(TextKind::TrackName, name.as_bytes().to_vec());
+            events.push((Ticks(0), EventKind::Meta(meta)));
+        }
+        if let Some(program) = self.program {
+            let status = 0xC0 | self.channel;
+            let event = ChannelEvent {
+                status,
+                data: [program, 0],
+            };
+            events.push((Ticks(0), EventKind::Midi(event)));
+        }
+
+        for note in &self.notes {
+            let on = ChannelEvent::note_on(self.channel, note.midi, note.velocity);
+            let off = ChannelEvent::note_off(self.channel, note.midi, 0);
+            events.push((note.onset, EventKind::Midi(on)));
+            events.push((note.onset + note.length, EventKind::Midi(off)));
+        }
+
+        events.sort_by_key(|(at, kind)| {
+            let order = match kind {
+                EventKind::Meta(_) => 0,
+                EventKind::Midi(event) if event.status >> 4 == 0x8 => 1,
+                _ => 2,
+            };
+            (*at, order)
+        });
+        track(events)
+    }
+}
+
+/// Returns a track of `events` in order of their absolute time, ending with an end of track.
+fn track(events: impl IntoIterator<Item = (Ticks, EventKind)>) -> Track {
+    let mut last = Ticks(0);
+    let mut track = Track::default();
+    for (at, kind) in events {
+        let mut delta = (at - last).0;
+        last = at;
+
+        // Gaps too long for one delta are filled with empty text events
+        while delta > MAX_VARIABLE as u64 {
+            track.events.push(TrackEvent {
+                delta: MAX_VARIABLE,
+                kind: EventKind::Meta(MetaEvent::Text(TextKind::Text, Vec::new())),
+            });
+            delta -= MAX_VARIABLE as u64;
+        }
+        track.events.push(TrackEvent {
+            delta: delta as u32,
+            kind,
+        });
+    }
+
+    track.events.push(TrackEvent {
+        delta: 0,
+        kind: EventKind::Meta(MetaEvent::EndOfTrack),
+    });
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::Octave,
+        time::{DurationKind, TimeSignature},
+        Pitch,
+    };
+
+    #[test]
+    fn it_splits_gaps_longer_than_a_delta() {
+        let marker = EventKind::Meta(MetaEvent::Text(TextKind::Marker, b"end".to_vec()));
+        let at = Ticks(MAX_VARIABLE as u64 * 2 + 5);
+        let track = track([(at, marker.clone())]);
+
+        let deltas: Vec<_> = track.events.iter().map(|event| event.delta).collect();
+        assert_eq!(deltas, [MAX_VARIABLE, MAX_VARIABLE, 5, 0]);
+        assert_eq!(track.events[2].kind, marker);
+    }
+
+    #[test]
+    fn it_round_trips_every_event() {
+        let events = [
+            EventKind::Meta(MetaEvent::SequenceNumber(None)),
+            EventKind::Meta(MetaEvent::SequenceNumber(Some(7))),
+            EventKind::Meta(MetaEvent::Text(TextKind::Lyric, b"la".to_vec())),
+            EventKind::Meta(MetaEvent::ChannelPrefix(3)),
+            EventKind::Meta(MetaEvent::Port(1)),
+            EventKind::Meta(MetaEvent::Tempo(400_000)),
+            EventKind::Meta(MetaEvent::SmpteOffset([1, 2, 3, 4, 5])),
+            EventKind::Meta(MetaEvent::KeySignature {
+                fifths: -3,
+                minor: true,
+            }),
+            EventKind::Meta(MetaEvent::SequencerSpecific(vec![0x41])),
+            EventKind::Meta(MetaEvent::Unknown {
+                kind: 0x60,
+                data: vec![1, 2],
+            }),
+            EventKind::SysEx(vec![0x7E, 0x7F, 0x09, 0x01, 0xF7]),
+            EventKind::Escape(vec![0xF8]),
+            EventKind::Midi(ChannelEvent {
+                status: 0x92,
+                data: [60, 100],
+            }),
+            EventKind::Midi(ChannelEvent {
+                status: 0x92,
+                data: [64, 100],
+            }),
+            EventKind::Midi(ChannelEvent {
+                status: 0xD2,
+                data: [40, 0],
+            }),
+            EventKind::Meta(MetaEvent::EndOfTrack),
+        ];
+        let track = Track {
+            events: events
+                .into_iter()
+                .zip(
+                    [0, 1, 0x7F, 0x80, 0x3FFF, 0x4000, MAX_VARIABLE]
+                        .into_iter()
+                        .cycle(),
+                )
+                .map(|(kind, delta)| TrackEvent { delta, kind })
+                .collect(),
+        };
+
+        for timing in [
+            Timing::Metrical(960),
+            Timing::Timecode {
+                fps: 25,
+                subframes: 40,
+            },
+        ] {
+            let smf = Smf {
+                header: Header {
+                    format: Format::Sequential,
+                    timing,
+                },
+                tracks: vec![track.clone(), Track::default()],
+            };
+
+            let mut parsed = Smf::parse(&smf.to_bytes()).unwrap();
+            assert_eq!(parsed.tracks[0], smf.tracks[0]);
+            assert_eq!(
+                parsed.tracks.pop().unwrap().events,
+                [TrackEvent {
+                    delta: 0,
+                    kind: EventKind::Meta(MetaEvent::EndOfTrack)
+                }]
+            );
+            assert_eq!(parsed.header, smf.header);
+        }
+    }
+
+    #[test]
+    fn it_writes_tempo_ramps_and_meters() {
+        let mut tempo = TempoMap::new(4, 60.);
+        tempo.ramp_tempo(Ticks(0), Ticks(8), 120.);
+        tempo.set_meter(2, TimeSignature::new(DurationKind::Eigth, 6));
+        let sequence = Sequence::new(tempo).with_key(Key::minor(Pitch::A));
+
+        let smf = Smf::parse(&sequence.to_bytes()).unwrap();
+        let metas: Vec<_> = smf.tracks[0]
+            .absolute()
+            .filter_map(|(at, event)| match &event.kind {
+                EventKind::Meta(meta) => Some((at.0, meta.clone())),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(metas[0].1.key(), Some(Key::minor(Pitch::A)));
+        let tempos: Vec<_> = metas
+            .iter()
+            .filter_map(|(at, meta)| match meta {
+                MetaEvent::Tempo(micros) => Some((*at, *micros)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tempos, [(0, 1_000_000), (4, 666_667), (8, 500_000)]);
+
+        let meters: Vec<_> = metas
+            .iter()
+            .filter_map(|(at, meta)| Some((*at, meta.time_signature()?)))
+            .collect();
+        assert_eq!(
+            meters,
+            [
+                (0, TimeSignature::new(DurationKind::Quarter, 4)),
+                (16, TimeSignature::new(DurationKind::Eigth, 6))
+            ]
+        );
+    }
+
+    #[test]
+    fn it_ends_notes_before_starting_the_next() {
+        let c4 = MidiNote::new(Pitch::C, Octave::FOUR);
+        let sequence = Sequence::new(TempoMap::new(480, 120.));
+        let notes = sequence.scale([c4, c4], Duration::from(DurationKind::Quarter), 90);
+        let track = SequenceTrack::new(9).with_notes(notes).to_track();
+
+        let events: Vec<_> = track
+            .absolute()
+            .map(|(at, event)| (at.0, event.kind.clone()))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (0, EventKind::Midi(ChannelEvent::note_on(9, c4, 90))),
+                (480, EventKind::Midi(ChannelEvent::note_off(9, c4, 0))),
+                (480, EventKind::Midi(ChannelEvent::note_on(9, c4, 90))),
+                (960, EventKind::Midi(ChannelEvent::note_off(9, c4, 0))),
+                (960, EventKind::Meta(MetaEvent::EndOfTrack)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use super::{
+    message::{ChannelMode, Message, NoteMessage, RealTime},
+    MidiNote, MidiSet,
+};
+use crate::{
+    time::{PerformedNote, Ticks},
+    Chord,
+};
+use core::cmp::Reverse;
+
+/// Sustain pedal controller.
+const SUSTAIN: u8 = 64;
+
+/// Sostenuto pedal controller.
+const SOSTENUTO: u8 = 66;
+
+/// A note that ended, with the channel it played on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteSpan {
+    pub channel: u8,
+    pub note: PerformedNote,
+}
+
+/// Tracker of the notes sounding on each channel of a stream of [`Message`]s,
+/// with the sustain and sostenuto pedals.
+///
+/// A note sounds from its note on until its key is released,
+/// or later while a pedal holds it.
+///
+/// The tracker doesn't allocate, so it can follow a [`Parser`](super::message::Parser)
+/// without the standard library.
+/// ```
+/// use staff::midi::message::{Message, NoteMessage};
+/// use staff::midi::NoteTracker;
+/// use staff::time::Ticks;
+/// use staff::midi;
+///
+/// let mut tracker = NoteTracker::new();
+/// let mut spans = Vec::new();
+/// let mut push = |tracker: &mut NoteTracker, at, message| {
+///     tracker.push(Ticks(at), &message, |span| spans.push(span))
+/// };
+/// let on = |note| Message::NoteOn(NoteMessage::new(0, note, 100));
+/// let off = |note| Message::NoteOff(NoteMessage::new(0, note, 0));
+/// let sustain = |value| Message::ControlChange { channel: 0, control: 64, value };
+///
+/// push(&mut tracker, 0, on(midi!(E, 3)));
+/// push(&mut tracker, 0, sustain(127));
+/// push(&mut tracker, 0, on(midi!(G, 3)));
+/// push(&mut tracker, 0, on(midi!(C, 4)));
+/// push(&mut tracker, 240, off(midi!(E, 3)));
+/// assert_eq!(tracker.chord().unwrap().to_string(), "C/E");
+///
+/// push(&mut tracker, 480, sustain(0));
+/// assert!(!tracker.sounding().contains(midi!(E, 3)));
+///
+/// push(&mut tracker, 960, off(midi!(G, 3)));
+/// push(&mut tracker, 960, off(midi!(C, 4)));
+/// assert!(tracker.chord().is_none());
+///
+/// // The pedal held E3 until 480 ticks
+/// assert_eq!(spans[0].note.midi, midi!(E, 3));
+/// assert_eq!(spans[0].note.length, Ticks(480));
+/// ```
+#[derive(Clone, Debug)]
+pub struct NoteTracker {
+    channels: [ChannelNotes; 16],
+}
+
+impl NoteTracker {
+    pub fn new() -> Self {
+        Self {
+            channels: core::array::from_fn(|_| ChannelNotes::default()),
+        }
+    }
+
+    /// Update the notes with a `message` at `at` ticks,
+    /// calling `f` with each note that ends.
+    ///
+    /// A note on of a note that's already sounding ends it and starts it again.
+    /// A [`RealTime::Reset`] ends every note and releases the pedals.
+    pub fn push<F>(&mut self, at: Ticks, message: &Message, mut f: F)
+    where
+        F: FnMut(NoteSpan),
+    {
+        if let Message::RealTime(RealTime::Reset) = message {
+            self.finish(at, f);
+            for channel in &mut self.channels {
+                *channel = ChannelNotes::default();
+            }
+            return;
+        }
+
+        let Some(channel) = message.channel() else {
+            return;
+        };
+        let notes = &mut self.channels[channel as usize & 0x0F];
+        let mut end = |note: PerformedNote| f(NoteSpan { channel, note });
+
+        match *message {
+            Message::NoteOn(NoteMessage { note, velocity, .. }) => {
+                notes.end(note, at, &mut end);
+                notes.held.push(note);
+                notes.sounding.push(note);
+                notes.onsets[note.into_byte() as usize & 0x7F] = Some((at, velocity));
+            }
+            Message::NoteOff(NoteMessage { note, .. }) => {
+                notes.held.remove(note);
+                if !notes.is_pedaled(note) {
+                    notes.end(note, at, &mut end);
+                }
+            }
+            Message::ControlChange {
+                control: SUSTAIN,
+                value,
+                ..
+            } => {
+                notes.is_sustained = value >= 64;
+                notes.release(at, &mut end);
+            }
+            Message::ControlChange {
+                control: SOSTENUTO,
+                value,
+                ..
+            } => {
+                let is_down = value >= 64;
+                if is_down && !notes.is_sostenuto {
+                    // Sostenuto only holds the notes sounding when it's pressed
+                    notes.sostenuto = notes.sounding.clone();
+                } else if !is_down {
+                    notes.sostenuto = MidiSet::default();
+                }
+                notes.is_sostenuto = is_down;
+                notes.release(at, &mut end);
+            }
+            Message::ChannelMode { mode, .. } => match mode {
+                ChannelMode::AllSoundOff => {
+                    for note in notes.sounding.clone() {
+                        notes.end(note, at, &mut end);
+                    }
+                }
+                ChannelMode::ResetAllControllers => {
+                    notes.is_sustained = false;
+                    notes.is_sostenuto = false;
+                    notes.sostenuto = MidiSet::default();
+                    notes.release(at, &mut end);
+                }
+                ChannelMode::LocalControl(_) => {}
+                // Every other mode message also turns off held keys
+                _ => {
+                    notes.held = MidiSet::default();
+                    notes.release(at, &mut end);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// End every sounding note at `at`, such as at the end of a track,
+    /// calling `f` with each note.
+    pub fn finish<F>(&mut self, at: Ticks, mut f: F)
+    where
+        F: FnMut(NoteSpan),
+    {
+        for (channel, notes) in (0..).zip(&mut self.channels) {
+            let mut end = |note: PerformedNote| f(NoteSpan { channel, note });
+            for note in notes.sounding.clone() {
+                notes.end(note, at, &mut end);
+            }
+            notes.held = MidiSet::default();
+        }
+    }
+
+    /// Returns the notes of `channel` from 0 to 15.
+    pub fn channel(&self, channel: u8) -> &ChannelNotes {
+        &self.channels[channel as usize & 0x0F]
+    }
+
+    /// Returns the notes sounding on every channel.
+    pub fn sounding(&self) -> MidiSet {
+        let mut sounding = MidiSet::default();
+        for notes in &self.channels {
+            sounding.extend(notes.sounding.clone());
+        }
+        sounding
+    }
+
+    /// Returns the chord of the notes sounding on every channel, or `None` if none are.
+    ///
+    /// The root is the sounding note with a third above it, then a perfect fifth,
+    /// preferring the bass note and then lower notes when they tie.
+    pub fn chord(&self) -> Option<Chord> {
+        let sounding = self.sounding();
+        let bass = sounding.clone().next()?;
+
+        let classes = sounding.clone().fold(0u16, |classes, note| {
+            classes | 1 << note.pitch().into_byte()
+        });
+        let has = |root: MidiNote, semitones: u8| {
+            let class = (root.pitch().into_byte() + semitones) % 12;
+            classes >> class & 1 == 1
+        };
+
+        let root = sounding
+            .clone()
+            .min_by_key(|root| {
+                let third = has(*root, 3) || has(*root, 4);
+                Reverse((third, has(*root, 7), *root == bass))
+            })
+            .unwrap_or(bass);
+        Chord::from_midi(root, sounding)
+    }
+}
+
+impl Default for NoteTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Notes of a channel of a [`NoteTracker`].
+#[derive(Clone, Debug)]
+pub struct ChannelNotes {
+    /// Onset and velocity of each sounding note.
+    onsets: [Option<(Ticks, u8)>; 128],
+    sounding: MidiSet,
+    held: MidiSet,
+    /// Notes held by the sostenuto pedal.
+    sostenuto: MidiSet,
+    is_sustained: bool,
+    is_sostenuto: bool,
+}
+
+impl ChannelNotes {
+    /// Returns the notes sounding, including notes held by a pedal.
+    pub fn sounding(&self) -> &MidiSet {
+        &self.sounding
+    }
+
+    /// Returns the notes with their keys held down.
+    pub fn held(&self) -> &MidiSet {
+        &self.held
+    }
+
+    pub fn is_sustained(&self) -> bool {
+        self.is_sustained
+    }
+
+    pub fn is_sostenuto(&self) -> bool {
+        self.is_sostenuto
+    }
+
+    /// Returns whether a pedal keeps `note` sounding after its key is released.
+    fn is_pedaled(&self, note: MidiNote) -> bool {
+        self.is_sustained || (self.is_sostenuto && self.sostenuto.contains(note))
+    }
+
+    /// End every sounding note that isn't held by its key or a pedal.
+    fn release(&mut self, at: Ticks, end: &mut impl FnMut(PerformedNote)) {
+        for note in self.sounding.clone() {
+            if !self.held.contains(note) && !self.is_pedaled(note) {
+                self.end(note, at, end);
+            }
+        }
+    }
+
+    /// End `note` if it's sounding.
+    fn end(&mut self, note: MidiNote, at: Ticks, end: &mut impl FnMut(PerformedNote)) {
+        self.sounding.remove(note);
+        if let Some((onset, velocity)) = self.onsets[note.into_byte() as usize & 0x7F].take() {
+            let length = Ticks(at.0.saturating_sub(onset.0));
+            end(PerformedNote::new(note, onset, length, velocity));
+        }
+    }
+}
+
+impl Default for ChannelNotes {
+    fn default() -> Self {
+        Self {
+            onsets: [None; 128],
+            sounding: MidiSet::default(),
+            held: MidiSet::default(),
+            sostenuto: MidiSet::default(),
+            is_sustained: false,
+            is_sostenuto: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(byte: u8) -> MidiNote {
+        MidiNote::from_byte(byte)
+    }
+
+    fn control(control: u8, value: u8) -> Message<'static> {
+        Message::ControlChange {
+            channel: 2,
+            control,
+            value,
+        }
+    }
+
+    fn track(messages: &[(u64, Message)]) -> (NoteTracker, Vec<(u8, u64, u64)>) {
+        let mut tracker = NoteTracker::new();
+        let mut spans = Vec::new();
+        for (at, message) in messages {
+            tracker.push(Ticks(*at), message, |span| {
+                spans.push((
+                    span.note.midi.into_byte(),
+                    span.note.onset.0,
+                    span.note.length.0,
+                ))
+            });
+        }
+        (tracker, spans)
+    }
+
+    #[test]
+    fn it_holds_notes_with_sostenuto() {
+        let on = |byte| Message::NoteOn(NoteMessage::new(2, note(byte), 90));
+        let off = |byte| Message::NoteOff(NoteMessage::new(2, note(byte), 0));
+
+        let (tracker, spans) = track(&[
+            (0, on(36)),
+            (0, control(SOSTENUTO, 127)),
+            (10, off(36)),
+            // Notes played after pressing sostenuto aren't held
+            (10, on(127)),
+            (20, off(127)),
+            (30, control(SOSTENUTO, 0)),
+        ]);
+        assert_eq!(spans, [(127, 10, 10), (36, 0, 30)]);
+        assert!(tracker.channel(2).sounding().is_empty());
+    }
+
+    #[test]
+    fn it_restarts_notes_and_turns_all_notes_off() {
+        let on = |byte| Message::NoteOn(NoteMessage::new(2, note(byte), 90));
+
+        let (tracker, spans) = track(&[
+            (0, control(SUSTAIN, 127)),
+            (0, on(60)),
+            (5, on(60)),
+            (8, on(64)),
+            (
+                10,
+                Message::ChannelMode {
+                    channel: 2,
+                    mode: ChannelMode::AllNotesOff,
+                },
+            ),
+        ]);
+
+        // The sustain pedal keeps notes sounding after all notes off
+        assert_eq!(spans, [(60, 0, 5)]);
+        assert!(tracker.channel(2).held().is_empty());
+        assert!(tracker.sounding().eq([note(60), note(64)]));
+    }
+
+    #[test]
+    fn it_names_chords_from_their_root() {
+        // A minor with C in the bass
+        let notes = [48, 57, 64];
+        let messages: Vec<_> = notes
+            .into_iter()
+            .map(|byte| (0, Message::NoteOn(NoteMessage::new(0, note(byte), 80))))
+            .collect();
+
+        let (tracker, _) = track(&messages);
+        let chord = tracker.chord().unwrap();
+        assert_eq!(chord.root, note(57));
+        assert_eq!(chord.bass, Some(note(48)));
+    }
+}
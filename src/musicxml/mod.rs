@@ -0,0 +1,239 @@
+//! MusicXML import and export of the [`score`](crate::score) model
+//!
+//! Partwise MusicXML files are read with [`read`],
+//! and compressed `.mxl` files with [`read_mxl`] when the `mxl` feature is enabled.
+//! Elements that can't be imported are skipped with a [`Warning`] instead of failing the import.
+//!
+//! Scores are written as MusicXML 4.0 with [`write`],
+//! and a [`LeadSheet`] makes a score of a chord progression or a scale.
+//!
+//! ```
+//! use staff::musicxml;
+//! use staff::score::Event;
+//! use staff::time::{DurationKind, TimeSignature};
+//! use staff::{Clef, Key, Pitch};
+//!
+//! let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+//! <score-partwise version="4.0">
+//!   <part-list>
+//!     <score-part id="P1"><part-name>Flute</part-name></score-part>
+//!   </part-list>
+//!   <part id="P1">
+//!     <measure number="1">
+//!       <attributes>
+//!         <divisions>1</divisions>
+//!         <key><fifths>2</fifths></key>
+//!         <time><beats>3</beats><beat-type>4</beat-type></time>
+//!         <clef><sign>G</sign><line>2</line></clef>
+//!       </attributes>
+//!       <note>
+//!         <pitch><step>F</step><alter>1</alter><octave>5</octave></pitch>
+//!         <duration>3</duration>
+//!         <type>half</type>
+//!         <dot/>
+//!       </note>
+//!     </measure>
+//!   </part>
+//! </score-partwise>"#;
+//!
+//! let import = musicxml::read(xml).unwrap();
+//! assert!(import.warnings.is_empty());
+//!
+//! let part = import.score.part("P1").unwrap();
+//! assert_eq!(part.name, "Flute");
+//!
+//! let voice = &part.staves[0].measures[0].voices[0];
+//! assert_eq!(voice.events[0], Event::Key(Key::major(Pitch::D)));
+//! assert_eq!(voice.events[1], Event::Time(TimeSignature::new(DurationKind::Quarter, 3)));
+//! assert_eq!(voice.events[2], Event::Clef(Clef::Treble));
+//!
+//! let Event::Note(note) = &voice.events[3] else {
+//!     unreachable!()
+//! };
+//! assert_eq!(note.heads[0].note.to_string(), "F♯");
+//! assert_eq!(note.duration.dots, 1);
+//! ```
+
+use core::fmt;
+
+mod read;
+pub use read::read;
+#[cfg_attr(docsrs, doc(cfg(feature = "mxl")))]
+#[cfg(feature = "mxl")]
+pub use read::read_mxl;
+
+mod write;
+pub use write::{write, LeadSheet};
+
+use crate::{score::Score, time::DurationKind};
+
+/// A score imported from MusicXML, with warnings for anything that was skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Import {
+    pub score: Score,
+    pub warnings: Vec<Warning>,
+}
+
+/// Something in a MusicXML file that couldn't be fully imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// Id of the part, or `None` outside of parts.
+    pub part: Option<String>,
+    /// Number of the measure, or `None` outside of measures.
+    pub measure: Option<u32>,
+    pub kind: WarningKind,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(part) = &self.part {
+            write!(f, "Part {}, ", part)?;
+        }
+        if let Some(measure) = self.measure {
+            write!(f, "measure {}: ", measure)?;
+        }
+        self.kind.fmt(f)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// An element that isn't supported, skipped with everything inside of it.
+    Unsupported(String),
+    /// An element with a value that couldn't be read.
+    Invalid(String),
+    /// A note or rest that can't be written as a single duration,
+    /// imported as the longest duration that fits.
+    Duration,
+    /// A note or rest that starts before the previous one in its voice ends.
+    Overlap,
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(element) => write!(f, "Unsupported element <{}>", element),
+            Self::Invalid(element) => write!(f, "Invalid value in <{}>", element),
+            Self::Duration => f.write_str("Duration can't be written as a single note"),
+            Self::Overlap => f.write_str("Event starts before the previous event in its voice"),
+        }
+    }
+}
+
+/// Error reading a MusicXML file.
+#[derive(Debug)]
+pub enum Error {
+    Xml(roxmltree::Error),
+    /// The root element isn't `<score-partwise>`.
+    UnsupportedRoot(String),
+    #[cfg(feature = "mxl")]
+    Zip(zip::result::ZipError),
+    #[cfg(feature = "mxl")]
+    Io(std::io::Error),
+    /// The compressed file has no root file listed in `META-INF/container.xml`.
+    #[cfg(feature = "mxl")]
+    MissingRootfile,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(error) => write!(f, "Invalid XML: {}", error),
+            Self::UnsupportedRoot(root) => write!(
+                f,
+                "Unsupported root element <{}>, expected <score-partwise>",
+                root
+            ),
+            #[cfg(feature = "mxl")]
+            Self::Zip(error) => write!(f, "Invalid MXL archive: {}", error),
+            #[cfg(feature = "mxl")]
+            Self::Io(error) => write!(f, "Error reading MXL archive: {}", error),
+            #[cfg(feature = "mxl")]
+            Self::MissingRootfile => f.write_str("MXL archive has no root file"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<roxmltree::Error> for Error {
+    fn from(error: roxmltree::Error) -> Self {
+        Self::Xml(error)
+    }
+}
+
+#[cfg(feature = "mxl")]
+impl From<zip::result::ZipError> for Error {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::Zip(error)
+    }
+}
+
+#[cfg(feature = "mxl")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Chord symbol suffixes of each MusicXML harmony `<kind>`.
+const CHORD_KINDS: [(&str, &str); 27] = [
+    ("major", ""),
+    ("none", ""),
+    ("other", ""),
+    ("minor", "m"),
+    ("augmented", "aug"),
+    ("diminished", "dim"),
+    ("dominant", "7"),
+    ("major-seventh", "maj7"),
+    ("minor-seventh", "m7"),
+    ("diminished-seventh", "dim7"),
+    ("augmented-seventh", "aug7"),
+    ("half-diminished", "m7b5"),
+    ("major-minor", "m(maj7)"),
+    ("major-sixth", "6"),
+    ("minor-sixth", "m6"),
+    ("dominant-ninth", "9"),
+    ("major-ninth", "maj9"),
+    ("minor-ninth", "m9"),
+    ("dominant-11th", "11"),
+    ("major-11th", "maj11"),
+    ("minor-11th", "m11"),
+    ("dominant-13th", "13"),
+    ("major-13th", "maj13"),
+    ("minor-13th", "m13"),
+    ("suspended-second", "sus2"),
+    ("suspended-fourth", "sus4"),
+    ("power", "5"),
+];
+
+/// Returns the chord symbol suffix of a harmony `<kind>`.
+fn chord_kind(value: &str) -> Option<&'static str> {
+    CHORD_KINDS
+        .iter()
+        .find(|(kind, _)| *kind == value)
+        .map(|(_, suffix)| *suffix)
+}
+
+/// Returns the harmony `<kind>` of a chord symbol suffix.
+fn chord_kind_value(suffix: &str) -> Option<&'static str> {
+    CHORD_KINDS
+        .iter()
+        .find(|(_, kind_suffix)| *kind_suffix == suffix)
+        .map(|(kind, _)| *kind)
+}
+
+/// Returns the note `<type>` of `kind`.
+fn type_name(kind: DurationKind) -> &'static str {
+    match kind {
+        DurationKind::Breve => "breve",
+        DurationKind::Whole => "whole",
+        DurationKind::Half => "half",
+        DurationKind::Quarter => "quarter",
+        DurationKind::Eigth => "eighth",
+        DurationKind::Sixteenth => "16th",
+        DurationKind::ThirtySecond => "32nd",
+        DurationKind::SixtyFourth => "64th",
+        DurationKind::HundredTwentyEighth => "128th",
+    }
+}
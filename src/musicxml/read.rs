@@ -0,0 +1,960 @@
+use super::{chord_kind, type_name, Error, Import, Warning, WarningKind};
+use crate::{
+    key::Mode,
+    midi::Octave,
+    note::{Accidental, Note},
+    score::{
+        Articulation, ChordSymbol, Dynamic, Event, Grace, Lyric, Measure, NoteEvent, Notehead,
+        Part, Rest, Score, Staff, Syllabic, Tie, Voice,
+    },
+    time::{Duration, DurationKind, Fraction, TimeSignature, Tuplet},
+    Clef, Key, Natural,
+};
+use core::{mem, str::FromStr};
+use roxmltree::{Document, Node, ParsingOptions};
+
+/// Read a partwise MusicXML document into a score.
+///
+/// Each part's measures are split into a staff for each of its `<staves>`,
+/// with a voice for each `<voice>` number on a staff.
+/// Gaps left in a voice by `<backup>` and `<forward>` are filled with rests.
+pub fn read(xml: &str) -> Result<Import, Error> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(xml, options)?;
+
+    let root = document.root_element();
+    if !root.has_tag_name("score-partwise") {
+        return Err(Error::UnsupportedRoot(root.tag_name().name().to_owned()));
+    }
+
+    let mut score = Score::new();
+    let mut warnings = Vec::new();
+    for node in elements(root) {
+        match node.tag_name().name() {
+            "work" => {
+                if let Some(title) = text(node, "work-title") {
+                    score.title.get_or_insert_with(|| title.to_owned());
+                }
+            }
+            "movement-title" => {
+                if let Some(title) = node.text() {
+                    score.title.get_or_insert_with(|| title.trim().to_owned());
+                }
+            }
+            "identification" => {
+                let composer = elements(node)
+                    .filter(|child| child.has_tag_name("creator"))
+                    .find(|creator| creator.attribute("type") == Some("composer"))
+                    .and_then(|creator| creator.text());
+                if let Some(composer) = composer {
+                    score.composer = Some(composer.trim().to_owned());
+                }
+            }
+            "part-list" => {
+                for score_part in elements(node).filter(|child| child.has_tag_name("score-part")) {
+                    let id = score_part.attribute("id").unwrap_or_default();
+                    let name = text(score_part, "part-name").unwrap_or_default();
+                    score.parts.push(Part::new(id, name));
+                }
+            }
+            "part" => {
+                let id = node.attribute("id").unwrap_or_default();
+                let index = match score.parts.iter().position(|part| part.id == id) {
+                    Some(index) => index,
+                    None => {
+                        score.parts.push(Part::new(id, ""));
+                        score.parts.len() - 1
+                    }
+                };
+
+                let mut reader = PartReader::new(id, &mut warnings);
+                for measure in elements(node) {
+                    if measure.has_tag_name("measure") {
+                        reader.measure(measure);
+                    } else {
+                        reader.unsupported(measure);
+                    }
+                }
+                score.parts[index].staves = reader.staves;
+            }
+            "movement-number" | "defaults" | "credit" => {}
+            name => warnings.push(Warning {
+                part: None,
+                measure: None,
+                kind: WarningKind::Unsupported(name.to_owned()),
+            }),
+        }
+    }
+
+    Ok(Import { score, warnings })
+}
+
+/// Read a compressed MusicXML (`.mxl`) archive into a score,
+/// from the root file listed in its `META-INF/container.xml`.
+#[cfg_attr(docsrs, doc(cfg(feature = "mxl")))]
+#[cfg(feature = "mxl")]
+pub fn read_mxl<R>(reader: R) -> Result<Import, Error>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let container = match read_entry(&mut archive, "META-INF/container.xml") {
+        Err(Error::Zip(zip::result::ZipError::FileNotFound)) => return Err(Error::MissingRootfile),
+        result => result?,
+    };
+
+    let document = Document::parse(&container)?;
+    let path = document
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|node| node.attribute("full-path"))
+        .ok_or(Error::MissingRootfile)?;
+
+    let xml = read_entry(&mut archive, path)?;
+    read(&xml)
+}
+
+#[cfg(feature = "mxl")]
+fn read_entry<R>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<String, Error>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    use std::io::Read;
+
+    let mut s = String::new();
+    archive.by_name(name)?.read_to_string(&mut s)?;
+    Ok(s)
+}
+
+/// A voice of the measure being read, with its end in divisions.
+struct VoiceState {
+    staff: usize,
+    id: String,
+    voice: Voice,
+    end: u64,
+}
+
+/// Reader of the measures of one part.
+struct PartReader<'a> {
+    part: String,
+    warnings: &'a mut Vec<Warning>,
+    number: u32,
+    /// Divisions per quarter note.
+    divisions: u64,
+    meter: Option<TimeSignature>,
+    staves: Vec<Staff>,
+    /// Position in the current measure in divisions.
+    position: u64,
+    voices: Vec<VoiceState>,
+    /// Events without a duration, waiting for the next note on their staff.
+    pending: Vec<(usize, Event)>,
+    /// Index of the voice of the last note, for adding chord notes to it.
+    last: Option<usize>,
+}
+
+impl<'a> PartReader<'a> {
+    fn new(part: &str, warnings: &'a mut Vec<Warning>) -> Self {
+        Self {
+            part: part.to_owned(),
+            warnings,
+            number: 0,
+            divisions: 1,
+            meter: None,
+            staves: vec![Staff::new()],
+            position: 0,
+            voices: Vec::new(),
+            pending: Vec::new(),
+            last: None,
+        }
+    }
+
+    fn warn(&mut self, kind: WarningKind) {
+        self.warnings.push(Warning {
+            part: Some(self.part.clone()),
+            measure: Some(self.number),
+            kind,
+        });
+    }
+
+    fn unsupported(&mut self, node: Node) {
+        self.warn(WarningKind::Unsupported(node.tag_name().name().to_owned()));
+    }
+
+    fn invalid(&mut self, name: &str) {
+        self.warn(WarningKind::Invalid(name.to_owned()));
+    }
+
+    /// Parse the text of the child `name` of `node`, warning if it's invalid.
+    fn parse<T: FromStr>(&mut self, node: Node, name: &str) -> Option<T> {
+        let value = text(node, name)?.parse().ok();
+        if value.is_none() {
+            self.invalid(name);
+        }
+        value
+    }
+
+    /// Returns the length of `divisions` in whole notes.
+    fn length(&self, divisions: u64) -> Fraction {
+        Fraction::new(divisions, self.divisions * 4)
+    }
+
+    fn measure(&mut self, node: Node) {
+        self.number = node
+            .attribute("number")
+            .and_then(|number| number.parse().ok())
+            .unwrap_or(self.number + 1);
+
+        for child in elements(node) {
+            match child.tag_name().name() {
+                "attributes" => self.attributes(child),
+                "note" => self.note(child),
+                "backup" => {
+                    if let Some(divisions) = self.parse::<u64>(child, "duration") {
+                        if divisions > self.position {
+                            self.invalid("backup");
+                        }
+                        self.position = self.position.saturating_sub(divisions);
+                    }
+                }
+                "forward" => {
+                    if let Some(divisions) = self.parse::<u64>(child, "duration") {
+                        self.position += divisions;
+                    }
+                }
+                "direction" => self.direction(child),
+                "harmony" => self.harmony(child),
+                "barline" => {
+                    if elements(child).any(|child| !child.has_tag_name("bar-style")) {
+                        self.unsupported(child);
+                    }
+                }
+                "print" | "sound" | "listening" => {}
+                _ => self.unsupported(child),
+            }
+        }
+
+        self.finish_measure();
+    }
+
+    /// Move the voices of the current measure onto their staves.
+    fn finish_measure(&mut self) {
+        for (staff, event) in mem::take(&mut self.pending) {
+            let index = self.voice(staff, "");
+            self.voices[index].voice.push(event);
+        }
+
+        let mut measures: Vec<_> = self
+            .staves
+            .iter()
+            .map(|_| Measure {
+                number: self.number,
+                voices: Vec::new(),
+            })
+            .collect();
+        for voice in mem::take(&mut self.voices) {
+            measures[voice.staff].voices.push(voice.voice);
+        }
+        for (staff, measure) in self.staves.iter_mut().zip(measures) {
+            staff.measures.push(measure);
+        }
+
+        self.position = 0;
+        self.last = None;
+    }
+
+    /// Returns the index of the voice `id` on `staff`, or of the first voice on `staff`
+    /// if `id` is empty, adding it if it doesn't exist yet.
+    fn voice(&mut self, staff: usize, id: &str) -> usize {
+        let index = self
+            .voices
+            .iter()
+            .position(|voice| voice.staff == staff && (id.is_empty() || voice.id == id));
+
+        index.unwrap_or_else(|| {
+            self.voices.push(VoiceState {
+                staff,
+                id: id.to_owned(),
+                voice: Voice::new(),
+                end: 0,
+            });
+            self.voices.len() - 1
+        })
+    }
+
+    /// Returns the index of the staff in the `<staff>` child of `node`, or the first staff.
+    fn staff(&mut self, node: Node) -> usize {
+        match self.parse::<usize>(node, "staff") {
+            Some(staff) if staff > 0 && staff <= self.staves.len() => staff - 1,
+            Some(_) => {
+                self.invalid("staff");
+                0
+            }
+            None => 0,
+        }
+    }
+
+    fn attributes(&mut self, node: Node) {
+        // Staves come after keys and times that apply to all of them
+        if let Some(staves) = self.parse::<usize>(node, "staves") {
+            while self.staves.len() < staves {
+                let measures = self.staves[0]
+                    .measures
+                    .iter()
+                    .map(|measure| Measure {
+                        number: measure.number,
+                        voices: Vec::new(),
+                    })
+                    .collect();
+                self.staves.push(Staff { measures });
+            }
+        }
+
+        for child in elements(node) {
+            match child.tag_name().name() {
+                "divisions" => match self.parse(node, "divisions") {
+                    Some(0) => self.invalid("divisions"),
+                    Some(divisions) => self.divisions = divisions,
+                    None => {}
+                },
+                "key" => {
+                    if let Some(key) = self.key(child) {
+                        self.push_staves(child, Event::Key(key));
+                    }
+                }
+                "time" => {
+                    if let Some(time) = self.time(child) {
+                        self.meter = Some(time);
+                        self.push_staves(child, Event::Time(time));
+                    }
+                }
+                "clef" => {
+                    if let Some(clef) = self.clef(child) {
+                        self.push_staves(child, Event::Clef(clef));
+                    }
+                }
+                "staves" => {}
+                _ => self.unsupported(child),
+            }
+        }
+    }
+
+    /// Add `event` to the staff in the `number` attribute of `node`, or to every staff.
+    fn push_staves(&mut self, node: Node, event: Event) {
+        match node.attribute("number") {
+            Some(number) => match number.parse::<usize>() {
+                Ok(number) if number > 0 && number <= self.staves.len() => {
+                    self.pending.push((number - 1, event));
+                }
+                _ => self.invalid(node.tag_name().name()),
+            },
+            None => {
+                for staff in 0..self.staves.len() {
+                    self.pending.push((staff, event.clone()));
+                }
+            }
+        }
+    }
+
+    fn key(&mut self, node: Node) -> Option<Key> {
+        let Some(fifths) = self.parse::<i8>(node, "fifths") else {
+            // Keys without fifths list each altered step
+            self.unsupported(node);
+            return None;
+        };
+        if !(-7..=7).contains(&fifths) {
+            self.invalid("fifths");
+            return None;
+        }
+
+        let mode = match text(node, "mode") {
+            None | Some("major" | "ionian") => Mode::Major,
+            Some("minor" | "aeolian") => Mode::Minor,
+            Some("dorian") => Mode::Dorian,
+            Some("phrygian") => Mode::Phrygian,
+            Some("lydian") => Mode::Lydian,
+            Some("mixolydian") => Mode::Mixolydian,
+            Some("locrian") => Mode::Locrian,
+            Some(_) => {
+                self.invalid("mode");
+                Mode::Major
+            }
+        };
+        Some(Key::from_fifths(fifths, mode))
+    }
+
+    fn time(&mut self, node: Node) -> Option<TimeSignature> {
+        let (Some(beats), Some(beat_type)) = (text(node, "beats"), text(node, "beat-type")) else {
+            self.unsupported(node);
+            return None;
+        };
+        if elements(node)
+            .filter(|child| child.has_tag_name("beats"))
+            .count()
+            > 1
+        {
+            // Composite signatures such as 3/8+2/4 are read as the first signature
+            self.unsupported(node);
+        }
+
+        let time = format!("{}/{}", beats, beat_type).parse().ok();
+        if time.is_none() {
+            self.invalid("time");
+        }
+        time
+    }
+
+    fn clef(&mut self, node: Node) -> Option<Clef> {
+        let clef = match (text(node, "sign"), text(node, "line")) {
+            (Some("G"), None | Some("2")) => Clef::Treble,
+            (Some("F"), None | Some("4")) => Clef::Bass,
+            (Some("C"), None | Some("3")) => Clef::Alto,
+            (Some("C"), Some("4")) => Clef::Tenor,
+            _ => {
+                self.unsupported(node);
+                return None;
+            }
+        };
+
+        if let Some(change) = child(node, "clef-octave-change") {
+            if change.text().map(str::trim) != Some("0") {
+                self.unsupported(change);
+            }
+        }
+        Some(clef)
+    }
+
+    fn note(&mut self, node: Node) {
+        let grace = child(node, "grace").map(|grace| Grace {
+            slash: grace.attribute("slash") == Some("yes"),
+        });
+        let divisions = match grace {
+            Some(_) => 0,
+            None => self.parse(node, "duration").unwrap_or(0),
+        };
+
+        let rest = child(node, "rest");
+        let head = if let Some(pitch) = child(node, "pitch") {
+            self.notehead(pitch, "step", "octave")
+        } else if let Some(unpitched) = child(node, "unpitched") {
+            self.notehead(unpitched, "display-step", "display-octave")
+        } else if rest.is_none() {
+            self.invalid("note");
+            None
+        } else {
+            None
+        };
+        let head = head.map(|head| Notehead {
+            tie: self.tie(node),
+            ..head
+        });
+
+        if child(node, "chord").is_some() {
+            let last = self
+                .last
+                .and_then(|index| self.voices[index].voice.events.last_mut());
+            match (last, head) {
+                (Some(Event::Note(note)), Some(head)) => {
+                    note.heads.push(head);
+                    return;
+                }
+                _ => self.invalid("chord"),
+            }
+        }
+        if head.is_none() && rest.is_none() {
+            return;
+        }
+
+        let is_measure = rest.is_some_and(|rest| rest.attribute("measure") == Some("yes"));
+        let measure_rest = self
+            .meter
+            .filter(|_| is_measure && text(node, "type").is_none())
+            .and_then(Rest::measure);
+        let duration = match measure_rest {
+            Some(rest) => Some(rest.duration),
+            None => self.duration(node, divisions, grace.is_some()),
+        };
+        let Some(duration) = duration else {
+            self.invalid("duration");
+            return;
+        };
+
+        let event = match head {
+            Some(head) => {
+                let mut note = NoteEvent::note(head, duration);
+                note.grace = grace;
+                for child in elements(node) {
+                    match child.tag_name().name() {
+                        "notations" => self.notations(child, &mut note),
+                        "lyric" => {
+                            if let Some(lyric) = self.lyric(child) {
+                                note.lyrics.push(lyric);
+                            }
+                        }
+                        "pitch" | "unpitched" | "chord" | "grace" | "duration" | "voice"
+                        | "type" | "dot" | "time-modification" | "staff" | "tie" | "stem"
+                        | "beam" | "accidental" | "notehead" | "instrument" | "play" | "listen"
+                        | "footnote" | "level" => {}
+                        _ => self.unsupported(child),
+                    }
+                }
+                Event::Note(note)
+            }
+            None => Event::Rest(Rest {
+                duration,
+                is_measure,
+            }),
+        };
+
+        let staff = self.staff(node);
+        let index = self.voice(staff, text(node, "voice").unwrap_or("1"));
+        self.place(index, event, divisions);
+    }
+
+    /// Add `event` lasting `divisions` to the voice at `index` at the current position.
+    fn place(&mut self, index: usize, event: Event, divisions: u64) {
+        match self.position.checked_sub(self.voices[index].end) {
+            Some(0) => {}
+            Some(gap) => {
+                let rests: Vec<Event> = Duration::decompose(self.length(gap))
+                    .map(|duration| Rest::new(duration).into())
+                    .collect();
+                self.voices[index].voice.events.extend(rests);
+            }
+            None => self.warn(WarningKind::Overlap),
+        }
+
+        let staff = self.voices[index].staff;
+        let (pending, others) = mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(pending_staff, _)| *pending_staff == staff);
+        self.pending = others;
+
+        let voice = &mut self.voices[index];
+        voice
+            .voice
+            .events
+            .extend(pending.into_iter().map(|(_, event)| event));
+        voice.voice.push(event);
+        voice.end = self.position + divisions;
+
+        self.position += divisions;
+        self.last = Some(index);
+    }
+
+    /// Returns the duration of the note `node` from its type,
+    /// or from its length in `divisions` if it doesn't have one.
+    fn duration(&mut self, node: Node, divisions: u64, is_grace: bool) -> Option<Duration> {
+        if let Some(kind) = text(node, "type") {
+            match duration_kind(kind) {
+                Some(kind) => {
+                    let dots = elements(node).filter(|child| child.has_tag_name("dot"));
+                    let mut duration = Duration::new(kind, dots.count() as u8);
+
+                    if let Some(modification) = child(node, "time-modification") {
+                        let actual = self.parse(modification, "actual-notes");
+                        let normal = self.parse(modification, "normal-notes");
+                        match (actual, normal) {
+                            (Some(actual), Some(normal)) if actual > 0 && normal > 0 => {
+                                duration = duration.with_tuplet(Tuplet::new(actual, normal));
+                            }
+                            _ => self.invalid("time-modification"),
+                        }
+                    }
+                    return Some(duration);
+                }
+                None => self.invalid("type"),
+            }
+        }
+
+        if is_grace {
+            return Some(Duration::from(DurationKind::Eigth));
+        }
+
+        let mut tied = Duration::decompose(self.length(divisions));
+        let duration = tied.next()?;
+        if tied.next().is_some() || !tied.remainder().is_zero() {
+            self.warn(WarningKind::Duration);
+        }
+        Some(duration)
+    }
+
+    /// Returns the spelled note in the `step` and `alter` children of `node`.
+    fn spelled(&mut self, node: Node, step: &str, alter: &str) -> Option<Note> {
+        let mut chars = text(node, step)?.chars();
+        let natural = chars
+            .next()
+            .and_then(|c| Natural::try_from(c).ok())
+            .filter(|_| chars.next().is_none());
+        let Some(natural) = natural else {
+            self.invalid(step);
+            return None;
+        };
+
+        let semitones = match self.parse::<f64>(node, alter) {
+            Some(semitones) if semitones.fract() != 0. => {
+                // Microtones are rounded to the nearest semitone
+                self.invalid(alter);
+                semitones.round()
+            }
+            Some(semitones) => semitones,
+            None => 0.,
+        };
+        let accidental = Accidental::from_semitones(semitones as i8).unwrap_or_else(|| {
+            self.invalid(alter);
+            Accidental::Natural
+        });
+        Some(Note::new(natural, accidental))
+    }
+
+    fn notehead(&mut self, node: Node, step: &str, octave: &str) -> Option<Notehead> {
+        let note = self.spelled(node, step, "alter")?;
+        match self.parse::<i8>(node, octave) {
+            Some(number) if (-1..=9).contains(&number) => {
+                Some(Notehead::new(note, Octave::new_unchecked(number)))
+            }
+            _ => {
+                self.invalid(octave);
+                None
+            }
+        }
+    }
+
+    fn tie(&mut self, node: Node) -> Option<Tie> {
+        let mut tie = None;
+        for child in elements(node).filter(|child| child.has_tag_name("tie")) {
+            tie = match (tie, child.attribute("type")) {
+                (None, Some("start")) => Some(Tie::Start),
+                (None, Some("stop")) => Some(Tie::Stop),
+                (Some(_), Some("start" | "stop")) => Some(Tie::Continue),
+                _ => {
+                    self.invalid("tie");
+                    tie
+                }
+            };
+        }
+        tie
+    }
+
+    fn notations(&mut self, node: Node, note: &mut NoteEvent) {
+        for child in elements(node) {
+            match child.tag_name().name() {
+                "articulations" => {
+                    for articulation in elements(child) {
+                        let articulation = match articulation.tag_name().name() {
+                            "staccato" => Articulation::Staccato,
+                            "staccatissimo" => Articulation::Staccatissimo,
+                            "tenuto" => Articulation::Tenuto,
+                            "accent" => Articulation::Accent,
+                            "strong-accent" => Articulation::Marcato,
+                            _ => {
+                                self.unsupported(articulation);
+                                continue;
+                            }
+                        };
+                        note.articulations.push(articulation);
+                    }
+                }
+                "fermata" => note.articulations.push(Articulation::Fermata),
+                // Ties and tuplets are read from the note itself
+                "tied" | "tuplet" | "footnote" | "level" => {}
+                _ => self.unsupported(child),
+            }
+        }
+    }
+
+    fn lyric(&mut self, node: Node) -> Option<Lyric> {
+        let Some(text_node) = child(node, "text") else {
+            self.unsupported(node);
+            return None;
+        };
+        if child(node, "elision").is_some() {
+            // Only the first syllable of an elision is kept
+            self.warn(WarningKind::Unsupported("elision".to_owned()));
+        }
+
+        let syllabic = match text(node, "syllabic") {
+            Some("begin") => Syllabic::Begin,
+            Some("middle") => Syllabic::Middle,
+            Some("end") => Syllabic::End,
+            _ => Syllabic::Single,
+        };
+        Some(Lyric {
+            verse: node
+                .attribute("number")
+                .and_then(|number| number.parse().ok())
+                .unwrap_or(1),
+            text: text_node.text().unwrap_or_default().to_owned(),
+            syllabic,
+        })
+    }
+
+    fn direction(&mut self, node: Node) {
+        let staff = self.staff(node);
+        for child in elements(node) {
+            match child.tag_name().name() {
+                "direction-type" => {
+                    for kind in elements(child) {
+                        if !kind.has_tag_name("dynamics") {
+                            self.unsupported(kind);
+                            continue;
+                        }
+
+                        for dynamic in elements(kind) {
+                            match dynamic_from_name(dynamic.tag_name().name()) {
+                                Some(value) => self.pending.push((staff, Event::Dynamic(value))),
+                                None => self.unsupported(dynamic),
+                            }
+                        }
+                    }
+                }
+                "offset" | "staff" | "voice" | "sound" | "listening" | "footnote" | "level" => {}
+                _ => self.unsupported(child),
+            }
+        }
+    }
+
+    fn harmony(&mut self, node: Node) {
+        let Some(root) = child(node, "root") else {
+            // Roman numerals and functions don't have a root step
+            self.unsupported(node);
+            return;
+        };
+        let Some(root) = self.spelled(root, "root-step", "root-alter") else {
+            return;
+        };
+        let bass =
+            child(node, "bass").and_then(|bass| self.spelled(bass, "bass-step", "bass-alter"));
+
+        let kind = child(node, "kind").and_then(|kind| match kind.attribute("text") {
+            Some(text) => Some(text.to_owned()),
+            None => chord_kind(kind.text()?.trim()).map(str::to_owned),
+        });
+        let kind = kind.unwrap_or_else(|| {
+            self.invalid("kind");
+            String::new()
+        });
+
+        if let Some(degree) = child(node, "degree") {
+            self.unsupported(degree);
+        }
+
+        let staff = self.staff(node);
+        self.pending
+            .push((staff, Event::ChordSymbol(ChordSymbol { root, kind, bass })));
+    }
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(Node::is_element)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+/// Returns the trimmed text of the child `name` of `node`.
+fn text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)?.text().map(str::trim)
+}
+
+fn duration_kind(name: &str) -> Option<DurationKind> {
+    DurationKind::ALL
+        .into_iter()
+        .find(|kind| type_name(*kind) == name)
+}
+
+fn dynamic_from_name(name: &str) -> Option<Dynamic> {
+    let dynamic = match name {
+        "ppp" => Dynamic::PPP,
+        "pp" => Dynamic::PP,
+        "p" => Dynamic::P,
+        "mp" => Dynamic::MP,
+        "mf" => Dynamic::MF,
+        "f" => Dynamic::F,
+        "ff" => Dynamic::FF,
+        "fff" => Dynamic::FFF,
+        "fp" => Dynamic::FP,
+        "sf" => Dynamic::SF,
+        "sfz" => Dynamic::SFZ,
+        _ => return None,
+    };
+    Some(dynamic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(measures: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">
+<score-partwise version="4.0">
+  <work><work-title>Test</work-title></work>
+  <identification><creator type="composer">Anonymous</creator></identification>
+  <part-list>
+    <score-part id="P1"><part-name>Piano</part-name></score-part>
+  </part-list>
+  <part id="P1">{}</part>
+</score-partwise>"#,
+            measures
+        )
+    }
+
+    #[test]
+    fn it_reads_voices_and_staves() {
+        let xml = score(
+            r#"<measure number="1">
+  <attributes>
+    <divisions>2</divisions>
+    <time><beats>2</beats><beat-type>4</beat-type></time>
+    <staves>2</staves>
+    <clef number="1"><sign>G</sign><line>2</line></clef>
+    <clef number="2"><sign>F</sign><line>4</line></clef>
+  </attributes>
+  <harmony><root><root-step>C</root-step></root><kind>major-seventh</kind></harmony>
+  <note>
+    <pitch><step>C</step><octave>5</octave></pitch>
+    <duration>4</duration><tie type="start"/><voice>1</voice><type>half</type><staff>1</staff>
+    <lyric number="1"><syllabic>begin</syllabic><text>La</text></lyric>
+  </note>
+  <note>
+    <chord/><pitch><step>E</step><octave>5</octave></pitch>
+    <duration>4</duration><voice>1</voice><type>half</type><staff>1</staff>
+  </note>
+  <backup><duration>4</duration></backup>
+  <forward><duration>2</duration></forward>
+  <note>
+    <pitch><step>B</step><alter>-1</alter><octave>4</octave></pitch>
+    <duration>2</duration><voice>2</voice><type>quarter</type><staff>1</staff>
+  </note>
+  <backup><duration>4</duration></backup>
+  <note>
+    <rest measure="yes"/><duration>4</duration><voice>5</voice><staff>2</staff>
+  </note>
+</measure>"#,
+        );
+
+        let import = read(&xml).unwrap();
+        assert_eq!(import.warnings, []);
+        assert_eq!(import.score.title.as_deref(), Some("Test"));
+        assert_eq!(import.score.composer.as_deref(), Some("Anonymous"));
+
+        let staves = &import.score.parts[0].staves;
+        assert_eq!(staves.len(), 2);
+
+        let upper = &staves[0].measures[0];
+        assert_eq!(upper.voices.len(), 2);
+        assert!(
+            matches!(upper.voices[0].events[2], Event::ChordSymbol(ref symbol) if symbol.to_string() == "Cmaj7")
+        );
+
+        let Event::Note(chord) = &upper.voices[0].events[3] else {
+            panic!("expected a chord")
+        };
+        assert_eq!(chord.heads.len(), 2);
+        assert_eq!(chord.heads[0].tie, Some(Tie::Start));
+        assert_eq!(chord.lyrics[0].syllabic, Syllabic::Begin);
+
+        // The second voice starts with a rest before the forward
+        assert_eq!(
+            upper.voices[1].events[0],
+            Event::Rest(Rest::new(DurationKind::Quarter.into()))
+        );
+        assert_eq!(upper.length(), Fraction::new(1, 2));
+
+        let lower = &staves[1].measures[0];
+        assert_eq!(
+            lower.voices[0].events[0],
+            Event::Time("2/4".parse().unwrap())
+        );
+        assert_eq!(lower.voices[0].events[1], Event::Clef(Clef::Bass));
+        assert!(matches!(lower.voices[0].events[2], Event::Rest(rest) if rest.is_measure));
+        assert!(lower.check("2/4".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn it_warns_about_unsupported_elements() {
+        let xml = score(
+            r#"<measure number="3">
+  <attributes><divisions>3</divisions></attributes>
+  <direction><direction-type><words>dolce</words></direction-type></direction>
+  <direction><direction-type><dynamics><pp/></dynamics></direction-type></direction>
+  <note>
+    <pitch><step>G</step><alter>0.5</alter><octave>4</octave></pitch>
+    <duration>2</duration>
+    <notations><articulations><staccato/><doit/></articulations></notations>
+  </note>
+  <note>
+    <pitch><step>G</step><octave>4</octave></pitch>
+    <duration>10</duration>
+  </note>
+</measure>"#,
+        );
+
+        let import = read(&xml).unwrap();
+        let kinds: Vec<_> = import
+            .warnings
+            .iter()
+            .inspect(|warning| assert_eq!(warning.measure, Some(3)))
+            .map(|warning| &warning.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                &WarningKind::Unsupported("words".to_owned()),
+                &WarningKind::Invalid("alter".to_owned()),
+                &WarningKind::Unsupported("doit".to_owned()),
+                &WarningKind::Duration,
+            ]
+        );
+
+        let events = &import.score.parts[0].staves[0].measures[0].voices[0].events;
+        assert_eq!(events[0], Event::Dynamic(Dynamic::PP));
+
+        // Two thirds of a quarter note is a triplet quarter
+        let Event::Note(note) = &events[1] else {
+            panic!("expected a note")
+        };
+        assert_eq!(
+            note.duration,
+            Duration::from(DurationKind::Quarter).with_tuplet(Tuplet::TRIPLET)
+        );
+        assert_eq!(note.heads[0].note, Note::sharp(Natural::G));
+        assert_eq!(note.articulations, [Articulation::Staccato]);
+    }
+
+    #[test]
+    fn it_rejects_timewise_scores() {
+        let error = read("<score-timewise/>").unwrap_err();
+        assert!(matches!(error, Error::UnsupportedRoot(root) if root == "score-timewise"));
+    }
+
+    #[cfg(feature = "mxl")]
+    #[test]
+    fn it_reads_compressed_scores() {
+        use std::io::{Cursor, Write};
+        use zip::write::SimpleFileOptions;
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("META-INF/container.xml", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .write_all(
+                br#"<container><rootfiles><rootfile full-path="score.musicxml"/></rootfiles></container>"#,
+            )
+            .unwrap();
+        writer
+            .start_file("score.musicxml", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(score("").as_bytes()).unwrap();
+        let archive = writer.finish().unwrap();
+
+        let import = read_mxl(archive).unwrap();
+        assert_eq!(import.score.parts[0].name, "Piano");
+    }
+}
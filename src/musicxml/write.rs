@@ -0,0 +1,935 @@
+use super::{chord_kind_value, type_name};
+use crate::{
+    midi::MidiNote,
+    note::Note,
+    score::{
+        Articulation, Attributes, ChordSymbol, Event, Measure, NoteEvent, Notehead, Part, Rest,
+        Score, Staff, Syllabic, Tie, Voice,
+    },
+    time::{Duration, Fraction, TimeSignature, Tuplet},
+    Chord, Clef, Key,
+};
+use core::fmt::{self, Write};
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">
+"#;
+
+/// Write a score as a partwise MusicXML 4.0 document.
+///
+/// Voices are numbered from 1 on the first staff of a part, from 5 on the second, and so on.
+/// ```
+/// use staff::musicxml;
+/// use staff::score::{Event, NoteEvent, Notehead, Part, Score, Staff, Measure, Voice};
+/// use staff::time::{Duration, DurationKind};
+/// use staff::{midi, Clef};
+///
+/// let mut voice = Voice::new();
+/// voice.push(Event::Clef(Clef::Bass));
+/// voice.push(NoteEvent::note(midi!(C, 3).into(), Duration::from(DurationKind::Whole)));
+///
+/// let mut staff = Staff::new();
+/// staff.measures.push(Measure::new(1, voice));
+/// let mut part = Part::new("P1", "Cello");
+/// part.staves.push(staff);
+/// let mut score = Score::new();
+/// score.parts.push(part);
+///
+/// let xml = musicxml::write(&score);
+/// assert!(xml.contains("<clef>\n          <sign>F</sign>"));
+/// assert_eq!(musicxml::read(&xml).unwrap().score, score);
+/// ```
+pub fn write(score: &Score) -> String {
+    let mut xml = Xml {
+        out: HEADER.to_owned(),
+        depth: 0,
+    };
+
+    xml.open("score-partwise", &[("version", "4.0")]);
+    if let Some(title) = &score.title {
+        xml.open("work", &[]);
+        xml.text("work-title", &[], title);
+        xml.close("work");
+    }
+
+    xml.open("identification", &[]);
+    if let Some(composer) = &score.composer {
+        xml.text("creator", &[("type", "composer")], composer);
+    }
+    xml.open("encoding", &[]);
+    xml.text("software", &[], "staff");
+    xml.close("encoding");
+    xml.close("identification");
+
+    let ids: Vec<_> = score
+        .parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| match part.id.as_str() {
+            "" => format!("P{}", index + 1),
+            id => id.to_owned(),
+        })
+        .collect();
+
+    xml.open("part-list", &[]);
+    for (part, id) in score.parts.iter().zip(&ids) {
+        xml.open("score-part", &[("id", id)]);
+        xml.text("part-name", &[], &part.name);
+        xml.close("score-part");
+    }
+    xml.close("part-list");
+
+    for (part, id) in score.parts.iter().zip(&ids) {
+        xml.open("part", &[("id", id)]);
+        PartWriter::new(&mut xml, part).write();
+        xml.close("part");
+    }
+
+    xml.close("score-partwise");
+    xml.out
+}
+
+/// A single-staff lead sheet of a chord progression or a scale,
+/// for writing exercises with [`write`].
+/// ```
+/// use staff::musicxml::{self, LeadSheet};
+/// use staff::time::{DurationKind, TimeSignature};
+/// use staff::{midi, Chord, Key, Pitch};
+///
+/// let time = TimeSignature::new(DurationKind::Quarter, 4);
+/// let sheet = LeadSheet::new(Key::major(Pitch::F), time)
+///     .with_title("ii-V-I in F");
+/// let score = sheet.progression([
+///     Chord::minor_seventh(midi!(G, 3)),
+///     Chord::seventh(midi!(C, 4)),
+///     Chord::major(midi!(F, 3)),
+/// ]);
+///
+/// let xml = musicxml::write(&score);
+/// assert!(xml.contains(r#"<kind text="m7">minor-seventh</kind>"#));
+/// assert_eq!(xml.matches("<alter>-1</alter>").count(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeadSheet {
+    pub title: Option<String>,
+    pub key: Key,
+    pub time: TimeSignature,
+    pub clef: Clef,
+}
+
+impl LeadSheet {
+    pub fn new(key: Key, time: TimeSignature) -> Self {
+        Self {
+            title: None,
+            key,
+            time,
+            clef: Clef::Treble,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_clef(mut self, clef: Clef) -> Self {
+        self.clef = clef;
+        self
+    }
+
+    /// Returns a score of `chords` lasting a measure each, with their symbols above them.
+    pub fn progression(&self, chords: impl IntoIterator<Item = Chord>) -> Score {
+        let measure = self.time.measure();
+        self.score(chords.into_iter().map(|chord| {
+            let symbol = ChordSymbol::from_chord(&chord, self.key);
+            let heads = chord
+                .into_iter()
+                .map(|midi| Notehead::in_key(midi, self.key))
+                .collect();
+            (heads, measure, Some(symbol))
+        }))
+    }
+
+    /// Returns a score of `notes` each lasting `duration`, such as the notes of a scale.
+    /// ```
+    /// use staff::musicxml::LeadSheet;
+    /// use staff::time::{Duration, DurationKind, TimeSignature};
+    /// use staff::{midi, Key, Pitch, Scale};
+    ///
+    /// let time = TimeSignature::new(DurationKind::Quarter, 4);
+    /// let sheet = LeadSheet::new(Key::major(Pitch::D), time);
+    /// let score = sheet.scale(Scale::major(midi!(D, 4)), Duration::from(DurationKind::Quarter));
+    ///
+    /// // Seven quarter notes and a quarter rest
+    /// let measures = &score.parts[0].staves[0].measures;
+    /// assert_eq!(measures.len(), 2);
+    /// assert!(measures[1].check(sheet.time).is_ok());
+    /// ```
+    pub fn scale(&self, notes: impl IntoIterator<Item = MidiNote>, duration: Duration) -> Score {
+        self.score(notes.into_iter().map(|midi| {
+            let head = Notehead::in_key(midi, self.key);
+            (vec![head], duration.length(), None)
+        }))
+    }
+
+    /// Returns a score of notes with their length and an optional chord symbol,
+    /// split into tied notes at barlines and beats.
+    ///
+    /// Parts shorter than a 128th note can't be written, so they're left out
+    /// while the notes after them keep their place.
+    fn score<I>(&self, notes: I) -> Score
+    where
+        I: IntoIterator<Item = (Vec<Notehead>, Fraction, Option<ChordSymbol>)>,
+    {
+        let measure = self.time.measure();
+        let mut voices = vec![Voice::new()];
+        let mut position = Fraction::ZERO;
+
+        for (heads, length, mut symbol) in notes {
+            let mut is_tied = false;
+            for piece in self.time.split(position, length) {
+                let (index, _) = piece.start.div_rem(measure);
+                while voices.len() <= index as usize {
+                    voices.push(Voice::new());
+                }
+
+                let voice = &mut voices[index as usize];
+                if let Some(symbol) = symbol.take() {
+                    voice.push(Event::ChordSymbol(symbol));
+                }
+
+                let note = NoteEvent::chord(heads.iter().copied(), piece.duration);
+                let note = match (is_tied, piece.is_tied) {
+                    (false, false) => note,
+                    (false, true) => note.with_tie(Tie::Start),
+                    (true, true) => note.with_tie(Tie::Continue),
+                    (true, false) => note.with_tie(Tie::Stop),
+                };
+                voice.push(note);
+                is_tied = piece.is_tied;
+            }
+            position += length;
+        }
+
+        let (index, offset) = position.div_rem(measure);
+        if position.is_zero() {
+            if let Some(rest) = Rest::measure(self.time) {
+                voices[0].push(rest);
+            }
+        } else if !offset.is_zero() {
+            for piece in self.time.split(position, measure - offset) {
+                voices[index as usize].push(Rest::new(piece.duration));
+            }
+        }
+
+        let attributes = [
+            Event::Clef(self.clef),
+            Event::Key(self.key),
+            Event::Time(self.time),
+        ];
+        voices[0].events.splice(0..0, attributes);
+
+        let mut staff = Staff::new();
+        staff.measures = voices
+            .into_iter()
+            .zip(1..)
+            .map(|(voice, number)| Measure::new(number, voice))
+            .collect();
+
+        let mut part = Part::new("P1", "");
+        part.staves.push(staff);
+        Score {
+            title: self.title.clone(),
+            composer: None,
+            parts: vec![part],
+        }
+    }
+}
+
+/// Writer of the measures of one part.
+struct PartWriter<'a> {
+    xml: &'a mut Xml,
+    part: &'a Part,
+    /// Divisions per quarter note.
+    divisions: u64,
+    attributes: Vec<Attributes>,
+}
+
+impl<'a> PartWriter<'a> {
+    fn new(xml: &'a mut Xml, part: &'a Part) -> Self {
+        // Divisions that write the length of every note as a whole number
+        let divisions = part
+            .staves
+            .iter()
+            .flat_map(|staff| &staff.measures)
+            .flat_map(|measure| &measure.voices)
+            .flat_map(|voice| &voice.events)
+            .map(|event| (event.length() * 4).denom())
+            .fold(1, lcm);
+
+        Self {
+            xml,
+            part,
+            divisions,
+            attributes: vec![Attributes::default(); part.staves.len()],
+        }
+    }
+
+    fn write(&mut self) {
+        let part = self.part;
+        let len = part
+            .staves
+            .iter()
+            .map(|staff| staff.measures.len())
+            .max()
+            .unwrap_or(0);
+
+        for index in 0..len {
+            let number = part
+                .staves
+                .iter()
+                .find_map(|staff| staff.measures.get(index))
+                .map_or(index as u32 + 1, |measure| measure.number);
+            self.xml.open("measure", &[("number", &number.to_string())]);
+
+            // Clefs, keys and times at the start of every voice are written together
+            let mut leading = Vec::new();
+            for (staff_index, staff) in part.staves.iter().enumerate() {
+                for event in staff
+                    .measures
+                    .get(index)
+                    .into_iter()
+                    .flat_map(|measure| &measure.voices)
+                    .flat_map(|voice| voice.events.iter().take_while(|event| is_attribute(event)))
+                {
+                    leading.push((staff_index, event));
+                }
+            }
+            if index == 0 || !leading.is_empty() {
+                self.write_attributes(&leading, index == 0);
+            }
+
+            let mut position = 0;
+            for (staff_index, staff) in part.staves.iter().enumerate() {
+                let Some(measure) = staff.measures.get(index) else {
+                    continue;
+                };
+                for event in measure.voices.iter().flat_map(|voice| &voice.events) {
+                    self.attributes[staff_index].apply(event);
+                }
+
+                if position > 0 {
+                    self.xml.open("backup", &[]);
+                    self.xml.text("duration", &[], &position.to_string());
+                    self.xml.close("backup");
+                }
+                position = self.write_measure(staff_index, measure);
+            }
+
+            self.xml.close("measure");
+        }
+    }
+
+    /// Write the voices of `measure` on a staff and return its length in divisions.
+    fn write_measure(&mut self, staff: usize, measure: &Measure) -> u64 {
+        if measure.voices.iter().all(|voice| voice.length().is_zero()) {
+            if let Some(rest) = self.attributes[staff].time.and_then(Rest::measure) {
+                let number = staff * 4 + 1;
+                self.write_rest(&rest, staff, number, (false, false));
+                return self.divisions_of(rest.duration.length());
+            }
+        }
+
+        let mut position = 0;
+        for (voice_index, voice) in measure.voices.iter().enumerate() {
+            if position > 0 {
+                self.xml.open("backup", &[]);
+                self.xml.text("duration", &[], &position.to_string());
+                self.xml.close("backup");
+            }
+            position = self.divisions_of(voice.length());
+
+            let number = staff * 4 + voice_index + 1;
+            let tuplets = tuplets(&voice.events);
+            let skip = voice
+                .events
+                .iter()
+                .take_while(|event| is_attribute(event))
+                .count();
+
+            for (event, tuplet) in voice.events.iter().zip(tuplets).skip(skip) {
+                match event {
+                    Event::Note(note) => self.write_note(note, staff, number, tuplet),
+                    Event::Rest(rest) => self.write_rest(rest, staff, number, tuplet),
+                    Event::Clef(_) | Event::Key(_) | Event::Time(_) => {
+                        self.write_attributes(&[(staff, event)], false);
+                    }
+                    Event::ChordSymbol(symbol) => self.write_harmony(symbol, staff),
+                    Event::Dynamic(dynamic) => {
+                        self.xml.open("direction", &[("placement", "below")]);
+                        self.xml.open("direction-type", &[]);
+                        self.xml.open("dynamics", &[]);
+                        self.xml.empty(&dynamic.to_string(), &[]);
+                        self.xml.close("dynamics");
+                        self.xml.close("direction-type");
+                        self.write_staff(staff);
+                        self.xml.close("direction");
+                    }
+                }
+            }
+        }
+        position
+    }
+
+    fn write_staff(&mut self, staff: usize) {
+        if self.part.staves.len() > 1 {
+            self.xml.text("staff", &[], &(staff + 1).to_string());
+        }
+    }
+
+    /// Returns the length of `length` in divisions.
+    fn divisions_of(&self, length: Fraction) -> u64 {
+        (length * (self.divisions * 4)).floor()
+    }
+
+    /// Write the clef, key and time `events` on each staff,
+    /// with the number of each staff if the part has more than one.
+    fn write_attributes(&mut self, events: &[(usize, &Event)], is_first: bool) {
+        let staves = self.part.staves.len();
+        let mut keys: Vec<(usize, Key)> = Vec::new();
+        let mut times: Vec<(usize, TimeSignature)> = Vec::new();
+        let mut clefs: Vec<(usize, Clef)> = Vec::new();
+        for (staff, event) in events {
+            match event {
+                Event::Key(key) if !keys.iter().any(|(s, _)| s == staff) => {
+                    keys.push((*staff, *key));
+                }
+                Event::Time(time) if !times.iter().any(|(s, _)| s == staff) => {
+                    times.push((*staff, *time));
+                }
+                Event::Clef(clef) if !clefs.iter().any(|(s, _)| s == staff) => {
+                    clefs.push((*staff, *clef));
+                }
+                _ => {}
+            }
+        }
+
+        self.xml.open("attributes", &[]);
+        if is_first {
+            self.xml.text("divisions", &[], &self.divisions.to_string());
+        }
+
+        for (staff, key) in shared(&keys, staves) {
+            let number = staff.map(|staff| (staff + 1).to_string());
+            self.xml.open("key", &number_attribute(&number));
+            self.xml.text("fifths", &[], &key.fifths().to_string());
+            self.xml.text("mode", &[], &key.mode.to_string());
+            self.xml.close("key");
+        }
+
+        for (staff, time) in shared(&times, staves) {
+            let number = staff.map(|staff| (staff + 1).to_string());
+            let time = time.to_string();
+            let (beats, beat_type) = time.split_once('/').unwrap_or_default();
+            self.xml.open("time", &number_attribute(&number));
+            self.xml.text("beats", &[], beats);
+            self.xml.text("beat-type", &[], beat_type);
+            self.xml.close("time");
+        }
+
+        if is_first && staves > 1 {
+            self.xml.text("staves", &[], &staves.to_string());
+        }
+
+        for (staff, clef) in clefs {
+            let number = (staves > 1).then(|| (staff + 1).to_string());
+            let (sign, line) = match clef {
+                Clef::Treble => ("G", "2"),
+                Clef::Bass => ("F", "4"),
+                Clef::Alto => ("C", "3"),
+                Clef::Tenor => ("C", "4"),
+            };
+            self.xml.open("clef", &number_attribute(&number));
+            self.xml.text("sign", &[], sign);
+            self.xml.text("line", &[], line);
+            self.xml.close("clef");
+        }
+        self.xml.close("attributes");
+    }
+
+    fn write_note(&mut self, note: &NoteEvent, staff: usize, voice: usize, tuplet: (bool, bool)) {
+        for (index, head) in note.heads.iter().enumerate() {
+            self.xml.open("note", &[]);
+            if let Some(grace) = note.grace {
+                let slash: &[_] = if grace.slash {
+                    &[("slash", "yes")]
+                } else {
+                    &[]
+                };
+                self.xml.empty("grace", slash);
+            }
+            if index > 0 {
+                self.xml.empty("chord", &[]);
+            }
+
+            self.xml.open("pitch", &[]);
+            self.xml.text("step", &[], &head.note.natural.to_string());
+            let alter = head.note.accidental.semitones();
+            if alter != 0 {
+                self.xml.text("alter", &[], &alter.to_string());
+            }
+            self.xml
+                .text("octave", &[], &head.octave.into_i8().to_string());
+            self.xml.close("pitch");
+
+            if note.grace.is_none() {
+                let divisions = self.divisions_of(note.duration.length());
+                self.xml.text("duration", &[], &divisions.to_string());
+            }
+
+            let ties: &[&str] = match head.tie {
+                None => &[],
+                Some(Tie::Start) => &["start"],
+                Some(Tie::Stop) => &["stop"],
+                Some(Tie::Continue) => &["stop", "start"],
+            };
+            for tie in ties {
+                self.xml.empty("tie", &[("type", tie)]);
+            }
+
+            self.xml.text("voice", &[], &voice.to_string());
+            self.write_type(note.duration);
+            self.write_staff(staff);
+
+            let articulations = if index == 0 {
+                note.articulations.as_slice()
+            } else {
+                &[]
+            };
+            let tuplet = if index == 0 { tuplet } else { (false, false) };
+            if !ties.is_empty() || !articulations.is_empty() || tuplet != (false, false) {
+                self.xml.open("notations", &[]);
+                for tie in ties {
+                    self.xml.empty("tied", &[("type", tie)]);
+                }
+                self.write_tuplet(tuplet);
+                self.write_articulations(articulations);
+                self.xml.close("notations");
+            }
+
+            if index == 0 {
+                for lyric in &note.lyrics {
+                    let syllabic = match lyric.syllabic {
+                        Syllabic::Single => "single",
+                        Syllabic::Begin => "begin",
+                        Syllabic::Middle => "middle",
+                        Syllabic::End => "end",
+                    };
+                    self.xml
+                        .open("lyric", &[("number", &lyric.verse.to_string())]);
+                    self.xml.text("syllabic", &[], syllabic);
+                    self.xml.text("text", &[], &lyric.text);
+                    self.xml.close("lyric");
+                }
+            }
+            self.xml.close("note");
+        }
+    }
+
+    fn write_rest(&mut self, rest: &Rest, staff: usize, voice: usize, tuplet: (bool, bool)) {
+        self.xml.open("note", &[]);
+        if rest.is_measure {
+            self.xml.empty("rest", &[("measure", "yes")]);
+        } else {
+            self.xml.empty("rest", &[]);
+        }
+
+        let divisions = self.divisions_of(rest.duration.length());
+        self.xml.text("duration", &[], &divisions.to_string());
+        self.xml.text("voice", &[], &voice.to_string());
+
+        // Whole-measure rests are centered in the measure without a type
+        if !rest.is_measure {
+            self.write_type(rest.duration);
+        }
+        self.write_staff(staff);
+
+        if tuplet != (false, false) {
+            self.xml.open("notations", &[]);
+            self.write_tuplet(tuplet);
+            self.xml.close("notations");
+        }
+        self.xml.close("note");
+    }
+
+    /// Write the type, dots and time modification of `duration`.
+    fn write_type(&mut self, duration: Duration) {
+        self.xml.text("type", &[], type_name(duration.kind));
+        for _ in 0..duration.dots {
+            self.xml.empty("dot", &[]);
+        }
+
+        if let Some(Tuplet { actual, normal }) = duration.tuplet {
+            self.xml.open("time-modification", &[]);
+            self.xml.text("actual-notes", &[], &actual.to_string());
+            self.xml.text("normal-notes", &[], &normal.to_string());
+            self.xml.close("time-modification");
+        }
+    }
+
+    fn write_tuplet(&mut self, (start, stop): (bool, bool)) {
+        if start {
+            self.xml
+                .empty("tuplet", &[("type", "start"), ("bracket", "yes")]);
+        }
+        if stop {
+            self.xml.empty("tuplet", &[("type", "stop")]);
+        }
+    }
+
+    fn write_articulations(&mut self, articulations: &[Articulation]) {
+        let marks: Vec<_> = articulations
+            .iter()
+            .filter_map(|articulation| match articulation {
+                Articulation::Staccato => Some("staccato"),
+                Articulation::Staccatissimo => Some("staccatissimo"),
+                Articulation::Tenuto => Some("tenuto"),
+                Articulation::Accent => Some("accent"),
+                Articulation::Marcato => Some("strong-accent"),
+                Articulation::Fermata => None,
+            })
+            .collect();
+
+        if !marks.is_empty() {
+            self.xml.open("articulations", &[]);
+            for mark in marks {
+                self.xml.empty(mark, &[]);
+            }
+            self.xml.close("articulations");
+        }
+
+        if articulations.contains(&Articulation::Fermata) {
+            self.xml.empty("fermata", &[]);
+        }
+    }
+
+    fn write_harmony(&mut self, symbol: &ChordSymbol, staff: usize) {
+        self.xml.open("harmony", &[]);
+        self.xml.open("root", &[]);
+        self.write_step(symbol.root, "root");
+        self.xml.close("root");
+
+        let value = chord_kind_value(&symbol.kind).unwrap_or("other");
+        self.xml.text("kind", &[("text", &symbol.kind)], value);
+
+        if let Some(bass) = symbol.bass {
+            self.xml.open("bass", &[]);
+            self.write_step(bass, "bass");
+            self.xml.close("bass");
+        }
+        self.write_staff(staff);
+        self.xml.close("harmony");
+    }
+
+    /// Write the step and alter of `note` in a harmony, such as `<root-step>`.
+    fn write_step(&mut self, note: Note, prefix: &str) {
+        self.xml
+            .text(&format!("{}-step", prefix), &[], &note.natural.to_string());
+        let alter = note.accidental.semitones();
+        if alter != 0 {
+            self.xml
+                .text(&format!("{}-alter", prefix), &[], &alter.to_string());
+        }
+    }
+}
+
+fn is_attribute(event: &Event) -> bool {
+    matches!(event, Event::Clef(_) | Event::Key(_) | Event::Time(_))
+}
+
+/// Returns a single value without a staff if every staff has the same one,
+/// or else each value with its staff.
+fn shared<T: Copy + PartialEq>(values: &[(usize, T)], staves: usize) -> Vec<(Option<usize>, T)> {
+    match values.first() {
+        Some((_, first))
+            if staves == 1
+                || (values.len() == staves && values.iter().all(|(_, value)| value == first)) =>
+        {
+            vec![(None, *first)]
+        }
+        _ => values
+            .iter()
+            .map(|(staff, value)| (Some(*staff), *value))
+            .collect(),
+    }
+}
+
+fn number_attribute(number: &Option<String>) -> Vec<(&str, &str)> {
+    number
+        .iter()
+        .map(|number| ("number", number.as_str()))
+        .collect()
+}
+
+/// Returns whether each of `events` starts or stops a tuplet bracket.
+///
+/// A bracket stops when its notes fill the tuplet's actual number of its shortest note,
+/// or when the next note isn't in the same tuplet.
+fn tuplets(events: &[Event]) -> Vec<(bool, bool)> {
+    struct Group {
+        tuplet: Tuplet,
+        last: usize,
+        /// Written length of the notes without the tuplet.
+        written: Fraction,
+        shortest: Fraction,
+    }
+
+    let mut marks = vec![(false, false); events.len()];
+    let mut group: Option<Group> = None;
+    for (index, event) in events.iter().enumerate() {
+        let duration = match event {
+            Event::Note(note) if note.grace.is_none() => note.duration,
+            Event::Rest(rest) => rest.duration,
+            _ => continue,
+        };
+
+        if let Some(open) = &group {
+            if duration.tuplet != Some(open.tuplet) {
+                marks[open.last].1 = true;
+                group = None;
+            }
+        }
+
+        let Some(tuplet) = duration.tuplet else {
+            continue;
+        };
+        let open = group.get_or_insert_with(|| {
+            marks[index].0 = true;
+            Group {
+                tuplet,
+                last: index,
+                written: Fraction::ZERO,
+                shortest: duration.kind.length(),
+            }
+        });
+        open.last = index;
+        open.written += Duration {
+            tuplet: None,
+            ..duration
+        }
+        .length();
+        open.shortest = open.shortest.min(duration.kind.length());
+
+        if open.written == open.shortest * tuplet.actual as u64 {
+            marks[index].1 = true;
+            group = None;
+        }
+    }
+
+    if let Some(open) = group {
+        marks[open.last].1 = true;
+    }
+    marks
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    a / gcd(a, b) * b
+}
+
+/// Indented XML output.
+struct Xml {
+    out: String,
+    depth: usize,
+}
+
+impl Xml {
+    fn start(&mut self, tag: &str, attributes: &[(&str, &str)]) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+        self.out.push('<');
+        self.out.push_str(tag);
+        for (name, value) in attributes {
+            let _ = write!(self.out, " {}=\"{}\"", name, Escape(value));
+        }
+    }
+
+    fn open(&mut self, tag: &str, attributes: &[(&str, &str)]) {
+        self.start(tag, attributes);
+        self.out.push_str(">\n");
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+        let _ = writeln!(self.out, "</{}>", tag);
+    }
+
+    fn empty(&mut self, tag: &str, attributes: &[(&str, &str)]) {
+        self.start(tag, attributes);
+        self.out.push_str("/>\n");
+    }
+
+    fn text(&mut self, tag: &str, attributes: &[(&str, &str)], text: &str) {
+        self.start(tag, attributes);
+        let _ = writeln!(self.out, ">{}</{}>", Escape(text), tag);
+    }
+}
+
+/// Text escaped for XML content and attribute values.
+struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::Octave,
+        musicxml::read,
+        score::{Dynamic, Grace, Lyric},
+        time::DurationKind,
+        Natural, Pitch,
+    };
+
+    #[test]
+    fn it_reads_what_it_writes() {
+        let eighth = Duration::from(DurationKind::Eigth);
+        let triplet = Duration::from(DurationKind::Eigth).with_tuplet(Tuplet::TRIPLET);
+        let head = |note, octave| Notehead::new(note, Octave::new_unchecked(octave));
+        let key = Key::minor(Pitch::G);
+        let time = TimeSignature::new(DurationKind::Quarter, 2);
+
+        let mut upper = Voice::new();
+        upper.push(Event::Key(key));
+        upper.push(Event::Time(time));
+        upper.push(Event::Clef(Clef::Treble));
+        upper.push(Event::Dynamic(Dynamic::MP));
+        upper.push(Event::ChordSymbol(ChordSymbol {
+            root: Note::G,
+            kind: "m".to_owned(),
+            bass: Some(Note::flat(Natural::B)),
+        }));
+        upper.push(
+            NoteEvent::note(head(Note::sharp(Natural::F), 5), eighth)
+                .with_grace(Grace { slash: true }),
+        );
+        for (note, syllabic) in [(Note::G, Syllabic::Begin), (Note::A, Syllabic::End)] {
+            let mut lyric = Lyric::new("la & \"li\"");
+            lyric.syllabic = syllabic;
+            upper.push(
+                NoteEvent::chord([head(note, 5), head(Note::flat(Natural::B), 4)], triplet)
+                    .with_lyric(lyric),
+            );
+        }
+        upper.push(Rest::new(triplet));
+        upper.push(
+            NoteEvent::note(head(Note::D, 5), Duration::from(DurationKind::Quarter))
+                .with_tie(Tie::Start)
+                .with_articulation(Articulation::Accent)
+                .with_articulation(Articulation::Fermata),
+        );
+
+        let mut lower = Voice::new();
+        lower.push(NoteEvent::note(
+            head(Note::G, 4),
+            Duration::new(DurationKind::Quarter, 1),
+        ));
+        lower.push(Rest::new(eighth));
+
+        let mut bass = Voice::new();
+        bass.push(Event::Key(key));
+        bass.push(Event::Time(time));
+        bass.push(Event::Clef(Clef::Bass));
+        bass.push(Rest::measure(time).unwrap());
+
+        let mut tied = Voice::new();
+        tied.push(
+            NoteEvent::note(head(Note::D, 5), Duration::from(DurationKind::Half))
+                .with_tie(Tie::Stop),
+        );
+
+        let mut part = Part::new("P1", "Piano & Voice");
+        part.staves = vec![
+            Staff {
+                measures: vec![
+                    Measure {
+                        number: 1,
+                        voices: vec![upper, lower],
+                    },
+                    Measure::new(2, tied),
+                ],
+            },
+            Staff {
+                measures: vec![
+                    Measure::new(1, bass),
+                    Measure::new(
+                        2,
+                        [Rest::measure(time).unwrap().into()].into_iter().collect(),
+                    ),
+                ],
+            },
+        ];
+        let score = Score {
+            title: Some("Études".to_owned()),
+            composer: Some("A < B".to_owned()),
+            parts: vec![part],
+        };
+
+        let xml = write(&score);
+        assert!(xml.contains(r#"<tuplet type="start" bracket="yes"/>"#));
+        let import = read(&xml).unwrap();
+        assert_eq!(import.warnings, []);
+        assert_eq!(import.score, score);
+    }
+
+    #[test]
+    fn it_brackets_tuplets() {
+        let triplet = |kind| Duration::from(kind).with_tuplet(Tuplet::TRIPLET);
+        let rest = |duration| Event::Rest(Rest::new(duration));
+        let events = [
+            rest(triplet(DurationKind::Quarter)),
+            rest(triplet(DurationKind::Eigth)),
+            rest(triplet(DurationKind::Eigth)),
+            rest(triplet(DurationKind::Eigth)),
+            rest(triplet(DurationKind::Eigth)),
+            rest(Duration::from(DurationKind::Quarter)),
+        ];
+
+        assert_eq!(
+            tuplets(&events),
+            [
+                (true, false),
+                (false, true),
+                (true, false),
+                (false, false),
+                (false, true),
+                (false, false)
+            ]
+        );
+    }
+}
@@ -23,6 +23,36 @@ impl Accidental {
             _ => false,
         }
     }
+
+    /// Returns the number of semitones this accidental raises a natural by.
+    /// ```
+    /// use staff::note::Accidental;
+    ///
+    /// assert_eq!(Accidental::DoubleFlat.semitones(), -2);
+    /// ```
+    pub const fn semitones(self) -> i8 {
+        match self {
+            Self::Natural => 0,
+            Self::Flat => -1,
+            Self::DoubleFlat => -2,
+            Self::Sharp => 1,
+            Self::DoubleSharp => 2,
+        }
+    }
+
+    /// Returns the accidental that raises a natural by `semitones`,
+    /// or `None` if there isn't one.
+    pub const fn from_semitones(semitones: i8) -> Option<Self> {
+        let accidental = match semitones {
+            0 => Self::Natural,
+            -1 => Self::Flat,
+            -2 => Self::DoubleFlat,
+            1 => Self::Sharp,
+            2 => Self::DoubleSharp,
+            _ => return None,
+        };
+        Some(accidental)
+    }
 }
 
 impl fmt::Display for Accidental {
@@ -85,6 +115,52 @@ impl Note {
     pub const fn double_sharp(natural: Natural) -> Self {
         Self::new(natural, Accidental::DoubleSharp)
     }
+
+    /// Returns the number of perfect fifths from C to this note.
+    /// ```
+    /// use staff::{Natural, Note};
+    ///
+    /// assert_eq!(Note::sharp(Natural::F).fifths(), 6);
+    /// assert_eq!(Note::flat(Natural::E).fifths(), -3);
+    /// ```
+    pub const fn fifths(self) -> i8 {
+        let letter = match self.natural {
+            Natural::F => -1,
+            Natural::C => 0,
+            Natural::G => 1,
+            Natural::D => 2,
+            Natural::A => 3,
+            Natural::E => 4,
+            Natural::B => 5,
+        };
+        letter + self.accidental.semitones() * 7
+    }
+
+    /// Returns the note a number of perfect `fifths` from C,
+    /// or `None` if it would need more than a double sharp or double flat.
+    /// ```
+    /// use staff::{Natural, Note};
+    ///
+    /// assert_eq!(Note::from_fifths(-6), Some(Note::flat(Natural::G)));
+    /// ```
+    pub const fn from_fifths(fifths: i8) -> Option<Self> {
+        const LETTERS: [Natural; 7] = [
+            Natural::F,
+            Natural::C,
+            Natural::G,
+            Natural::D,
+            Natural::A,
+            Natural::E,
+            Natural::B,
+        ];
+
+        let idx = fifths as i16 + 1;
+        let natural = LETTERS[idx.rem_euclid(7) as usize];
+        match Accidental::from_semitones(idx.div_euclid(7) as i8) {
+            Some(accidental) => Some(Self::new(natural, accidental)),
+            None => None,
+        }
+    }
 }
 
 impl From<Natural> for Note {
@@ -102,10 +178,21 @@ impl fmt::Display for Note {
 impl FromStr for Note {
     type Err = Error;
 
+    /// Parse a note from its letter and an optional accidental,
+    /// written either in ASCII (`b`, `bb`, `#`, `##`) or with symbols (`♭`, `♯`, `♮`, `𝄫`, `𝄪`).
+    /// ```
+    /// use staff::{Natural, Note};
+    ///
+    /// let note: Note = "Bb".parse().unwrap();
+    /// assert_eq!(note, Note::flat(Natural::B));
+    ///
+    /// let note: Note = "f♯".parse().unwrap();
+    /// assert_eq!(note, Note::sharp(Natural::F));
+    /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut chars = s.chars();
         let natural: Natural = if let Some(c) = chars.next() {
-            c.try_into().unwrap()
+            c.try_into()?
         } else {
             return Err(Error::Empty);
         };
@@ -121,10 +208,19 @@ impl FromStr for Note {
                 Some(c) => return Err(c.into()),
                 None => Accidental::Sharp,
             },
+            Some('♭') => Accidental::Flat,
+            Some('♯') => Accidental::Sharp,
+            Some('♮') => Accidental::Natural,
+            Some('𝄫') => Accidental::DoubleFlat,
+            Some('𝄪') => Accidental::DoubleSharp,
             Some(c) => return Err(c.into()),
             None => Accidental::Natural,
         };
 
+        if let Some(c) = chars.next() {
+            return Err(c.into());
+        }
+
         Ok(Self::new(natural, accidental))
     }
 }
@@ -65,8 +65,14 @@ impl Pitch {
         self as _
     }
 
+    /// Returns the ascending interval from `rhs` up to `self`.
+    /// ```
+    /// use staff::{Interval, Pitch};
+    ///
+    /// assert_eq!(Pitch::C.sub(Pitch::A), Interval::MINOR_THIRD);
+    /// ```
     pub const fn sub(self, rhs: Self) -> Interval {
-        Interval::new(self as u8 - rhs as u8)
+        Interval::new((self as u8 + Self::B as u8 + 1 - rhs as u8) % (Self::B as u8 + 1))
     }
 
     pub fn transpose(self, key: Pitch, to: Pitch) -> Pitch {
@@ -11,6 +11,20 @@ pub trait Degree {
     fn state(self) -> Self::State;
 
     fn degree(self, state: &mut Self::State, interval: Interval) -> Self;
+
+    /// Move `self` by a number of octaves, returning `None` if the result can't be represented.
+    ///
+    /// By default only moving by zero octaves can be represented.
+    fn add_octaves(self, octaves: i8) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if octaves == 0 {
+            Some(self)
+        } else {
+            None
+        }
+    }
 }
 
 impl Degree for Note {
@@ -47,6 +61,10 @@ impl Degree for Note {
 
         Self::new(natural, accidental)
     }
+
+    fn add_octaves(self, _octaves: i8) -> Option<Self> {
+        Some(self)
+    }
 }
 
 impl Degree for Pitch {
@@ -57,6 +75,10 @@ impl Degree for Pitch {
     fn degree(self, _state: &mut Self::State, interval: Interval) -> Self {
         self + interval
     }
+
+    fn add_octaves(self, _octaves: i8) -> Option<Self> {
+        Some(self)
+    }
 }
 
 impl Degree for MidiNote {
@@ -67,4 +89,12 @@ impl Degree for MidiNote {
     fn degree(self, _state: &mut Self::State, interval: Interval) -> Self {
         self + interval
     }
+
+    fn add_octaves(self, octaves: i8) -> Option<Self> {
+        let byte = self.into_byte() as i16 + octaves as i16 * Interval::OCTAVE.semitones() as i16;
+        u8::try_from(byte)
+            .ok()
+            .filter(|byte| *byte <= 127)
+            .map(Self::from_byte)
+    }
 }
@@ -0,0 +1,232 @@
+use super::ScaleSet;
+use crate::{midi::MidiNote, set::IntervalSet, Chord, Interval};
+use core::fmt::{self, Write};
+
+/// A chord built on a degree of a scale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiatonicChord {
+    /// The zero-based scale degree this chord is built on.
+    pub degree: usize,
+    pub chord: Chord,
+}
+
+impl DiatonicChord {
+    /// Returns the roman numeral label of this chord.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 4));
+    /// let numerals = scale.sevenths().map(|chord| chord.numeral().to_string());
+    ///
+    /// assert!(numerals.eq(["Imaj7", "ii7", "iii7", "IVmaj7", "V7", "vi7", "viiø7"]));
+    /// ```
+    pub fn numeral(&self) -> Numeral {
+        Numeral {
+            degree: self.degree,
+            intervals: self.chord.clone().intervals().collect(),
+        }
+    }
+}
+
+/// Roman numeral label of a [`DiatonicChord`].
+pub struct Numeral {
+    degree: usize,
+    intervals: IntervalSet,
+}
+
+impl fmt::Display for Numeral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+
+        let is_major = self.intervals.contains(Interval::MAJOR_THIRD);
+        let is_minor = self.intervals.contains(Interval::MINOR_THIRD);
+        let numeral = NUMERALS.get(self.degree).copied().unwrap_or("?");
+
+        // Chords without a third, such as quartal chords, have no quality
+        if !is_major && !is_minor {
+            return f.write_str(numeral);
+        }
+
+        if is_major {
+            f.write_str(numeral)?;
+        } else {
+            for c in numeral.chars() {
+                f.write_char(c.to_ascii_lowercase())?;
+            }
+        }
+
+        let is_diminished = !is_major
+            && self.intervals.contains(Interval::TRITONE)
+            && !self.intervals.contains(Interval::PERFECT_FIFTH);
+        let is_augmented = is_major && self.intervals.contains(Interval::MINOR_SIXTH);
+
+        // A major ninth replaces the seventh in the label, other ninths are added after it
+        let extension = if self.intervals.contains(Interval::MAJOR_NINTH) {
+            '9'
+        } else {
+            '7'
+        };
+        if self.intervals.contains(Interval::MAJOR_SEVENTH) {
+            if is_augmented {
+                f.write_char('+')?;
+            }
+            f.write_str("maj")?;
+            f.write_char(extension)?;
+        } else if self.intervals.contains(Interval::MINOR_SEVENTH) {
+            if is_diminished {
+                f.write_char('ø')?;
+            }
+            f.write_char(extension)?;
+        } else if is_diminished && self.intervals.contains(Interval::MAJOR_SIXTH) {
+            // The diminished seventh is spelled as a major sixth above the root
+            f.write_char('°')?;
+            f.write_char(extension)?;
+        } else if is_diminished {
+            f.write_char('°')?;
+        } else if is_augmented {
+            f.write_char('+')?;
+        }
+
+        if self.intervals.contains(Interval::MINOR_NINTH) {
+            f.write_str("b9")?;
+        } else if self.intervals.contains(Interval::AUGMENTED_NINTH) {
+            f.write_str("#9")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterator over the chords built on each degree of a [`ScaleSet`].
+///
+/// Created by [`ScaleSet::triads`], [`ScaleSet::sevenths`], [`ScaleSet::ninths`],
+/// [`ScaleSet::quartal`] and [`ScaleSet::stacks`].
+pub struct Harmonize {
+    scale: ScaleSet<MidiNote>,
+    step: i32,
+    notes: i32,
+    degree: usize,
+}
+
+impl Iterator for Harmonize {
+    type Item = DiatonicChord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.degree < self.scale.len() {
+            let degree = self.degree;
+            self.degree += 1;
+
+            let notes =
+                (0..self.notes).map(|idx| self.scale.degree(degree as i32 + idx * self.step));
+            if notes.clone().all(|note| note.is_some()) {
+                let root = self.scale.degree(degree as i32)?;
+                let chord = Chord::from_midi(root, notes.flatten())?;
+                return Some(DiatonicChord { degree, chord });
+            }
+        }
+
+        None
+    }
+}
+
+impl ScaleSet<MidiNote> {
+    /// Returns an iterator over the chords built on each degree of this scale
+    /// by stacking `notes` scale tones that are `step` degrees apart.
+    ///
+    /// Degrees whose chords would fall outside the MIDI range are skipped.
+    pub fn stacks(&self, step: i32, notes: i32) -> Harmonize {
+        Harmonize {
+            scale: *self,
+            step,
+            notes,
+            degree: 0,
+        }
+    }
+
+    /// Returns an iterator over the triads built on each degree of this scale.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::natural_minor(midi!(A, 3));
+    /// let names = scale.triads().map(|chord| chord.chord.to_string());
+    ///
+    /// assert!(names.eq(["Am", "Bmb5", "C", "Dm", "Em", "F", "G"]));
+    /// ```
+    pub fn triads(&self) -> Harmonize {
+        self.stacks(2, 3)
+    }
+
+    /// Returns an iterator over the seventh chords built on each degree of this scale.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 4));
+    /// let names = scale.sevenths().map(|chord| chord.chord.to_string());
+    ///
+    /// assert!(names.eq(["Cmaj7", "Dm7", "Em7", "Fmaj7", "G7", "Am7", "Bm7b5"]));
+    /// ```
+    pub fn sevenths(&self) -> Harmonize {
+        self.stacks(2, 4)
+    }
+
+    /// Returns an iterator over the ninth chords built on each degree of this scale.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 3));
+    /// let numerals = scale.ninths().map(|chord| chord.numeral().to_string());
+    ///
+    /// assert!(numerals.eq(["Imaj9", "ii9", "iii7b9", "IVmaj9", "V9", "vi9", "viiø7b9"]));
+    /// ```
+    pub fn ninths(&self) -> Harmonize {
+        self.stacks(2, 5)
+    }
+
+    /// Returns an iterator over the three note chords stacked in fourths
+    /// on each degree of this scale.
+    ///
+    /// Their numerals have no quality, as they have no third.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 3));
+    /// let numerals = scale.quartal().map(|chord| chord.numeral().to_string());
+    ///
+    /// assert!(numerals.eq(["I", "II", "III", "IV", "V", "VI", "VII"]));
+    /// ```
+    pub fn quartal(&self) -> Harmonize {
+        self.stacks(3, 3)
+    }
+}
+
+impl<T> ScaleSet<T>
+where
+    T: Clone + Into<crate::Pitch>,
+{
+    /// Returns `true` if every note of `chord` is part of this scale.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet, Chord, Pitch};
+    ///
+    /// let scale = ScaleSet::major(Pitch::G);
+    ///
+    /// assert!(scale.is_diatonic(&Chord::seventh(midi!(D, 4))));
+    /// assert!(!scale.is_diatonic(&Chord::major(midi!(F, 4))));
+    /// ```
+    pub fn is_diatonic(&self, chord: &Chord) -> bool {
+        chord.clone().into_iter().all(|midi| self.contains(midi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{midi::MidiNote, scale::ScaleSet};
+
+    #[test]
+    fn it_labels_harmonic_minor_sevenths() {
+        let scale = ScaleSet::harmonic_minor(MidiNote::from_byte(69));
+        let numerals = scale.sevenths().map(|chord| chord.numeral().to_string());
+
+        assert!(numerals.eq(["imaj7", "iiø7", "III+maj7", "iv7", "V7", "VImaj7", "vii°7"]));
+    }
+}
@@ -121,4 +121,143 @@ impl ScaleIntervals {
             Interval::MINOR_SEVENTH,
         ])
     }
+
+    /// The fourth mode of melodic minor.
+    pub fn lydian_dominant() -> Self {
+        Self::from_iter([
+            Interval::UNISON,
+            Interval::MAJOR_SECOND,
+            Interval::MAJOR_THIRD,
+            Interval::TRITONE,
+            Interval::PERFECT_FIFTH,
+            Interval::MAJOR_SIXTH,
+            Interval::MINOR_SEVENTH,
+        ])
+    }
+
+    /// The fifth mode of harmonic minor.
+    pub fn phrygian_dominant() -> Self {
+        Self::from_iter([
+            Interval::UNISON,
+            Interval::MINOR_SECOND,
+            Interval::MAJOR_THIRD,
+            Interval::PERFECT_FOURTH,
+            Interval::PERFECT_FIFTH,
+            Interval::MINOR_SIXTH,
+            Interval::MINOR_SEVENTH,
+        ])
+    }
+
+    pub fn major_pentatonic() -> Self {
+        Self::from_iter([
+            Interval::UNISON,
+            Interval::MAJOR_SECOND,
+            Interval::MAJOR_THIRD,
+            Interval::PERFECT_FIFTH,
+            Interval::MAJOR_SIXTH,
+        ])
+    }
+
+    pub fn minor_pentatonic() -> Self {
+        Self::from_iter([
+            Interval::UNISON,
+            Interval::MINOR_THIRD,
+            Interval::PERFECT_FOURTH,
+            Interval::PERFECT_FIFTH,
+            Interval::MINOR_SEVENTH,
+        ])
+    }
+
+    /// Returns the form of this scale played while descending.
+    ///
+    /// Melodic minor descends as natural minor, every other scale is symmetric.
+    /// ```
+    /// use staff::scale::ScaleIntervals;
+    ///
+    /// assert_eq!(
+    ///     ScaleIntervals::melodic_minor().descending(),
+    ///     ScaleIntervals::natural_minor()
+    /// );
+    /// ```
+    pub fn descending(self) -> Self {
+        if self == Self::melodic_minor() {
+            Self::natural_minor()
+        } else {
+            self
+        }
+    }
+
+    /// Returns the intervals of the scale with the given `name`, ignoring case and
+    /// extra whitespace.
+    /// ```
+    /// use staff::scale::ScaleIntervals;
+    ///
+    /// assert_eq!(
+    ///     ScaleIntervals::from_name("Harmonic  Minor"),
+    ///     Some(ScaleIntervals::harmonic_minor())
+    /// );
+    /// assert_eq!(ScaleIntervals::from_name("ionian"), Some(ScaleIntervals::major()));
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        NAMES
+            .iter()
+            .chain(ALIASES)
+            .find(|(known, _)| eq_words(known, name))
+            .map(|(_, intervals)| intervals())
+    }
+
+    /// Returns the name of this scale, if it has one.
+    /// ```
+    /// use staff::scale::ScaleIntervals;
+    ///
+    /// assert_eq!(ScaleIntervals::lydian_dominant().name(), Some("lydian dominant"));
+    /// ```
+    pub fn name(self) -> Option<&'static str> {
+        NAMES
+            .iter()
+            .find(|(_, intervals)| intervals() == self)
+            .map(|(name, _)| *name)
+    }
+
+    /// Returns an iterator over the names of every known scale.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        NAMES.iter().map(|(name, _)| *name)
+    }
+}
+
+type Named = (&'static str, fn() -> ScaleIntervals);
+
+const NAMES: &[Named] = &[
+    ("major", ScaleIntervals::major),
+    ("natural minor", ScaleIntervals::natural_minor),
+    ("harmonic minor", ScaleIntervals::harmonic_minor),
+    ("melodic minor", ScaleIntervals::melodic_minor),
+    ("blues", ScaleIntervals::blues),
+    ("dorian", ScaleIntervals::dorian),
+    ("phrygian", ScaleIntervals::phrygian),
+    ("lydian", ScaleIntervals::lydian),
+    ("mixolydian", ScaleIntervals::mixolydian),
+    ("locrian", ScaleIntervals::locrian),
+    ("lydian dominant", ScaleIntervals::lydian_dominant),
+    ("phrygian dominant", ScaleIntervals::phrygian_dominant),
+    ("major pentatonic", ScaleIntervals::major_pentatonic),
+    ("minor pentatonic", ScaleIntervals::minor_pentatonic),
+];
+
+const ALIASES: &[Named] = &[
+    ("ionian", ScaleIntervals::major),
+    ("minor", ScaleIntervals::natural_minor),
+    ("aeolian", ScaleIntervals::natural_minor),
+];
+
+fn eq_words(a: &str, b: &str) -> bool {
+    let mut a = a.split_whitespace();
+    let mut b = b.split_whitespace();
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => {}
+            (None, None) => break true,
+            _ => break false,
+        }
+    }
 }
@@ -1,7 +1,7 @@
 //! Scales with iterators
 
-use crate::Interval;
-use core::ops::Add;
+use crate::{note, Interval, Note};
+use core::{fmt, ops::Add, str::FromStr};
 
 mod degree;
 pub use degree::Degree;
@@ -12,6 +12,16 @@ pub use intervals::ScaleIntervals;
 mod diatonic;
 pub use diatonic::{Diatonic, DiatonicScale};
 
+mod harmony;
+pub use harmony::{DiatonicChord, Harmonize, Numeral};
+
+mod run;
+pub use run::{Direction, Pattern, Run};
+
+mod scale_set;
+pub use scale_set::ScaleSet;
+
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scale<T, U> {
     root: T,
@@ -41,8 +51,8 @@ impl<T> Scale<T, ScaleIntervals> {
 
 impl<T, U> Iterator for Scale<T, U>
 where
-    T: Add<Interval> + Clone,
-    U: Iterator<Item = Interval>,
+    T: Add<U::Item> + Clone,
+    U: Iterator,
 {
     type Item = T::Output;
 
@@ -69,6 +79,67 @@ where
     }
 }
 
+impl FromStr for DiatonicScale<Note> {
+    type Err = Error;
+
+    /// Parse a scale from its tonic and name, like [`ScaleSet`].
+    /// ```
+    /// use staff::{scale::DiatonicScale, Natural, Note};
+    ///
+    /// let scale: DiatonicScale<Note> = "F# lydian dominant".parse().unwrap();
+    ///
+    /// assert!(scale.eq([
+    ///     Note::sharp(Natural::F),
+    ///     Note::sharp(Natural::G),
+    ///     Note::sharp(Natural::A),
+    ///     Note::sharp(Natural::B),
+    ///     Note::sharp(Natural::C),
+    ///     Note::sharp(Natural::D),
+    ///     Note::E,
+    /// ]));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<ScaleSet<Note>>().map(Into::into)
+    }
+}
+
+/// Error parsing a scale.
+#[derive(Debug)]
+pub enum Error {
+    Empty,
+    Note(note::Error),
+    UnknownName,
+    InvalidInterval,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Empty scale input"),
+            Self::Note(error) => write!(f, "Invalid tonic: {}", error),
+            Self::UnknownName => {
+                f.write_str("Unknown scale name, expected one of: ")?;
+                for (idx, name) in ScaleIntervals::names().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(name)?;
+                }
+                Ok(())
+            }
+            Self::InvalidInterval => {
+                f.write_str("Invalid interval, expected semitones from 0 to 11")
+            }
+        }
+    }
+}
+
+impl From<note::Error> for Error {
+    fn from(error: note::Error) -> Self {
+        Self::Note(error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Natural, Note, Scale};
@@ -0,0 +1,270 @@
+use super::{Degree, ScaleIntervals, ScaleSet};
+use crate::{midi::MidiNote, Interval, Pitch};
+
+/// Direction of a [`Run`] through a scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    Ascending,
+    Descending,
+    /// Ascend to the top note and descend back without repeating it.
+    UpDown,
+}
+
+/// Iterator over the notes of a scale between two MIDI notes.
+///
+/// Descending notes use the descending form of the scale, see [`ScaleIntervals::descending`].
+/// ```
+/// use staff::{midi, scale::{Direction, ScaleSet}};
+///
+/// // E melodic minor
+/// let scale = ScaleSet::melodic_minor(midi!(E, 4));
+///
+/// assert!(scale.run(Direction::UpDown, 1).eq([
+///     midi!(E, 4),
+///     midi!(FSharp, 4),
+///     midi!(G, 4),
+///     midi!(A, 4),
+///     midi!(B, 4),
+///     midi!(CSharp, 5),
+///     midi!(DSharp, 5),
+///     midi!(E, 5),
+///     midi!(D, 5),
+///     midi!(C, 5),
+///     midi!(B, 4),
+///     midi!(A, 4),
+///     midi!(G, 4),
+///     midi!(FSharp, 4),
+///     midi!(E, 4),
+/// ]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Run {
+    ascending: ScaleSet<Pitch>,
+    descending: ScaleSet<Pitch>,
+    low: u8,
+    high: u8,
+    up: Option<u8>,
+    down: Option<u8>,
+    turn: bool,
+}
+
+impl Run {
+    /// Create a new run of `scale` from `low` up to and including `high`.
+    pub fn new<T>(scale: &ScaleSet<T>, low: MidiNote, high: MidiNote, direction: Direction) -> Self
+    where
+        T: Clone + Into<Pitch>,
+    {
+        let root = scale.root.clone().into();
+        let (low, high) = (low.into_byte(), high.into_byte());
+        let (up, down) = match direction {
+            Direction::Ascending => (Some(low), None),
+            Direction::Descending => (None, Some(high)),
+            Direction::UpDown => (Some(low), None),
+        };
+
+        Self {
+            ascending: ScaleSet::new(root, scale.intervals),
+            descending: ScaleSet::new(root, scale.intervals.descending()),
+            low,
+            high,
+            up,
+            down,
+            turn: direction == Direction::UpDown,
+        }
+    }
+
+    /// Use `intervals` for the descending notes of this run.
+    /// ```
+    /// use staff::{midi, scale::{Direction, ScaleIntervals, ScaleSet}};
+    ///
+    /// // Descend melodic minor in its ascending form
+    /// let scale = ScaleSet::melodic_minor(midi!(A, 4));
+    /// let run = scale
+    ///     .run(Direction::Descending, 1)
+    ///     .with_descending(ScaleIntervals::melodic_minor());
+    ///
+    /// assert_eq!(run.skip(1).next(), Some(midi!(GSharp, 5)));
+    /// ```
+    pub fn with_descending(mut self, intervals: ScaleIntervals) -> Self {
+        self.descending.intervals = intervals;
+        self
+    }
+}
+
+impl Iterator for Run {
+    type Item = MidiNote;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(from) = self.up {
+            let next =
+                (from..=self.high).find(|byte| self.ascending.contains(MidiNote::from_byte(*byte)));
+            self.up = next.and_then(|byte| byte.checked_add(1));
+
+            match next {
+                Some(byte) => {
+                    if self.turn {
+                        self.down = byte.checked_sub(1);
+                    }
+                    return Some(MidiNote::from_byte(byte));
+                }
+                None => self.up = None,
+            }
+        }
+
+        let from = self.down?;
+        let next = (self.low..=from)
+            .rev()
+            .find(|byte| self.descending.contains(MidiNote::from_byte(*byte)));
+        self.down = next
+            .and_then(|byte| byte.checked_sub(1))
+            .filter(|byte| *byte >= self.low);
+        next.map(MidiNote::from_byte)
+    }
+}
+
+/// Iterator over a pattern of scale degrees repeated from each starting degree.
+///
+/// Created by [`ScaleSet::pattern`].
+pub struct Pattern<'a, T, S> {
+    scale: &'a ScaleSet<T>,
+    pattern: &'a [i32],
+    starts: S,
+    start: Option<i32>,
+    pos: usize,
+    /// Whether the pattern has played a note from any start.
+    has_played: bool,
+    /// Whether the pattern has played a note from the current start.
+    is_playing: bool,
+}
+
+impl<T, S> Iterator for Pattern<'_, T, S>
+where
+    T: Degree + Clone,
+    S: Iterator<Item = i32>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pattern.is_empty() || self.scale.is_empty() {
+            return None;
+        }
+
+        loop {
+            let start = match self.start {
+                Some(start) if self.pos < self.pattern.len() => start,
+                _ => {
+                    // A whole start out of range after playing means the starts left the range
+                    if self.start.is_some() && self.has_played && !self.is_playing {
+                        return None;
+                    }
+
+                    self.start = Some(self.starts.next()?);
+                    self.pos = 0;
+                    self.is_playing = false;
+                    continue;
+                }
+            };
+
+            let offset = self.pattern[self.pos];
+            self.pos += 1;
+            if let Some(note) = start
+                .checked_add(offset)
+                .and_then(|degree| self.scale.degree(degree))
+            {
+                self.has_played = true;
+                self.is_playing = true;
+                return Some(note);
+            }
+        }
+    }
+}
+
+impl ScaleSet<MidiNote> {
+    /// Returns an iterator over `octaves` of this scale from its root.
+    /// ```
+    /// use staff::{midi, scale::{Direction, ScaleSet}};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 4));
+    /// let run = scale.run(Direction::Descending, 2);
+    ///
+    /// assert_eq!(run.clone().next(), Some(midi!(C, 6)));
+    /// assert_eq!(run.last(), Some(midi!(C, 4)));
+    /// ```
+    pub fn run(&self, direction: Direction, octaves: u8) -> Run {
+        let semitones = octaves as u16 * Interval::OCTAVE.semitones() as u16;
+        let high = (self.root.into_byte() as u16 + semitones).min(127) as u8;
+        self.run_between(self.root, MidiNote::from_byte(high), direction)
+    }
+}
+
+impl<T> ScaleSet<T>
+where
+    T: Clone + Into<Pitch>,
+{
+    /// Returns an iterator over the notes of this scale from `low` up to and including `high`.
+    pub fn run_between(&self, low: MidiNote, high: MidiNote, direction: Direction) -> Run {
+        Run::new(self, low, high, direction)
+    }
+}
+
+impl<T> ScaleSet<T>
+where
+    T: Degree + Clone,
+{
+    /// Returns an iterator over the zero-based degree offsets in `pattern`
+    /// played from each degree in `starts`.
+    ///
+    /// Notes that fall outside the range of `T` are skipped,
+    /// and once the pattern has played, the iterator ends at the first start with every note
+    /// out of range, so `starts` can be unbounded.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 4));
+    ///
+    /// // Thirds
+    /// let thirds = scale.pattern(&[0, 2], 0..3);
+    /// assert!(thirds.eq([
+    ///     midi!(C, 4),
+    ///     midi!(E, 4),
+    ///     midi!(D, 4),
+    ///     midi!(F, 4),
+    ///     midi!(E, 4),
+    ///     midi!(G, 4),
+    /// ]));
+    ///
+    /// // 1-2-3-1 descending from the octave
+    /// let pattern = scale.pattern(&[0, 1, 2, 0], (6..=7).rev());
+    /// assert!(pattern.eq([
+    ///     midi!(C, 5),
+    ///     midi!(D, 5),
+    ///     midi!(E, 5),
+    ///     midi!(C, 5),
+    ///     midi!(B, 4),
+    ///     midi!(C, 5),
+    ///     midi!(D, 5),
+    ///     midi!(B, 4),
+    /// ]));
+    ///
+    /// // Thirds up to the top of the MIDI range
+    /// let scale = ScaleSet::major(midi!(C, 9));
+    /// let thirds = scale.pattern(&[0, 2], 0..);
+    /// assert_eq!(thirds.count(), 8);
+    /// assert_eq!(scale.pattern(&[], 0..).next(), None);
+    /// ```
+    pub fn pattern<'a, S>(&'a self, pattern: &'a [i32], starts: S) -> Pattern<'a, T, S::IntoIter>
+    where
+        S: IntoIterator<Item = i32>,
+    {
+        Pattern {
+            scale: self,
+            pattern,
+            starts: starts.into_iter(),
+            start: None,
+            pos: 0,
+            has_played: false,
+            is_playing: false,
+        }
+    }
+}
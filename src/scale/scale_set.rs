@@ -0,0 +1,533 @@
+use super::{Degree, DiatonicScale, Error, Scale, ScaleIntervals};
+use crate::{
+    midi::MidiNote,
+    note::{Accidental, Note},
+    Interval, Pitch,
+};
+use core::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+/// A scale as a reusable value made of a root and the intervals above it.
+///
+/// Unlike [`Scale`], which is consumed after a single pass, a `ScaleSet` can be
+/// queried for its degrees, membership and nearest scale tones.
+/// ```
+/// use staff::{midi, scale::ScaleSet};
+///
+/// // D major
+/// let scale = ScaleSet::major(midi!(D, 4));
+///
+/// assert_eq!(scale.degree(4), Some(midi!(A, 4)));
+/// assert!(scale.contains(midi!(FSharp, 2)));
+/// assert_eq!(scale.degree_of(midi!(CSharp, 5)), Some(6));
+/// assert_eq!(scale.quantize(midi!(DSharp, 4)), midi!(D, 4));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScaleSet<T> {
+    pub root: T,
+    pub intervals: ScaleIntervals,
+}
+
+impl<T> ScaleSet<T> {
+    pub const fn new(root: T, intervals: ScaleIntervals) -> Self {
+        Self { root, intervals }
+    }
+
+    pub fn major(root: T) -> Self {
+        Self::new(root, ScaleIntervals::major())
+    }
+
+    pub fn natural_minor(root: T) -> Self {
+        Self::new(root, ScaleIntervals::natural_minor())
+    }
+
+    pub fn harmonic_minor(root: T) -> Self {
+        Self::new(root, ScaleIntervals::harmonic_minor())
+    }
+
+    pub fn melodic_minor(root: T) -> Self {
+        Self::new(root, ScaleIntervals::melodic_minor())
+    }
+
+    pub fn blues(root: T) -> Self {
+        Self::new(root, ScaleIntervals::blues())
+    }
+
+    pub fn ionian(root: T) -> Self {
+        Self::major(root)
+    }
+
+    pub fn dorian(root: T) -> Self {
+        Self::new(root, ScaleIntervals::dorian())
+    }
+
+    pub fn phrygian(root: T) -> Self {
+        Self::new(root, ScaleIntervals::phrygian())
+    }
+
+    pub fn lydian(root: T) -> Self {
+        Self::new(root, ScaleIntervals::lydian())
+    }
+
+    pub fn mixolydian(root: T) -> Self {
+        Self::new(root, ScaleIntervals::mixolydian())
+    }
+
+    pub fn aeolian(root: T) -> Self {
+        Self::natural_minor(root)
+    }
+
+    pub fn locrian(root: T) -> Self {
+        Self::new(root, ScaleIntervals::locrian())
+    }
+
+    /// Returns the number of notes in one octave of this scale.
+    pub fn len(&self) -> usize {
+        self.intervals.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.bits == 0
+    }
+}
+
+impl<T> ScaleSet<T>
+where
+    T: Degree + Clone,
+{
+    /// Returns an iterator over one ascending octave of this scale.
+    pub fn iter(&self) -> DiatonicScale<T> {
+        Scale::diatonic(self.root.clone(), self.intervals)
+    }
+
+    /// Returns the note at the zero-based scale `index`, so `0` is the root and `4` is the
+    /// fifth degree of a heptatonic scale.
+    ///
+    /// Indices past the octave or below the root wrap into the neighbouring octaves.
+    /// This returns `None` for an empty scale or if the note falls outside the range of `T`.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet};
+    ///
+    /// let scale = ScaleSet::major(midi!(C, 4));
+    ///
+    /// assert_eq!(scale.degree(9), Some(midi!(E, 5)));
+    /// assert_eq!(scale.degree(-1), Some(midi!(B, 3)));
+    /// ```
+    ///
+    /// Spelled notes keep the spelling of their degree.
+    /// ```
+    /// use staff::{scale::ScaleSet, Natural, Note};
+    ///
+    /// let scale = ScaleSet::major(Note::flat(Natural::E));
+    ///
+    /// assert_eq!(scale.degree(3), Some(Note::flat(Natural::A)));
+    /// assert_eq!(scale.degree(-2), Some(Note::C));
+    /// ```
+    pub fn degree(&self, index: i32) -> Option<T> {
+        let len = self.len() as i32;
+        if len == 0 {
+            return None;
+        }
+
+        let octaves = i8::try_from(index.div_euclid(len)).ok()?;
+        let note = self.iter().nth(index.rem_euclid(len) as usize)?;
+        note.add_octaves(octaves)
+    }
+}
+
+impl<T> ScaleSet<T>
+where
+    T: Clone + Into<Pitch>,
+{
+    /// Returns `true` if the pitch class of `note` is part of this scale.
+    pub fn contains<U: Into<Pitch>>(&self, note: U) -> bool {
+        self.intervals.contains(self.interval_of(note))
+    }
+
+    /// Returns the zero-based position of `note` within one octave of this scale,
+    /// or `None` if its pitch class is not part of this scale.
+    pub fn degree_of<U: Into<Pitch>>(&self, note: U) -> Option<usize> {
+        let interval = self.interval_of(note);
+        if self.intervals.contains(interval) {
+            let (below, _) = self.intervals.split(interval);
+            Some(below.bits.count_ones() as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over every note of this scale from `from` up to and including `to`.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet, Pitch};
+    ///
+    /// let scale = ScaleSet::major(Pitch::G);
+    /// let notes = scale.range(midi!(E, 4), midi!(A, 4));
+    ///
+    /// assert!(notes.eq([midi!(E, 4), midi!(FSharp, 4), midi!(G, 4), midi!(A, 4)]));
+    /// ```
+    pub fn range(&self, from: MidiNote, to: MidiNote) -> impl Iterator<Item = MidiNote> {
+        let scale = self.clone();
+        (from.into_byte()..=to.into_byte())
+            .map(MidiNote::from_byte)
+            .filter(move |midi| scale.contains(*midi))
+    }
+
+    /// Returns the scale tone closest to `midi`, preferring the lower note on a tie.
+    ///
+    /// This returns `midi` unchanged if this scale is empty.
+    pub fn quantize(&self, midi: MidiNote) -> MidiNote {
+        let byte = midi.into_byte();
+        (0..=127)
+            .flat_map(|distance| {
+                [
+                    byte.checked_sub(distance),
+                    byte.checked_add(distance).filter(|byte| *byte <= 127),
+                ]
+            })
+            .flatten()
+            .map(MidiNote::from_byte)
+            .find(|midi| self.contains(*midi))
+            .unwrap_or(midi)
+    }
+
+    /// Transpose `midi` by a number of scale `steps`, so `2` moves a note up a third
+    /// within this scale.
+    ///
+    /// Chromatic notes outside of this scale keep their distance from the scale tone below them.
+    /// This returns `None` for an empty scale or if the result falls outside the MIDI range.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet, Pitch};
+    ///
+    /// let scale = ScaleSet::major(Pitch::D);
+    ///
+    /// assert_eq!(scale.transpose(midi!(D, 4), 2), Some(midi!(FSharp, 4)));
+    /// assert_eq!(scale.transpose(midi!(B, 4), -3), Some(midi!(FSharp, 4)));
+    ///
+    /// // F natural is a half step above E, so it lands a half step above G.
+    /// assert_eq!(scale.transpose(midi!(F, 4), 2), Some(midi!(GSharp, 4)));
+    /// ```
+    pub fn transpose(&self, midi: MidiNote, steps: i32) -> Option<MidiNote> {
+        let from_root = self.interval_of(midi).semitones() as i32;
+        let (position, below) = self.below(from_root)?;
+        let chromatic = from_root - below;
+
+        let (octaves, interval) = self.interval_at(position + steps)?;
+        let byte = midi.into_byte() as i32 - from_root
+            + octaves * Interval::OCTAVE.semitones() as i32
+            + interval
+            + chromatic;
+
+        u8::try_from(byte)
+            .ok()
+            .filter(|byte| *byte <= 127)
+            .map(MidiNote::from_byte)
+    }
+
+    /// Transpose each note of `melody` by a number of scale `steps`.
+    /// ```
+    /// use staff::{midi, scale::ScaleSet, Pitch};
+    ///
+    /// // Harmonize a melody in parallel thirds
+    /// let scale = ScaleSet::major(Pitch::C);
+    /// let melody = [midi!(C, 4), midi!(D, 4), midi!(B, 4)];
+    /// let thirds = scale.transpose_melody(melody, 2);
+    ///
+    /// assert!(thirds.eq([Some(midi!(E, 4)), Some(midi!(F, 4)), Some(midi!(D, 5))]));
+    /// ```
+    pub fn transpose_melody<'a, I>(
+        &'a self,
+        melody: I,
+        steps: i32,
+    ) -> impl Iterator<Item = Option<MidiNote>> + 'a
+    where
+        I: IntoIterator<Item = MidiNote>,
+        I::IntoIter: 'a,
+    {
+        melody
+            .into_iter()
+            .map(move |midi| self.transpose(midi, steps))
+    }
+
+    fn interval_of<U: Into<Pitch>>(&self, note: U) -> Interval {
+        note.into() - self.root.clone().into()
+    }
+
+    /// Returns the position and semitones from the root of the scale tone at or below
+    /// `semitones` from the root, wrapping to the last degree of the octave below.
+    fn below(&self, semitones: i32) -> Option<(i32, i32)> {
+        let (below, _) = self.intervals.split(Interval::new(semitones as u8 + 1));
+        if below.bits != 0 {
+            let position = below.bits.count_ones() as i32 - 1;
+            let interval = (u16::BITS - 1 - below.bits.leading_zeros()) as i32;
+            Some((position, interval))
+        } else {
+            let (octaves, interval) = self.interval_at(-1)?;
+            Some((
+                self.len() as i32 - 1,
+                octaves * Interval::OCTAVE.semitones() as i32 + interval,
+            ))
+        }
+    }
+
+    /// Returns the octave offset and the semitones from the root of the scale `index`.
+    fn interval_at(&self, index: i32) -> Option<(i32, i32)> {
+        let len = self.len() as i32;
+        if len == 0 {
+            return None;
+        }
+
+        let mut intervals = self.intervals;
+        let interval = intervals.nth(index.rem_euclid(len) as usize)?;
+        Some((index.div_euclid(len), interval.semitones() as i32))
+    }
+}
+
+impl ScaleSet<Note> {
+    /// Transpose a spelled `note` by a number of scale `steps`, keeping the spelling
+    /// of the scale.
+    ///
+    /// A chromatic note keeps its alteration from the degree with the same letter,
+    /// or from the scale tone below if this scale has no degree on that letter.
+    /// This returns `None` for an empty scale or if the result would need more than
+    /// a double sharp or double flat.
+    /// ```
+    /// use staff::{scale::ScaleSet, Natural, Note};
+    ///
+    /// let scale = ScaleSet::major(Note::flat(Natural::E));
+    ///
+    /// assert_eq!(scale.transpose_note(Note::G, 2), Some(Note::flat(Natural::B)));
+    ///
+    /// // A natural is a raised fourth, so it becomes a raised sixth.
+    /// assert_eq!(scale.transpose_note(Note::A, 2), Some(Note::sharp(Natural::C)));
+    /// ```
+    pub fn transpose_note(&self, note: Note, steps: i32) -> Option<Note> {
+        let pitch = Pitch::from(note);
+        let (position, alteration) = match self
+            .iter()
+            .enumerate()
+            .find(|(_, degree)| degree.natural == note.natural)
+        {
+            Some((position, degree)) => {
+                let semitones = (pitch - Pitch::from(degree)).semitones() as i8;
+                let alteration = if semitones > 6 {
+                    semitones - 12
+                } else {
+                    semitones
+                };
+                (position as i32, alteration)
+            }
+            None => {
+                let from_root = self.interval_of(pitch).semitones() as i32;
+                let (position, below) = self.below(from_root)?;
+                (position, (from_root - below) as i8)
+            }
+        };
+
+        let degree = self.degree(position + steps)?;
+        let accidental = Accidental::from_semitones(degree.accidental.semitones() + alteration)?;
+        Some(Note::new(degree.natural, accidental))
+    }
+
+    /// Transpose each spelled note of `melody` by a number of scale `steps`.
+    pub fn transpose_notes<'a, I>(
+        &'a self,
+        melody: I,
+        steps: i32,
+    ) -> impl Iterator<Item = Option<Note>> + 'a
+    where
+        I: IntoIterator<Item = Note>,
+        I::IntoIter: 'a,
+    {
+        melody
+            .into_iter()
+            .map(move |note| self.transpose_note(note, steps))
+    }
+}
+
+impl fmt::Display for ScaleSet<Note> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.natural.fmt(f)?;
+        if !self.root.accidental.is_natural() {
+            self.root.accidental.fmt(f)?;
+        }
+        fmt_intervals(self.intervals, f)
+    }
+}
+
+impl fmt::Display for ScaleSet<Pitch> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt(f)?;
+        fmt_intervals(self.intervals, f)
+    }
+}
+
+fn fmt_intervals(intervals: ScaleIntervals, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_char(' ')?;
+    if let Some(name) = intervals.name() {
+        return f.write_str(name);
+    }
+
+    f.write_char('[')?;
+    for (idx, interval) in intervals.enumerate() {
+        if idx > 0 {
+            f.write_char(' ')?;
+        }
+        write!(f, "{}", interval.semitones())?;
+    }
+    f.write_char(']')
+}
+
+impl FromStr for ScaleSet<Note> {
+    type Err = Error;
+
+    /// Parse a scale from its tonic and name, or a list of semitones in brackets.
+    ///
+    /// A tonic without a name is a major scale, or a natural minor scale if it's lowercase.
+    /// ```
+    /// use staff::{scale::{ScaleIntervals, ScaleSet}, Natural, Note};
+    ///
+    /// let scale: ScaleSet<Note> = "Bb harmonic minor".parse().unwrap();
+    /// assert_eq!(scale, ScaleSet::harmonic_minor(Note::flat(Natural::B)));
+    /// assert_eq!(scale.to_string(), "B♭ harmonic minor");
+    ///
+    /// let scale: ScaleSet<Note> = "c#".parse().unwrap();
+    /// assert_eq!(scale, ScaleSet::natural_minor(Note::sharp(Natural::C)));
+    ///
+    /// let scale: ScaleSet<Note> = "D [0 3 7]".parse().unwrap();
+    /// assert_eq!(scale.to_string(), "D [0 3 7]");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (tonic, name) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        if tonic.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let root: Note = tonic.parse()?;
+        let name = name.trim();
+        let intervals = if name.is_empty() {
+            if tonic.starts_with(|c: char| c.is_lowercase()) {
+                ScaleIntervals::natural_minor()
+            } else {
+                ScaleIntervals::major()
+            }
+        } else if let Some(semitones) = name.strip_prefix('[') {
+            let semitones = semitones.strip_suffix(']').ok_or(Error::InvalidInterval)?;
+            let mut intervals = ScaleIntervals::default();
+            for semitones in semitones.split_whitespace() {
+                match semitones.parse() {
+                    Ok(semitones) if semitones < Interval::OCTAVE.semitones() => {
+                        intervals.push(Interval::new(semitones))
+                    }
+                    _ => return Err(Error::InvalidInterval),
+                }
+            }
+            intervals
+        } else {
+            ScaleIntervals::from_name(name).ok_or(Error::UnknownName)?
+        };
+
+        Ok(Self::new(root, intervals))
+    }
+}
+
+impl FromStr for ScaleSet<Pitch> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let scale: ScaleSet<Note> = s.parse()?;
+        Ok(Self::new(scale.root.into(), scale.intervals))
+    }
+}
+
+impl<T> From<ScaleSet<T>> for DiatonicScale<T>
+where
+    T: Degree + Clone,
+{
+    fn from(scale: ScaleSet<T>) -> Self {
+        scale.iter()
+    }
+}
+
+impl<T> IntoIterator for ScaleSet<T>
+where
+    T: Degree + Clone,
+{
+    type Item = T;
+
+    type IntoIter = DiatonicScale<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScaleSet;
+    use crate::{
+        midi::{MidiNote, Octave},
+        Natural, Note, Pitch,
+    };
+
+    #[test]
+    fn it_wraps_degrees_across_octaves() {
+        let scale = ScaleSet::natural_minor(MidiNote::new(Pitch::A, Octave::THREE));
+
+        assert_eq!(scale.degree(-7), Some(MidiNote::new(Pitch::A, Octave::TWO)));
+        assert_eq!(scale.degree(16), Some(MidiNote::new(Pitch::C, Octave::SIX)));
+        assert_eq!(ScaleSet::major(MidiNote::from_byte(120)).degree(7), None);
+    }
+
+    #[test]
+    fn it_finds_degrees_of_spelled_notes() {
+        let scale = ScaleSet::major(Note::sharp(Natural::F));
+
+        assert_eq!(scale.degree_of(Note::sharp(Natural::E)), Some(6));
+        assert_eq!(scale.degree_of(Note::F), Some(6));
+        assert_eq!(scale.degree_of(Note::G), None);
+    }
+
+    #[test]
+    fn it_quantizes_to_the_nearest_scale_tone() {
+        let scale = ScaleSet::new(Pitch::C, [crate::Interval::UNISON].into_iter().collect());
+
+        assert_eq!(
+            scale.quantize(MidiNote::new(Pitch::F, Octave::FOUR)),
+            MidiNote::new(Pitch::C, Octave::FOUR)
+        );
+        assert_eq!(
+            scale.quantize(MidiNote::new(Pitch::G, Octave::FOUR)),
+            MidiNote::new(Pitch::C, Octave::FIVE)
+        );
+
+        // The nearest G is more than a tritone above the bottom of the MIDI range
+        let scale = ScaleSet::new(Pitch::G, [crate::Interval::UNISON].into_iter().collect());
+        assert_eq!(
+            scale.quantize(MidiNote::from_byte(0)),
+            MidiNote::from_byte(7)
+        );
+    }
+
+    #[test]
+    fn it_transposes_across_the_root() {
+        let scale = ScaleSet::harmonic_minor(Pitch::A);
+
+        assert_eq!(
+            scale.transpose(MidiNote::new(Pitch::B, Octave::FOUR), -2),
+            Some(MidiNote::new(Pitch::GSharp, Octave::FOUR))
+        );
+        assert_eq!(
+            scale.transpose(MidiNote::new(Pitch::GSharp, Octave::FOUR), 1),
+            Some(MidiNote::new(Pitch::A, Octave::FOUR))
+        );
+        // A# is a half step above the root
+        assert_eq!(
+            scale.transpose(MidiNote::new(Pitch::ASharp, Octave::FOUR), 7),
+            Some(MidiNote::new(Pitch::ASharp, Octave::FIVE))
+        );
+    }
+}
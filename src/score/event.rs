@@ -0,0 +1,366 @@
+use crate::{
+    fmt::Suffix,
+    midi::{MidiNote, Octave},
+    note::Note,
+    time::{Duration, Fraction, TimeSignature},
+    Chord, Clef, Key,
+};
+use core::fmt;
+
+/// An event in a [`Voice`](super::Voice), in the order they're written.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// A note or chord.
+    Note(NoteEvent),
+    Rest(Rest),
+    Clef(Clef),
+    Key(Key),
+    Time(TimeSignature),
+    ChordSymbol(ChordSymbol),
+    Dynamic(Dynamic),
+}
+
+impl Event {
+    /// Returns the length of this event in whole notes,
+    /// which is zero for grace notes and every event that isn't a note or rest.
+    pub fn length(&self) -> Fraction {
+        match self {
+            Self::Note(note) if note.grace.is_none() => note.duration.length(),
+            Self::Rest(rest) => rest.duration.length(),
+            _ => Fraction::ZERO,
+        }
+    }
+}
+
+impl From<NoteEvent> for Event {
+    fn from(note: NoteEvent) -> Self {
+        Self::Note(note)
+    }
+}
+
+impl From<Rest> for Event {
+    fn from(rest: Rest) -> Self {
+        Self::Rest(rest)
+    }
+}
+
+/// A note with one notehead, or a chord with several, sharing a duration.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteEvent {
+    pub heads: Vec<Notehead>,
+    pub duration: Duration,
+    pub grace: Option<Grace>,
+    pub articulations: Vec<Articulation>,
+    pub lyrics: Vec<Lyric>,
+}
+
+impl NoteEvent {
+    /// Create a note with a single notehead.
+    pub fn note(head: Notehead, duration: Duration) -> Self {
+        Self::chord([head], duration)
+    }
+
+    /// Create a chord of `heads`.
+    pub fn chord(heads: impl IntoIterator<Item = Notehead>, duration: Duration) -> Self {
+        Self {
+            heads: heads.into_iter().collect(),
+            duration,
+            grace: None,
+            articulations: Vec::new(),
+            lyrics: Vec::new(),
+        }
+    }
+
+    /// Make this a grace note before the next note.
+    pub fn with_grace(mut self, grace: Grace) -> Self {
+        self.grace = Some(grace);
+        self
+    }
+
+    pub fn with_articulation(mut self, articulation: Articulation) -> Self {
+        self.articulations.push(articulation);
+        self
+    }
+
+    pub fn with_lyric(mut self, lyric: Lyric) -> Self {
+        self.lyrics.push(lyric);
+        self
+    }
+
+    /// Tie every notehead to the next note.
+    pub fn with_tie(mut self, tie: Tie) -> Self {
+        for head in &mut self.heads {
+            head.tie = Some(tie);
+        }
+        self
+    }
+
+    pub fn is_chord(&self) -> bool {
+        self.heads.len() > 1
+    }
+
+    /// Returns the MIDI notes of each notehead.
+    pub fn midi_notes(&self) -> impl Iterator<Item = MidiNote> + '_ {
+        self.heads.iter().map(|head| head.midi())
+    }
+}
+
+/// A spelled note in an octave, tied or not to the same note next to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Notehead {
+    pub note: Note,
+    /// Octave of the natural letter, so B♯3 sounds as C4.
+    pub octave: Octave,
+    pub tie: Option<Tie>,
+}
+
+impl Notehead {
+    pub fn new(note: Note, octave: Octave) -> Self {
+        Self {
+            note,
+            octave,
+            tie: None,
+        }
+    }
+
+    /// Spell `midi` in `key`.
+    /// ```
+    /// use staff::key::{Key, Mode};
+    /// use staff::midi::Octave;
+    /// use staff::score::Notehead;
+    /// use staff::{midi, Natural, Note};
+    ///
+    /// // E♭ minor has 6 flats
+    /// let head = Notehead::in_key(midi!(B, 4), Key::from_fifths(-6, Mode::Minor));
+    /// assert_eq!(head.note, Note::flat(Natural::C));
+    /// assert_eq!(head.octave, Octave::FIVE);
+    /// ```
+    pub fn in_key(midi: MidiNote, key: Key) -> Self {
+        let note = key.spell(midi.pitch());
+        let octave = midi.octave().into_i8();
+        let octave = [octave, octave + 1, octave - 1]
+            .into_iter()
+            .map(Octave::new_unchecked)
+            .find(|octave| MidiNote::from_note(note, *octave) == midi)
+            .unwrap_or(midi.octave());
+
+        Self::new(note, octave)
+    }
+
+    pub fn midi(self) -> MidiNote {
+        MidiNote::from_note(self.note, self.octave)
+    }
+}
+
+impl From<MidiNote> for Notehead {
+    /// Spell `midi` with sharps.
+    fn from(midi: MidiNote) -> Self {
+        // Fifths from C, from F up to A♯
+        let fifths = (midi.pitch().into_byte() as i8 * 7 + 1) % 12 - 1;
+        let note = Note::from_fifths(fifths).expect("pitch classes fit in 10 fifths");
+        Self::new(note, midi.octave())
+    }
+}
+
+/// Which side of a tie a note is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tie {
+    /// Tied to the next note.
+    Start,
+    /// Tied from the previous note.
+    Stop,
+    /// Tied from the previous note and to the next one.
+    Continue,
+}
+
+/// A grace note, written small and taking no time in the measure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grace {
+    /// `true` for a slashed acciaccatura instead of an appoggiatura.
+    pub slash: bool,
+}
+
+/// A rest, or a whole-measure rest that fills any measure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rest {
+    pub duration: Duration,
+    pub is_measure: bool,
+}
+
+impl Rest {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            is_measure: false,
+        }
+    }
+
+    /// Create a whole-measure rest in `meter`, lasting the full measure.
+    pub fn measure(meter: TimeSignature) -> Option<Self> {
+        let mut tied = Duration::decompose(meter.measure());
+        let duration = tied.next()?;
+        if tied.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            duration,
+            is_measure: true,
+        })
+    }
+}
+
+/// A chord symbol written above the staff, such as `Cm7/G`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChordSymbol {
+    pub root: Note,
+    /// Suffix after the root, such as `m7` or `maj7`.
+    pub kind: String,
+    pub bass: Option<Note>,
+}
+
+impl ChordSymbol {
+    /// Create the symbol of `chord`, spelling its root and bass in `key`.
+    /// ```
+    /// use staff::score::ChordSymbol;
+    /// use staff::{midi, Chord, Key, Pitch};
+    ///
+    /// let chord = Chord::minor_seventh(midi!(ASharp, 3));
+    /// let symbol = ChordSymbol::from_chord(&chord, Key::minor(Pitch::F));
+    /// assert_eq!(symbol.to_string(), "B♭m7");
+    ///
+    /// // E♭ major over G
+    /// let notes = [midi!(G, 3), midi!(DSharp, 4), midi!(ASharp, 4)];
+    /// let chord = Chord::from_midi(midi!(DSharp, 4), notes).unwrap();
+    /// let symbol = ChordSymbol::from_chord(&chord, Key::major(Pitch::DSharp));
+    /// assert_eq!(symbol.kind, "");
+    /// assert_eq!(symbol.to_string(), "E♭/G");
+    /// ```
+    pub fn from_chord(chord: &Chord, key: Key) -> Self {
+        Self {
+            root: key.spell(chord.root.pitch()),
+            kind: Suffix(chord).to_string(),
+            bass: chord.bass.map(|bass| key.spell(bass.pitch())),
+        }
+    }
+}
+
+impl fmt::Display for ChordSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_note(f, self.root)?;
+        f.write_str(&self.kind)?;
+        if let Some(bass) = self.bass {
+            f.write_str("/")?;
+            write_note(f, bass)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `note` without a natural sign.
+fn write_note(f: &mut fmt::Formatter<'_>, note: Note) -> fmt::Result {
+    write!(f, "{}", note.natural)?;
+    if !note.accidental.is_natural() {
+        write!(f, "{}", note.accidental)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dynamic {
+    PPP,
+    PP,
+    P,
+    MP,
+    MF,
+    F,
+    FF,
+    FFF,
+    FP,
+    SF,
+    SFZ,
+}
+
+impl Dynamic {
+    /// Returns the MIDI velocity usually played for this dynamic.
+    pub fn velocity(self) -> u8 {
+        match self {
+            Self::PPP => 16,
+            Self::PP => 33,
+            Self::P => 49,
+            Self::MP => 64,
+            Self::MF => 80,
+            Self::F | Self::FP => 96,
+            Self::FF | Self::SF => 112,
+            Self::FFF | Self::SFZ => 127,
+        }
+    }
+}
+
+impl fmt::Display for Dynamic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::PPP => "ppp",
+            Self::PP => "pp",
+            Self::P => "p",
+            Self::MP => "mp",
+            Self::MF => "mf",
+            Self::F => "f",
+            Self::FF => "ff",
+            Self::FFF => "fff",
+            Self::FP => "fp",
+            Self::SF => "sf",
+            Self::SFZ => "sfz",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Articulation {
+    Staccato,
+    Staccatissimo,
+    Tenuto,
+    Accent,
+    Marcato,
+    Fermata,
+}
+
+/// A syllable of lyrics sung on a note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lyric {
+    /// Verse number, starting at 1.
+    pub verse: u8,
+    pub text: String,
+    pub syllabic: Syllabic,
+}
+
+impl Lyric {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            verse: 1,
+            text: text.into(),
+            syllabic: Syllabic::Single,
+        }
+    }
+}
+
+/// Where a syllable falls in its word, for drawing hyphens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Syllabic {
+    Single,
+    Begin,
+    Middle,
+    End,
+}
@@ -0,0 +1,292 @@
+//! Score data model
+//!
+//! A [`Score`] holds [`Part`]s for each instrument, with a [`Staff`] for each of their staves.
+//! Staves are split into [`Measure`]s, each with one or more [`Voice`]s of [`Event`]s.
+//!
+//! ```
+//! use staff::score::{Event, Measure, NoteEvent, Notehead, Part, Rest, Score, Staff, Voice};
+//! use staff::time::{Duration, DurationKind, Fraction, TimeSignature};
+//! use staff::{midi, Clef};
+//!
+//! let meter = TimeSignature::new(DurationKind::Quarter, 4);
+//! let half = Duration::from(DurationKind::Half);
+//!
+//! let mut voice = Voice::new();
+//! voice.push(Event::Clef(Clef::Treble));
+//! voice.push(Event::Time(meter));
+//! voice.push(NoteEvent::chord([midi!(C, 4).into(), midi!(E, 4).into()], half));
+//! voice.push(Rest::new(half));
+//!
+//! let mut staff = Staff::new();
+//! staff.measures.push(Measure::new(1, voice));
+//!
+//! let mut part = Part::new("P1", "Piano");
+//! part.staves.push(staff);
+//!
+//! let mut score = Score::new();
+//! score.parts.push(part);
+//!
+//! let measure = &score.parts[0].staves[0].measures[0];
+//! assert_eq!(measure.length(), Fraction::ONE);
+//! assert!(measure.check(meter).is_ok());
+//! ```
+
+mod event;
+pub use event::{
+    Articulation, ChordSymbol, Dynamic, Event, Grace, Lyric, NoteEvent, Notehead, Rest, Syllabic,
+    Tie,
+};
+
+use crate::{
+    time::{Fraction, MeasureError, TimeSignature},
+    Clef, Key,
+};
+
+/// A piece of music with a part for each instrument.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Score {
+    pub title: Option<String>,
+    pub composer: Option<String>,
+    pub parts: Vec<Part>,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the part with `id`.
+    pub fn part(&self, id: &str) -> Option<&Part> {
+        self.parts.iter().find(|part| part.id == id)
+    }
+
+    /// Returns the number of measures in the longest staff.
+    pub fn measures(&self) -> usize {
+        self.parts
+            .iter()
+            .flat_map(|part| &part.staves)
+            .map(|staff| staff.measures.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The staves of one instrument.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Part {
+    pub id: String,
+    pub name: String,
+    pub staves: Vec<Staff>,
+}
+
+impl Part {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            staves: Vec::new(),
+        }
+    }
+}
+
+/// A staff of measures.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Staff {
+    pub measures: Vec<Measure>,
+}
+
+impl Staff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the clef, key and time signature in effect at the start of measure `index`,
+    /// from the last changes in any voice of the measures before it.
+    pub fn attributes(&self, index: usize) -> Attributes {
+        let mut attributes = Attributes::default();
+        for measure in self.measures.iter().take(index) {
+            for event in measure.voices.iter().flat_map(|voice| &voice.events) {
+                attributes.apply(event);
+            }
+        }
+        attributes
+    }
+}
+
+/// The clef, key and time signature of a staff at a point in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attributes {
+    pub clef: Option<Clef>,
+    pub key: Option<Key>,
+    pub time: Option<TimeSignature>,
+}
+
+impl Attributes {
+    /// Update these attributes with a clef, key or time change `event`.
+    pub fn apply(&mut self, event: &Event) {
+        match event {
+            Event::Clef(clef) => self.clef = Some(*clef),
+            Event::Key(key) => self.key = Some(*key),
+            Event::Time(time) => self.time = Some(*time),
+            _ => {}
+        }
+    }
+}
+
+/// A measure with one or more voices played at the same time.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Measure {
+    /// Measure number, usually starting from 1 or 0 for a pickup measure.
+    pub number: u32,
+    pub voices: Vec<Voice>,
+}
+
+impl Measure {
+    /// Create a measure with a single voice.
+    pub fn new(number: u32, voice: Voice) -> Self {
+        Self {
+            number,
+            voices: vec![voice],
+        }
+    }
+
+    /// Returns the length of the longest voice in whole notes.
+    pub fn length(&self) -> Fraction {
+        self.voices
+            .iter()
+            .map(Voice::length)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Check that every voice fills `meter`, where a whole-measure rest always fills it.
+    pub fn check(&self, meter: TimeSignature) -> Result<(), MeasureError> {
+        for voice in &self.voices {
+            let is_measure_rest = voice
+                .events
+                .iter()
+                .any(|event| matches!(event, Event::Rest(rest) if rest.is_measure));
+
+            if !is_measure_rest {
+                meter.check_measure(voice.durations())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sequence of events played one after another.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Voice {
+    pub events: Vec<Event>,
+}
+
+impl Voice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: impl Into<Event>) {
+        self.events.push(event.into());
+    }
+
+    /// Returns the total length of this voice in whole notes.
+    pub fn length(&self) -> Fraction {
+        self.events.iter().map(Event::length).sum()
+    }
+
+    /// Returns the durations of every note and rest that takes time.
+    pub fn durations(&self) -> impl Iterator<Item = crate::time::Duration> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            Event::Note(note) if note.grace.is_none() => Some(note.duration),
+            Event::Rest(rest) => Some(rest.duration),
+            _ => None,
+        })
+    }
+
+    /// Returns each event with its start in whole notes from the start of the voice.
+    pub fn timed(&self) -> impl Iterator<Item = (Fraction, &Event)> + '_ {
+        self.events.iter().scan(Fraction::ZERO, |start, event| {
+            let at = *start;
+            *start += event.length();
+            Some((at, event))
+        })
+    }
+}
+
+impl FromIterator<Event> for Voice {
+    fn from_iter<T: IntoIterator<Item = Event>>(iter: T) -> Self {
+        Self {
+            events: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::Octave,
+        note::Note,
+        time::{Duration, DurationKind},
+        Natural,
+    };
+
+    #[test]
+    fn it_skips_grace_notes_in_measure_length() {
+        let quarter = Duration::from(DurationKind::Quarter);
+        let head = Notehead::new(Note::sharp(Natural::F), Octave::FOUR);
+
+        let voice: Voice = [
+            NoteEvent::note(head, Duration::from(DurationKind::Eigth))
+                .with_grace(Grace { slash: true })
+                .into(),
+            NoteEvent::note(head, quarter).into(),
+            Event::Dynamic(Dynamic::MF),
+            Rest::new(Duration::new(DurationKind::Half, 1)).into(),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(voice.length(), Fraction::ONE);
+        let starts: Vec<_> = voice.timed().map(|(start, _)| start).collect();
+        assert_eq!(
+            starts,
+            [
+                Fraction::ZERO,
+                Fraction::ZERO,
+                Fraction::new(1, 4),
+                Fraction::new(1, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tracks_attributes_across_measures() {
+        let meter = TimeSignature::new(DurationKind::Eigth, 6);
+        let rest = Rest::measure(meter).unwrap();
+        assert_eq!(rest.duration, Duration::new(DurationKind::Half, 1));
+
+        let mut staff = Staff::new();
+        staff.measures.push(Measure::new(
+            1,
+            [Event::Clef(Clef::Bass), Event::Time(meter), rest.into()]
+                .into_iter()
+                .collect(),
+        ));
+        staff
+            .measures
+            .push(Measure::new(2, [rest.into()].into_iter().collect()));
+
+        let attributes = staff.attributes(1);
+        assert_eq!(attributes.clef, Some(Clef::Bass));
+        assert_eq!(attributes.time, Some(meter));
+        assert!(staff.measures[1].check(meter).is_ok());
+    }
+}
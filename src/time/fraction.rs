@@ -0,0 +1,279 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
+};
+
+/// An exact non-negative fraction, such as a length in whole notes.
+///
+/// Fractions are always stored in lowest terms, so equal values compare equal.
+/// ```
+/// use staff::time::Fraction;
+///
+/// let triplet = Fraction::new(1, 12);
+/// assert_eq!(triplet * 3, Fraction::new(1, 4));
+/// assert_eq!(triplet + Fraction::new(1, 6), Fraction::new(1, 4));
+/// assert!(triplet < Fraction::new(1, 8));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fraction {
+    numer: u64,
+    denom: u64,
+}
+
+impl Fraction {
+    pub const ZERO: Self = Self { numer: 0, denom: 1 };
+
+    pub const ONE: Self = Self { numer: 1, denom: 1 };
+
+    /// Create a new fraction of `numer / denom` in lowest terms.
+    ///
+    /// # Panics
+    /// Panics if `denom` is zero.
+    pub const fn new(numer: u64, denom: u64) -> Self {
+        assert!(denom != 0, "fraction with a zero denominator");
+
+        let divisor = gcd(numer, denom);
+        Self {
+            numer: numer / divisor,
+            denom: denom / divisor,
+        }
+    }
+
+    /// Create a fraction from a whole number.
+    pub const fn from_int(n: u64) -> Self {
+        Self { numer: n, denom: 1 }
+    }
+
+    pub const fn numer(self) -> u64 {
+        self.numer
+    }
+
+    pub const fn denom(self) -> u64 {
+        self.denom
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.numer == 0
+    }
+
+    /// Returns `true` if the denominator is a power of two,
+    /// so this length can be written without tuplets.
+    pub const fn is_dyadic(self) -> bool {
+        self.denom.is_power_of_two()
+    }
+
+    /// Returns `self - rhs`, or `None` if `rhs` is larger.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let (a, b, denom) = self.common(rhs);
+        a.checked_sub(b).map(|numer| Self::reduce(numer, denom))
+    }
+
+    /// Returns `self - rhs`, or zero if `rhs` is larger.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or(Self::ZERO)
+    }
+
+    /// Returns how many whole times `rhs` fits in this fraction and the remaining length,
+    /// such as the number of full measures and the length of the last one.
+    /// ```
+    /// use staff::time::Fraction;
+    ///
+    /// let length = Fraction::new(11, 4);
+    /// assert_eq!(length.div_rem(Fraction::new(3, 4)), (3, Fraction::new(1, 2)));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `rhs` is zero or the quotient doesn't fit in a `u64`.
+    pub fn div_rem(self, rhs: Self) -> (u64, Self) {
+        assert!(!rhs.is_zero(), "division by a zero fraction");
+
+        let (a, b, denom) = self.common(rhs);
+        let quotient = u64::try_from(a / b).expect("fraction quotient overflow");
+        (quotient, Self::reduce(a % b, denom))
+    }
+
+    /// Returns the largest whole number less than or equal to this fraction.
+    pub const fn floor(self) -> u64 {
+        self.numer / self.denom
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+
+    /// Returns both numerators over a common denominator.
+    fn common(self, rhs: Self) -> (u128, u128, u128) {
+        let denom = lcm(self.denom, rhs.denom) as u128;
+        (
+            self.numer as u128 * (denom / self.denom as u128),
+            rhs.numer as u128 * (denom / rhs.denom as u128),
+            denom,
+        )
+    }
+
+    /// Returns the fraction of `numer` over `denom` in lowest terms.
+    ///
+    /// # Panics
+    /// Panics if the reduced numerator or denominator doesn't fit in a `u64`,
+    /// like the arithmetic operators of integers.
+    fn reduce(numer: u128, denom: u128) -> Self {
+        let divisor = gcd_u128(numer, denom);
+        Self {
+            numer: u64::try_from(numer / divisor).expect("fraction numerator overflow"),
+            denom: u64::try_from(denom / divisor).expect("fraction denominator overflow"),
+        }
+    }
+}
+
+impl Default for Fraction {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<u64> for Fraction {
+    fn from(n: u64) -> Self {
+        Self::from_int(n)
+    }
+}
+
+impl PartialOrd for Fraction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Fraction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numer as u128 * other.denom as u128).cmp(&(other.numer as u128 * self.denom as u128))
+    }
+}
+
+impl Add for Fraction {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (a, b, denom) = self.common(rhs);
+        Self::reduce(a + b, denom)
+    }
+}
+
+impl AddAssign for Fraction {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Fraction {
+    type Output = Self;
+
+    /// # Panics
+    /// Panics if `rhs` is larger than `self`.
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("fraction subtraction underflow")
+    }
+}
+
+impl SubAssign for Fraction {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for Fraction {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::reduce(
+            self.numer as u128 * rhs.numer as u128,
+            self.denom as u128 * rhs.denom as u128,
+        )
+    }
+}
+
+impl Mul<u64> for Fraction {
+    type Output = Self;
+
+    fn mul(self, rhs: u64) -> Self::Output {
+        self * Self::from_int(rhs)
+    }
+}
+
+impl Div for Fraction {
+    type Output = Self;
+
+    /// # Panics
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: Self) -> Self::Output {
+        assert!(!rhs.is_zero(), "division by a zero fraction");
+
+        Self::reduce(
+            self.numer as u128 * rhs.denom as u128,
+            self.denom as u128 * rhs.numer as u128,
+        )
+    }
+}
+
+impl Div<u64> for Fraction {
+    type Output = Self;
+
+    fn div(self, rhs: u64) -> Self::Output {
+        self / Self::from_int(rhs)
+    }
+}
+
+impl Sum for Fraction {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl fmt::Debug for Fraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for Fraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    if a == 0 {
+        1
+    } else {
+        a
+    }
+}
+
+const fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    if a == 0 {
+        1
+    } else {
+        a
+    }
+}
+
+const fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
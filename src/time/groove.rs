@@ -0,0 +1,305 @@
+//! Groove templates and humanisation of note timing and velocity
+//!
+//! ```
+//! use staff::time::groove::{Groove, Humanize};
+//! use staff::time::quantize::PerformedNote;
+//! use staff::time::tempo::Ticks;
+//! use staff::midi;
+//!
+//! // Straight eighths at 480 ticks per quarter note
+//! let mut notes: Vec<_> = (0..4)
+//!     .map(|i| PerformedNote::new(midi!(C, 4), Ticks(i * 240), Ticks(240), 100))
+//!     .collect();
+//!
+//! Groove::swing(2., 2).apply(&mut notes, 480, 1.);
+//! assert_eq!(notes[1].onset, Ticks(320));
+//!
+//! Humanize::new(10, 8).with_seed(7).apply(&mut notes);
+//! assert!(notes.iter().all(|note| note.velocity >= 92 && note.velocity <= 108));
+//! ```
+
+use super::{quantize::PerformedNote, tempo::Ticks};
+
+/// Timing and velocity offsets of one step of a [`Groove`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrooveStep {
+    /// Offset of notes on this step as a fraction of a step, where 0.5 is half a step late.
+    pub timing: f64,
+    /// Velocity multiplier for notes on this step.
+    pub velocity: f64,
+}
+
+impl Default for GrooveStep {
+    fn default() -> Self {
+        Self {
+            timing: 0.,
+            velocity: 1.,
+        }
+    }
+}
+
+/// A repeating template of timing and velocity offsets on a grid of steps.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Groove {
+    /// Number of steps per quarter note, such as 4 for sixteenths.
+    pub subdivision: u16,
+    /// Steps that repeat through the piece, starting from the first tick.
+    pub steps: Vec<GrooveStep>,
+}
+
+impl Groove {
+    /// Create a groove of `steps` with `subdivision` steps per quarter note.
+    ///
+    /// # Panics
+    /// Panics if `subdivision` is zero or there are no steps.
+    pub fn new(subdivision: u16, steps: Vec<GrooveStep>) -> Self {
+        assert!(subdivision > 0, "groove subdivision must be positive");
+        assert!(!steps.is_empty(), "groove must have at least one step");
+
+        Self { subdivision, steps }
+    }
+
+    /// Create a swing groove that delays every other step of `subdivision`,
+    /// so the first of each pair is `ratio` times longer than the second.
+    ///
+    /// A `ratio` of 2 is triplet swing and 1 is straight.
+    pub fn swing(ratio: f64, subdivision: u16) -> Self {
+        // The off-beat lands at ratio / (1 + ratio) of the pair
+        let timing = 2. * ratio / (1. + ratio) - 1.;
+        Self::new(
+            subdivision,
+            vec![
+                GrooveStep::default(),
+                GrooveStep {
+                    timing,
+                    velocity: 1.,
+                },
+            ],
+        )
+    }
+
+    /// Extract a groove of `len` steps from performed `notes`,
+    /// averaging the timing and velocity of the notes nearest each step.
+    /// ```
+    /// use staff::time::groove::Groove;
+    /// use staff::time::quantize::PerformedNote;
+    /// use staff::time::tempo::Ticks;
+    /// use staff::midi;
+    ///
+    /// let notes = [
+    ///     PerformedNote::new(midi!(C, 4), Ticks(0), Ticks(200), 120),
+    ///     PerformedNote::new(midi!(C, 4), Ticks(300), Ticks(200), 60),
+    ///     PerformedNote::new(midi!(C, 4), Ticks(480), Ticks(200), 120),
+    ///     PerformedNote::new(midi!(C, 4), Ticks(780), Ticks(200), 60),
+    /// ];
+    /// let groove = Groove::extract(&notes, 480, 2, 2);
+    ///
+    /// assert_eq!(groove.steps[1].timing, 0.25);
+    /// assert!(groove.steps[0].velocity > groove.steps[1].velocity);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `ppq`, `subdivision` or `len` is zero.
+    pub fn extract(notes: &[PerformedNote], ppq: u16, subdivision: u16, len: usize) -> Self {
+        assert!(
+            ppq > 0 && subdivision > 0 && len > 0,
+            "groove resolution must be positive"
+        );
+
+        let step_ticks = ppq as f64 / subdivision as f64;
+        let mut sums = vec![(0., 0., 0); len];
+        for note in notes {
+            let index = (note.onset.0 as f64 / step_ticks).round();
+            let slot = &mut sums[index as usize % len];
+            slot.0 += note.onset.0 as f64 / step_ticks - index;
+            slot.1 += note.velocity as f64;
+            slot.2 += 1;
+        }
+
+        let played: Vec<_> = sums.iter().filter(|(.., count)| *count > 0).collect();
+        let mean_velocity = played
+            .iter()
+            .map(|(_, velocity, count)| velocity / *count as f64)
+            .sum::<f64>()
+            / played.len().max(1) as f64;
+
+        let steps = sums
+            .into_iter()
+            .map(|(timing, velocity, count)| {
+                if count == 0 || mean_velocity == 0. {
+                    GrooveStep::default()
+                } else {
+                    GrooveStep {
+                        timing: timing / count as f64,
+                        velocity: velocity / count as f64 / mean_velocity,
+                    }
+                }
+            })
+            .collect();
+
+        Self::new(subdivision, steps)
+    }
+
+    /// Apply this groove to `notes` at `ppq` ticks per quarter note,
+    /// moving each note by its nearest step scaled by `strength` from 0 to 1.
+    ///
+    /// Notes are left unchanged if this groove has no steps or a subdivision of zero.
+    pub fn apply(&self, notes: &mut [PerformedNote], ppq: u16, strength: f64) {
+        if self.steps.is_empty() || self.subdivision == 0 {
+            return;
+        }
+
+        let step_ticks = ppq as f64 / self.subdivision as f64;
+        let strength = strength.clamp(0., 1.);
+
+        for note in notes {
+            let index = (note.onset.0 as f64 / step_ticks).round();
+            let step = self.steps[index as usize % self.steps.len()];
+
+            let onset = note.onset.0 as f64 + step.timing * step_ticks * strength;
+            note.onset = Ticks(onset.round().max(0.) as u64);
+
+            let scale = 1. + (step.velocity - 1.) * strength;
+            note.velocity = (note.velocity as f64 * scale).round().clamp(1., 127.) as u8;
+        }
+    }
+}
+
+/// Seeded random offsets of note timing and velocity.
+///
+/// Offsets follow a triangular distribution,
+/// so small offsets are more likely than the largest ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Humanize {
+    /// Largest timing offset in ticks.
+    pub timing: u64,
+    /// Largest velocity offset.
+    pub velocity: u8,
+    pub seed: u64,
+}
+
+impl Humanize {
+    pub fn new(timing: u64, velocity: u8) -> Self {
+        Self {
+            timing,
+            velocity,
+            seed: 0,
+        }
+    }
+
+    /// Use `seed` for the random offsets, so the same seed always gives the same result.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Randomly offset the onset and velocity of `notes`.
+    pub fn apply(&self, notes: &mut [PerformedNote]) {
+        let mut rng = SplitMix64(self.seed);
+        for note in notes {
+            let onset = note.onset.0 as f64 + rng.triangular() * self.timing as f64;
+            note.onset = Ticks(onset.round().max(0.) as u64);
+
+            let velocity = note.velocity as f64 + rng.triangular() * self.velocity as f64;
+            note.velocity = velocity.round().clamp(1., 127.) as u8;
+        }
+    }
+}
+
+/// Small seeded random number generator, from Steele, Lea and Flood's SplitMix64.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number from 0 to 1.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random number from -1 to 1, most likely near 0.
+    fn triangular(&mut self) -> f64 {
+        self.next_f64() - self.next_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::{MidiNote, Octave},
+        Pitch,
+    };
+
+    fn notes() -> Vec<PerformedNote> {
+        (0..16)
+            .map(|i| {
+                PerformedNote::new(
+                    MidiNote::new(Pitch::C, Octave::FOUR),
+                    Ticks(i * 120),
+                    Ticks(120),
+                    80,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_extracts_the_groove_it_applied() {
+        let groove = Groove::new(
+            4,
+            vec![
+                GrooveStep {
+                    timing: 0.,
+                    velocity: 1.25,
+                },
+                GrooveStep {
+                    timing: 0.25,
+                    velocity: 0.75,
+                },
+            ],
+        );
+
+        let mut notes = notes();
+        groove.apply(&mut notes, 480, 1.);
+        let extracted = Groove::extract(&notes, 480, 4, 2);
+
+        assert_eq!(extracted, groove);
+    }
+
+    #[test]
+    fn it_leaves_notes_without_steps_unchanged() {
+        let groove = Groove {
+            subdivision: 4,
+            steps: Vec::new(),
+        };
+
+        let mut notes = notes();
+        groove.apply(&mut notes, 480, 1.);
+        assert_eq!(notes, self::notes());
+    }
+
+    #[test]
+    fn it_humanizes_with_a_seed() {
+        let mut a = notes();
+        let mut b = notes();
+        Humanize::new(20, 10).with_seed(1).apply(&mut a);
+        Humanize::new(20, 10).with_seed(1).apply(&mut b);
+        assert_eq!(a, b);
+
+        Humanize::new(20, 10).with_seed(2).apply(&mut b);
+        assert_ne!(a, b);
+        for (note, original) in a.iter().zip(notes()) {
+            assert!(note.onset.0.abs_diff(original.onset.0) <= 20);
+        }
+    }
+}
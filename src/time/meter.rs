@@ -0,0 +1,481 @@
+use super::{Decompose, Duration, DurationKind, Fraction};
+use core::{fmt, str::FromStr};
+
+/// The most groups an additive time signature can have.
+const MAX_GROUPS: usize = 8;
+
+/// The kind of meter of a [`TimeSignature`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MeterKind {
+    /// Two, three or four beats of one unit, such as 3/4.
+    Simple,
+    /// Beats of three units, such as 6/8.
+    Compound,
+    /// Written groups of units, such as 3+3+2/8.
+    Additive,
+    /// Any other number of units, such as 5/8 or 7/4.
+    Irregular,
+}
+
+/// A time signature of `beats` of `unit` per measure.
+/// ```
+/// use staff::time::{DurationKind, Fraction, MeterKind, TimeSignature};
+///
+/// let meter: TimeSignature = "3+3+2/8".parse().unwrap();
+/// assert_eq!(meter.kind(), MeterKind::Additive);
+/// assert_eq!(meter.measure(), Fraction::ONE);
+/// assert_eq!(meter.groups(), [3, 3, 2]);
+///
+/// let meter = TimeSignature::new(DurationKind::Eigth, 6);
+/// assert_eq!(meter.kind(), MeterKind::Compound);
+/// assert_eq!(meter.groups(), [3, 3]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeSignature {
+    pub unit: DurationKind,
+    pub beats: u8,
+    groups: [u8; MAX_GROUPS],
+    len: u8,
+}
+
+impl TimeSignature {
+    /// # Panics
+    /// Panics if `beats` is zero.
+    pub fn new(unit: DurationKind, beats: u8) -> Self {
+        assert!(beats > 0, "time signature must have beats");
+
+        Self {
+            unit,
+            beats,
+            groups: [0; MAX_GROUPS],
+            len: 0,
+        }
+    }
+
+    /// Create an additive time signature from groups of `unit`, such as `[3, 3, 2]` eighths.
+    ///
+    /// Returns `None` if there are no groups, more than 8 groups,
+    /// an empty group or more than 255 units in total.
+    pub fn additive(groups: &[u8], unit: DurationKind) -> Option<Self> {
+        if groups.is_empty() || groups.len() > MAX_GROUPS || groups.contains(&0) {
+            return None;
+        }
+
+        let beats = groups
+            .iter()
+            .try_fold(0u8, |sum, group| sum.checked_add(*group))?;
+
+        let mut array = [0; MAX_GROUPS];
+        array[..groups.len()].copy_from_slice(groups);
+        Some(Self {
+            unit,
+            beats,
+            groups: array,
+            len: groups.len() as u8,
+        })
+    }
+
+    pub fn kind(&self) -> MeterKind {
+        if self.len > 0 {
+            MeterKind::Additive
+        } else if self.beats > 3 && self.beats.is_multiple_of(3) {
+            MeterKind::Compound
+        } else if self.beats <= 4 {
+            MeterKind::Simple
+        } else {
+            MeterKind::Irregular
+        }
+    }
+
+    /// Returns the length of a full measure in whole notes.
+    pub fn measure(&self) -> Fraction {
+        self.unit.length() * self.beats as u64
+    }
+
+    /// Returns the number of units in each beat of this meter.
+    ///
+    /// Simple meters beat every unit and compound meters every three units.
+    /// Irregular meters are grouped in twos with a final three, so 7/8 beats as 2+2+3.
+    pub fn groups(&self) -> &[u8] {
+        const ONES: [u8; MAX_GROUPS] = [1; MAX_GROUPS];
+        const THREES: [u8; MAX_GROUPS] = [3; MAX_GROUPS];
+        const TWOS: [u8; MAX_GROUPS] = [2, 2, 2, 2, 2, 2, 2, 3];
+
+        match self.kind() {
+            MeterKind::Additive => &self.groups[..self.len as usize],
+            MeterKind::Simple => &ONES[..self.beats as usize],
+            MeterKind::Compound if self.beats as usize / 3 <= MAX_GROUPS => {
+                &THREES[..self.beats as usize / 3]
+            }
+            MeterKind::Irregular if self.beats % 2 == 1 && self.beats <= 15 => {
+                &TWOS[MAX_GROUPS - self.beats as usize / 2..]
+            }
+            // Long meters beat on every unit
+            _ => &ONES[..(self.beats as usize).min(MAX_GROUPS)],
+        }
+    }
+
+    /// Returns the start of each beat in whole notes from the start of the measure.
+    /// ```
+    /// use staff::time::{Fraction, TimeSignature};
+    ///
+    /// let meter: TimeSignature = "7/8".parse().unwrap();
+    /// let starts: Vec<_> = meter.beats().collect();
+    ///
+    /// assert_eq!(starts, [Fraction::ZERO, Fraction::new(1, 4), Fraction::new(1, 2)]);
+    /// ```
+    pub fn beats(&self) -> impl Iterator<Item = Fraction> + '_ {
+        let unit = self.unit.length();
+        let groups = self.groups();
+        let even = groups.iter().sum::<u8>() == self.beats;
+
+        // Meters too long for their groups beat on every unit instead
+        let count = if even {
+            groups.len()
+        } else {
+            self.beats as usize
+        };
+        (0..count).scan(Fraction::ZERO, move |start, i| {
+            let beat = *start;
+            *start += unit * if even { groups[i] as u64 } else { 1 };
+            Some(beat)
+        })
+    }
+
+    /// Returns the start of each beam group in whole notes from the start of the measure.
+    ///
+    /// Notes shorter than a beat are beamed together within each group.
+    /// Beats of 4/4 and 4/8 are beamed in halves, 3/8 is beamed as one group,
+    /// and every other meter beams by beat.
+    /// ```
+    /// use staff::time::{DurationKind, Fraction, TimeSignature};
+    ///
+    /// let meter = TimeSignature::new(DurationKind::Quarter, 4);
+    /// let starts: Vec<_> = meter.beams().collect();
+    ///
+    /// assert_eq!(starts, [Fraction::ZERO, Fraction::new(1, 2)]);
+    /// ```
+    pub fn beams(&self) -> impl Iterator<Item = Fraction> + '_ {
+        let quadruple = self.kind() == MeterKind::Simple
+            && self.beats == 4
+            && self.unit >= DurationKind::Quarter;
+        let single =
+            self.kind() == MeterKind::Simple && self.beats == 3 && self.unit >= DurationKind::Eigth;
+
+        self.beats().enumerate().filter_map(move |(i, start)| {
+            if (quadruple && i % 2 == 1) || (single && i > 0) {
+                None
+            } else {
+                Some(start)
+            }
+        })
+    }
+
+    /// Check that `durations` exactly fill one measure.
+    /// ```
+    /// use staff::time::{Duration, DurationKind, MeasureError, TimeSignature};
+    ///
+    /// let meter = TimeSignature::new(DurationKind::Quarter, 3);
+    /// let half = Duration::from(DurationKind::Half);
+    /// let quarter = Duration::from(DurationKind::Quarter);
+    ///
+    /// assert!(meter.check_measure([half, quarter]).is_ok());
+    /// assert!(matches!(meter.check_measure([half]), Err(MeasureError::Incomplete { .. })));
+    /// assert!(matches!(meter.check_measure([half, half]), Err(MeasureError::Overfull { .. })));
+    /// ```
+    pub fn check_measure<I>(&self, durations: I) -> Result<(), MeasureError>
+    where
+        I: IntoIterator<Item = Duration>,
+    {
+        let length: Fraction = durations.into_iter().sum();
+        let measure = self.measure();
+
+        if length < measure {
+            Err(MeasureError::Incomplete {
+                missing: measure - length,
+            })
+        } else if length > measure {
+            Err(MeasureError::Overfull {
+                excess: length - measure,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Split a note from `start` for `length`, both in whole notes from the first barline,
+    /// into tied durations that don't cross barlines
+    /// or start off the beat and cross the next beat.
+    ///
+    /// Parts shorter than a 128th note can't be written, so they're skipped
+    /// and their length is kept in [`Split::remainder`].
+    /// ```
+    /// use staff::time::{Duration, DurationKind, Fraction, TimeSignature};
+    ///
+    /// let meter = TimeSignature::new(DurationKind::Quarter, 4);
+    ///
+    /// // A half note starting on the last eighth of a measure
+    /// let split: Vec<_> = meter
+    ///     .split(Fraction::new(7, 8), Fraction::new(1, 2))
+    ///     .map(|note| (note.start, note.duration, note.is_tied))
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     split,
+    ///     [
+    ///         (Fraction::new(7, 8), Duration::from(DurationKind::Eigth), true),
+    ///         (Fraction::ONE, Duration::new(DurationKind::Quarter, 1), false),
+    ///     ]
+    /// );
+    /// ```
+    pub fn split(&self, start: Fraction, length: Fraction) -> Split<'_> {
+        Split {
+            meter: self,
+            position: start,
+            end: start + length,
+            piece: None,
+            remainder: Fraction::ZERO,
+        }
+    }
+
+    /// Returns the next boundary after `position` that a note starting there can't cross,
+    /// or `None` if this meter has no beats to cross.
+    fn boundary(&self, position: Fraction) -> Option<Fraction> {
+        let measure = self.measure();
+        if measure.is_zero() {
+            return None;
+        }
+
+        let (count, offset) = position.div_rem(measure);
+        let barline = measure * count;
+
+        if self.beats().any(|beat| beat == offset) {
+            return Some(barline + measure);
+        }
+
+        self.beats()
+            .find(|beat| *beat > offset)
+            .map(|beat| barline + beat)
+            .or(Some(barline + measure))
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.len > 0 {
+            for (i, group) in self.groups().iter().enumerate() {
+                if i > 0 {
+                    f.write_str("+")?;
+                }
+                write!(f, "{}", group)?;
+            }
+        } else {
+            write!(f, "{}", self.beats)?;
+        }
+
+        write!(f, "/{}", self.unit.length().denom())
+    }
+}
+
+impl FromStr for TimeSignature {
+    type Err = MeterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (beats, unit) = s.trim().split_once('/').ok_or(MeterError::MissingUnit)?;
+
+        let unit = unit
+            .trim()
+            .parse()
+            .ok()
+            .filter(|denom: &u64| denom.is_power_of_two())
+            .and_then(|denom| DurationKind::from_length(Fraction::new(1, denom)))
+            .ok_or(MeterError::InvalidUnit)?;
+
+        if beats.contains('+') {
+            let mut groups = [0; MAX_GROUPS];
+            let mut len = 0;
+            for group in beats.split('+') {
+                if len == MAX_GROUPS {
+                    return Err(MeterError::InvalidBeats);
+                }
+                groups[len] = group.trim().parse().map_err(|_| MeterError::InvalidBeats)?;
+                len += 1;
+            }
+
+            Self::additive(&groups[..len], unit).ok_or(MeterError::InvalidBeats)
+        } else {
+            match beats.trim().parse() {
+                Ok(beats) if beats > 0 => Ok(Self::new(unit, beats)),
+                _ => Err(MeterError::InvalidBeats),
+            }
+        }
+    }
+}
+
+/// Error parsing a time signature.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MeterError {
+    MissingUnit,
+    InvalidUnit,
+    InvalidBeats,
+}
+
+impl fmt::Display for MeterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingUnit => f.write_str("Missing unit, expected a time signature like 3/4"),
+            Self::InvalidUnit => f.write_str("Invalid unit, expected a power of two up to 128"),
+            Self::InvalidBeats => f.write_str("Invalid beats, expected a count like 3 or 3+2"),
+        }
+    }
+}
+
+/// Error checking the length of a measure.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MeasureError {
+    Incomplete { missing: Fraction },
+    Overfull { excess: Fraction },
+}
+
+impl fmt::Display for MeasureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Incomplete { missing } => {
+                write!(f, "Incomplete measure, missing {} of a whole note", missing)
+            }
+            Self::Overfull { excess } => {
+                write!(f, "Overfull measure by {} of a whole note", excess)
+            }
+        }
+    }
+}
+
+/// A tied part of a note split by [`TimeSignature::split`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitNote {
+    /// Start in whole notes from the first barline.
+    pub start: Fraction,
+    pub duration: Duration,
+    /// `true` if this part is tied to the next one.
+    pub is_tied: bool,
+}
+
+/// Iterator over the tied parts of a note.
+///
+/// Created by [`TimeSignature::split`].
+pub struct Split<'a> {
+    meter: &'a TimeSignature,
+    position: Fraction,
+    end: Fraction,
+    piece: Option<Decompose>,
+    remainder: Fraction,
+}
+
+impl Split<'_> {
+    /// Returns the length in whole notes of the parts skipped so far
+    /// because they're shorter than a 128th note.
+    pub fn remainder(&self) -> Fraction {
+        self.remainder
+    }
+}
+
+impl Iterator for Split<'_> {
+    type Item = SplitNote;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(duration) = self.piece.as_mut().and_then(Iterator::next) {
+                let start = self.position;
+                self.position += duration.length();
+
+                return Some(SplitNote {
+                    start,
+                    duration,
+                    is_tied: self.position < self.end,
+                });
+            }
+
+            if self.position >= self.end {
+                return None;
+            }
+
+            // Skip over the end of a piece that can't be written
+            if let Some(piece) = self.piece.take() {
+                self.position += piece.remainder();
+                self.remainder += piece.remainder();
+                if self.position >= self.end {
+                    return None;
+                }
+            }
+
+            let end = self
+                .meter
+                .boundary(self.position)
+                .map_or(self.end, |boundary| boundary.min(self.end));
+            self.piece = Some(Duration::decompose(end - self.position));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_groups_irregular_meters() {
+        let meter = TimeSignature::new(DurationKind::Eigth, 5);
+        assert_eq!(meter.kind(), MeterKind::Irregular);
+        assert_eq!(meter.groups(), [2, 3]);
+
+        let meter = TimeSignature::new(DurationKind::Quarter, 4);
+        assert_eq!(meter.groups(), [1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn it_parses_its_own_display() {
+        for s in ["4/4", "6/8", "3+3+2/8", "2+2+3/16", "5/4"] {
+            assert_eq!(s.parse::<TimeSignature>().unwrap().to_string(), s);
+        }
+
+        assert_eq!("3/5".parse::<TimeSignature>(), Err(MeterError::InvalidUnit));
+        assert_eq!(
+            "3+0/8".parse::<TimeSignature>(),
+            Err(MeterError::InvalidBeats)
+        );
+    }
+
+    #[test]
+    fn it_splits_notes_at_barlines() {
+        let meter = TimeSignature::new(DurationKind::Quarter, 3);
+        let split: Fraction = meter
+            .split(Fraction::new(1, 2), Fraction::new(3, 2))
+            .inspect(|note| {
+                let (_, offset) = note.start.div_rem(meter.measure());
+                assert!(offset + note.duration.length() <= meter.measure());
+            })
+            .map(|note| note.duration.length())
+            .sum();
+
+        assert_eq!(split, Fraction::new(3, 2));
+    }
+
+    #[test]
+    fn it_keeps_the_remainder_of_parts_it_cant_write() {
+        let meter = TimeSignature::new(DurationKind::Quarter, 4);
+        let mut split = meter.split(Fraction::new(1, 512), Fraction::new(3, 2));
+        let written: Fraction = split.by_ref().map(|note| note.duration.length()).sum();
+
+        // A 512th note before the second beat and at the end are skipped
+        assert_eq!(split.remainder(), Fraction::new(1, 256));
+        assert_eq!(written + split.remainder(), Fraction::new(3, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "time signature must have beats")]
+    fn it_rejects_zero_beats() {
+        TimeSignature::new(DurationKind::Quarter, 0);
+    }
+}
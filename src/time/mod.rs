@@ -0,0 +1,348 @@
+//! Note durations and time signatures
+
+use core::iter::Sum;
+
+mod fraction;
+pub use fraction::Fraction;
+
+mod meter;
+pub use meter::{MeasureError, MeterError, MeterKind, Split, SplitNote, TimeSignature};
+
+mod ticks;
+pub use ticks::{PerformedNote, Ticks};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod tempo;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod quantize;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod groove;
+
+/// The written value of a note, from a breve down to a 128th note.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DurationKind {
+    Breve,
+    Whole,
+    Half,
+    Quarter,
+    Eigth,
+    Sixteenth,
+    ThirtySecond,
+    SixtyFourth,
+    HundredTwentyEighth,
+}
+
+impl DurationKind {
+    /// Every duration kind from the longest to the shortest.
+    pub const ALL: [Self; 9] = [
+        Self::Breve,
+        Self::Whole,
+        Self::Half,
+        Self::Quarter,
+        Self::Eigth,
+        Self::Sixteenth,
+        Self::ThirtySecond,
+        Self::SixtyFourth,
+        Self::HundredTwentyEighth,
+    ];
+
+    /// Returns the length of this note value in whole notes.
+    /// ```
+    /// use staff::time::{DurationKind, Fraction};
+    ///
+    /// assert_eq!(DurationKind::Breve.length(), Fraction::from_int(2));
+    /// assert_eq!(DurationKind::Sixteenth.length(), Fraction::new(1, 16));
+    /// ```
+    pub const fn length(self) -> Fraction {
+        match self {
+            Self::Breve => Fraction::from_int(2),
+            kind => Fraction::new(1, 1 << (kind as u8 - 1)),
+        }
+    }
+
+    /// Returns the note value with `length` in whole notes, if there is one.
+    pub fn from_length(length: Fraction) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.length() == length)
+    }
+}
+
+/// A written note duration made of its value, dots and tuplet.
+/// ```
+/// use staff::time::{Duration, DurationKind, Fraction, Tuplet};
+///
+/// let double_dotted = Duration::new(DurationKind::Half, 2);
+/// assert_eq!(double_dotted.length(), Fraction::new(7, 8));
+///
+/// let triplet = Duration::from(DurationKind::Eigth).with_tuplet(Tuplet::TRIPLET);
+/// assert_eq!(triplet.length(), Fraction::new(1, 12));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Duration {
+    pub kind: DurationKind,
+    pub dots: u8,
+    pub tuplet: Option<Tuplet>,
+}
+
+impl Duration {
+    pub fn new(kind: DurationKind, dots: u8) -> Self {
+        Self {
+            kind,
+            dots,
+            tuplet: None,
+        }
+    }
+
+    /// Returns this duration played as `tuplet`,
+    /// nested inside any tuplet it already belongs to.
+    pub fn with_tuplet(mut self, tuplet: Tuplet) -> Self {
+        self.tuplet = Some(match self.tuplet {
+            Some(outer) => outer.nested(tuplet),
+            None => tuplet,
+        });
+        self
+    }
+
+    pub fn is_dotted(self) -> bool {
+        self.dots > 0
+    }
+
+    /// Returns the exact length of this duration in whole notes.
+    ///
+    /// # Panics
+    /// Panics if there are 63 dots or more, or the length doesn't fit in a [`Fraction`].
+    pub fn length(self) -> Fraction {
+        let base = self.kind.length();
+
+        // Each dot adds half of the previous value: 2 - 1 / 2^dots
+        let dots = 1u64
+            .checked_shl(self.dots as u32)
+            .filter(|dots| *dots <= u64::MAX / 2)
+            .expect("too many dots");
+        let dotted = base * Fraction::new(2 * dots - 1, dots);
+
+        match self.tuplet {
+            Some(tuplet) => dotted * tuplet.ratio(),
+            None => dotted,
+        }
+    }
+
+    /// Returns the length of this duration in beats of `unit`,
+    /// where a `unit` of 4 counts quarter notes.
+    pub fn beats(self, unit: u8) -> f64 {
+        (self.length() * unit as u64).to_f64()
+    }
+
+    /// Returns the notatable durations, longest first, that tie together to `length` in whole notes.
+    ///
+    /// Lengths that aren't a power of two divisions use a tuplet, such as 3:2 for thirds.
+    /// ```
+    /// use staff::time::{Duration, DurationKind, Fraction, Tuplet};
+    ///
+    /// let tied: Vec<_> = Duration::decompose(Fraction::new(9, 8)).collect();
+    /// assert_eq!(
+    ///     tied,
+    ///     [Duration::from(DurationKind::Whole), Duration::from(DurationKind::Eigth)]
+    /// );
+    ///
+    /// let tied: Vec<_> = Duration::decompose(Fraction::new(1, 3)).collect();
+    /// assert_eq!(
+    ///     tied,
+    ///     [Duration::from(DurationKind::Half).with_tuplet(Tuplet::TRIPLET)]
+    /// );
+    /// ```
+    pub fn decompose(length: Fraction) -> Decompose {
+        Decompose::new(length)
+    }
+}
+
+impl From<DurationKind> for Duration {
+    fn from(value: DurationKind) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+/// Returns the tied length of every duration in whole notes.
+impl Sum<Duration> for Fraction {
+    fn sum<I: Iterator<Item = Duration>>(iter: I) -> Self {
+        iter.map(Duration::length).sum()
+    }
+}
+
+/// A tuplet that plays `actual` notes in the time of `normal` notes, such as 3:2 for triplets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tuplet {
+    pub actual: u16,
+    pub normal: u16,
+}
+
+impl Tuplet {
+    pub const TRIPLET: Self = Self::new(3, 2);
+
+    pub const QUINTUPLET: Self = Self::new(5, 4);
+
+    pub const fn new(actual: u16, normal: u16) -> Self {
+        Self { actual, normal }
+    }
+
+    /// Returns the tuplet of `inner` played inside of this tuplet.
+    /// ```
+    /// use staff::time::Tuplet;
+    ///
+    /// let nested = Tuplet::QUINTUPLET.nested(Tuplet::TRIPLET);
+    /// assert_eq!(nested, Tuplet::new(15, 8));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the nested number of notes doesn't fit in a `u16`.
+    pub const fn nested(self, inner: Self) -> Self {
+        match (
+            self.actual.checked_mul(inner.actual),
+            self.normal.checked_mul(inner.normal),
+        ) {
+            (Some(actual), Some(normal)) => Self::new(actual, normal),
+            _ => panic!("nested tuplet overflow"),
+        }
+    }
+
+    /// Returns the factor each note's length is multiplied by.
+    pub const fn ratio(self) -> Fraction {
+        Fraction::new(self.normal as u64, self.actual as u64)
+    }
+}
+
+/// Iterator over the notatable durations that tie together to a length.
+///
+/// Created by [`Duration::decompose`].
+pub struct Decompose {
+    remaining: Fraction,
+    tuplet: Option<Tuplet>,
+    max_dots: u8,
+}
+
+impl Decompose {
+    fn new(length: Fraction) -> Self {
+        // Split the denominator into a power of two and the odd part left for a tuplet
+        let odd = length.denom() >> length.denom().trailing_zeros();
+        let tuplet = if odd > 1 && odd <= u16::MAX as u64 {
+            let normal = 1 << (63 - odd.leading_zeros());
+            Some(Tuplet::new(odd as u16, normal))
+        } else {
+            None
+        };
+
+        let remaining = match tuplet {
+            Some(tuplet) => length / tuplet.ratio(),
+            None => length,
+        };
+
+        Self {
+            remaining,
+            tuplet,
+            max_dots: 2,
+        }
+    }
+
+    /// Limit each duration to at most `max_dots` dots (2 by default).
+    pub fn with_max_dots(mut self, max_dots: u8) -> Self {
+        self.max_dots = max_dots;
+        self
+    }
+
+    /// Returns the length in whole notes that is left over,
+    /// which is only non-zero once iteration ends on a length shorter than a 128th note.
+    pub fn remainder(&self) -> Fraction {
+        match self.tuplet {
+            Some(tuplet) => self.remaining * tuplet.ratio(),
+            None => self.remaining,
+        }
+    }
+}
+
+impl Iterator for Decompose {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_zero() {
+            return None;
+        }
+
+        let kind = DurationKind::ALL
+            .into_iter()
+            .find(|kind| kind.length() <= self.remaining)?;
+
+        let mut duration = Duration::new(kind, 0);
+        while duration.dots < self.max_dots
+            && kind != DurationKind::HundredTwentyEighth
+            && Duration::new(kind, duration.dots + 1).length() <= self.remaining
+        {
+            duration.dots += 1;
+        }
+
+        self.remaining -= duration.length();
+        duration.tuplet = self.tuplet;
+        Some(duration)
+    }
+}
+
+#[cfg(feature = "ui")]
+impl<'a> dioxus::prelude::IntoAttributeValue<'a> for Duration {
+    fn into_value(
+        self,
+        _bump: &'a dioxus::core::exports::bumpalo::Bump,
+    ) -> dioxus::core::AttributeValue<'a> {
+        dioxus::core::AttributeValue::Int(((self.kind as u8) << 2 | self.dots.min(3)) as _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decomposes_lengths_that_tie_back_together() {
+        for denom in [1, 2, 3, 4, 5, 6, 8, 12, 16, 24, 32, 128] {
+            for numer in 1..=40 {
+                let length = Fraction::new(numer, denom);
+                let mut decompose = Duration::decompose(length);
+                let tied: Fraction = decompose.by_ref().sum();
+
+                assert_eq!(tied, length);
+                assert!(decompose.remainder().is_zero());
+            }
+        }
+    }
+
+    #[test]
+    fn it_keeps_the_remainder_shorter_than_a_128th() {
+        let mut decompose = Duration::decompose(Fraction::new(1, 256));
+        assert_eq!(decompose.next(), None);
+        assert_eq!(decompose.remainder(), Fraction::new(1, 256));
+    }
+
+    #[test]
+    #[should_panic(expected = "fraction denominator overflow")]
+    fn it_panics_on_fraction_overflow() {
+        let _ = Fraction::new(1, u64::MAX) * Fraction::new(1, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many dots")]
+    fn it_panics_on_too_many_dots() {
+        Duration::new(DurationKind::Whole, 64).length();
+    }
+
+    #[test]
+    #[should_panic(expected = "nested tuplet overflow")]
+    fn it_panics_on_nested_tuplet_overflow() {
+        Tuplet::new(300, 2).nested(Tuplet::new(300, 2));
+    }
+}
@@ -0,0 +1,516 @@
+//! Quantisation of performed notes to notatable rhythms
+//!
+//! ```
+//! use staff::time::quantize::{PerformedNote, Quantizer};
+//! use staff::time::tempo::{TempoMap, Ticks};
+//! use staff::time::{Duration, DurationKind, TimeSignature};
+//! use staff::midi;
+//!
+//! let tempo = TempoMap::new(480, 120.);
+//!
+//! // Slightly early and late quarter notes, played in seconds
+//! let notes = [
+//!     PerformedNote::from_seconds(midi!(C, 4), 0.02, 0.45, 90, &tempo),
+//!     PerformedNote::from_seconds(midi!(E, 4), 0.49, 0.5, 80, &tempo),
+//!     PerformedNote::from_seconds(midi!(G, 4), 1.03, 0.9, 80, &tempo),
+//! ];
+//!
+//! let meter = TimeSignature::new(DurationKind::Quarter, 4);
+//! let quantized = Quantizer::new(meter, tempo.ppq()).quantize(&notes);
+//!
+//! let durations: Vec<_> = quantized
+//!     .events(meter)
+//!     .into_iter()
+//!     .map(|event| (event.duration(), event.is_rest()))
+//!     .collect();
+//!
+//! assert_eq!(
+//!     durations,
+//!     [
+//!         (Duration::from(DurationKind::Quarter), false),
+//!         (Duration::from(DurationKind::Quarter), false),
+//!         (Duration::from(DurationKind::Half), false),
+//!     ]
+//! );
+//! ```
+
+use super::{
+    tempo::{TempoMap, Ticks},
+    Duration, Fraction, TimeSignature,
+};
+use crate::midi::MidiNote;
+
+pub use super::PerformedNote;
+
+/// Cost of each grid division per beat, so simpler grids win close matches.
+const DIVISION_COST: f64 = 0.004;
+
+/// Weight of note ends when choosing a grid, compared to onsets.
+const END_WEIGHT: f64 = 0.5;
+
+/// A position in whole notes and its weight when choosing a grid.
+type Point = (f64, f64);
+
+impl PerformedNote {
+    /// Create a performed note from its onset and length in seconds with a `tempo` map.
+    pub fn from_seconds(
+        midi: MidiNote,
+        onset: f64,
+        length: f64,
+        velocity: u8,
+        tempo: &TempoMap,
+    ) -> Self {
+        let start = tempo.ticks_at(onset);
+        let end = tempo.ticks_at(onset + length).max(start);
+        Self::new(midi, start, end - start, velocity)
+    }
+}
+
+/// A note snapped to a grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuantizedNote {
+    pub midi: MidiNote,
+    pub velocity: u8,
+    /// Start in whole notes from the first barline.
+    pub start: Fraction,
+    /// Length in whole notes.
+    pub length: Fraction,
+    /// Onset in ticks moved towards the grid by the quantizer's strength, for playback.
+    pub onset: Ticks,
+}
+
+/// A notated note, chord or rest from [`Quantized::events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    Notes {
+        start: Fraction,
+        duration: Duration,
+        notes: Vec<MidiNote>,
+        /// `true` if these notes are tied to the next event.
+        is_tied: bool,
+    },
+    Rest {
+        start: Fraction,
+        duration: Duration,
+    },
+}
+
+impl Event {
+    pub fn start(&self) -> Fraction {
+        match self {
+            Self::Notes { start, .. } | Self::Rest { start, .. } => *start,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Notes { duration, .. } | Self::Rest { duration, .. } => *duration,
+        }
+    }
+
+    pub fn is_rest(&self) -> bool {
+        matches!(self, Self::Rest { .. })
+    }
+}
+
+/// Performed notes snapped to a grid.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quantized {
+    pub notes: Vec<QuantizedNote>,
+    /// Detected swing as the ratio of the first to the second eighth of each beat,
+    /// such as 2 for triplet swing, or `None` if the notes were played straight.
+    pub swing: Option<f64>,
+}
+
+impl Quantized {
+    /// Returns the notes as one voice of tied notes, chords and rests in `meter`,
+    /// filled with rests to the end of the last measure.
+    ///
+    /// Notes starting together become one chord,
+    /// and notes are cut short where the next note starts.
+    pub fn events(&self, meter: TimeSignature) -> Vec<Event> {
+        let mut notes = self.notes.clone();
+        notes.sort_by_key(|note| note.start);
+
+        let mut events = Vec::new();
+        let mut position = Fraction::ZERO;
+        let mut index = 0;
+        while index < notes.len() {
+            let start = notes[index].start;
+            let group = notes[index..]
+                .iter()
+                .take_while(|note| note.start == start)
+                .count();
+            let chord = &notes[index..index + group];
+            index += group;
+
+            let mut end = chord.iter().map(|note| note.start + note.length).max();
+            if let Some(next) = notes.get(index) {
+                end = end.map(|end| end.min(next.start));
+            }
+            let end = end.unwrap_or(start);
+
+            push_rests(&mut events, meter, position, start);
+            let midi: Vec<_> = chord.iter().map(|note| note.midi).collect();
+            events.extend(meter.split(start, end - start).map(|split| Event::Notes {
+                start: split.start,
+                duration: split.duration,
+                notes: midi.clone(),
+                is_tied: split.is_tied,
+            }));
+            position = end;
+        }
+
+        let (bars, offset) = position.div_rem(meter.measure());
+        if !offset.is_zero() {
+            push_rests(&mut events, meter, position, meter.measure() * (bars + 1));
+        }
+
+        events
+    }
+}
+
+fn push_rests(events: &mut Vec<Event>, meter: TimeSignature, start: Fraction, end: Fraction) {
+    if end > start {
+        events.extend(meter.split(start, end - start).map(|split| Event::Rest {
+            start: split.start,
+            duration: split.duration,
+        }));
+    }
+}
+
+/// Quantizer that snaps performed notes to a grid chosen for each beat.
+///
+/// Each beat of the meter picks the straight or triplet division that best fits
+/// the onsets and ends played in it, preferring simpler divisions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantizer {
+    meter: TimeSignature,
+    ppq: u16,
+    strength: f64,
+    detect_swing: bool,
+    triplets: bool,
+    max_division: u8,
+}
+
+impl Quantizer {
+    /// Create a quantizer for `meter` with `ppq` ticks per quarter note,
+    /// full strength, swing detection, triplets and up to 4 divisions per beat.
+    pub fn new(meter: TimeSignature, ppq: u16) -> Self {
+        Self {
+            meter,
+            ppq,
+            strength: 1.,
+            detect_swing: true,
+            triplets: true,
+            max_division: 4,
+        }
+    }
+
+    /// Move the playback onsets `strength` of the way to the grid, from 0 to 1.
+    ///
+    /// Notated starts and lengths are always fully snapped.
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength.clamp(0., 1.);
+        self
+    }
+
+    /// Enable or disable straightening swung eighths before quantizing.
+    pub fn with_swing_detection(mut self, detect_swing: bool) -> Self {
+        self.detect_swing = detect_swing;
+        self
+    }
+
+    /// Enable or disable triplet divisions.
+    pub fn with_triplets(mut self, triplets: bool) -> Self {
+        self.triplets = triplets;
+        self
+    }
+
+    /// Limit the straight grid to `max_division` divisions per beat, such as 4 for sixteenths.
+    pub fn with_max_division(mut self, max_division: u8) -> Self {
+        self.max_division = max_division.max(1);
+        self
+    }
+
+    /// Snap `notes` to the grid.
+    pub fn quantize(&self, notes: &[PerformedNote]) -> Quantized {
+        let whole = self.ppq as f64 * 4.;
+        let position = |ticks: Ticks| ticks.0 as f64 / whole;
+
+        let swing = if self.detect_swing {
+            self.swing(notes.iter().map(|note| position(note.onset)))
+        } else {
+            None
+        };
+
+        // Straighten swung positions before choosing grids
+        let straight = |x: f64| match swing {
+            Some(p) => self.map_beat(x, |r| unswing(r, p)),
+            None => x,
+        };
+
+        let mut points: Vec<Point> = Vec::new();
+        for note in notes {
+            points.push((straight(position(note.onset)), 1.));
+            points.push((straight(position(note.onset + note.length)), END_WEIGHT));
+        }
+        let grids = self.grids(&points);
+
+        let notes = notes
+            .iter()
+            .map(|note| {
+                let onset = position(note.onset);
+                let start = self.snap(&grids, straight(onset));
+                let mut end = self.snap(&grids, straight(position(note.onset + note.length)));
+                if end <= start {
+                    end = start + self.step(&grids, start);
+                }
+
+                // Swing the grid back for playback
+                let grid = match swing {
+                    Some(p) => self.map_beat(start.to_f64(), |r| reswing(r, p)),
+                    None => start.to_f64(),
+                };
+                let moved = onset + (grid - onset) * self.strength;
+
+                QuantizedNote {
+                    midi: note.midi,
+                    velocity: note.velocity,
+                    start,
+                    length: end - start,
+                    onset: Ticks((moved * whole).round().max(0.) as u64),
+                }
+            })
+            .collect();
+
+        Quantized {
+            notes,
+            swing: swing.map(|p| p / (1. - p)),
+        }
+    }
+
+    /// Returns the beat containing `x` in whole notes, as its start and length.
+    fn beat(&self, x: f64) -> (Fraction, Fraction) {
+        let measure = self.meter.measure();
+        let bar = (x / measure.to_f64()).floor().max(0.) as u64;
+        let barline = measure * bar;
+
+        let mut starts: Vec<_> = self.meter.beats().collect();
+        starts.push(measure);
+        let offset = x - barline.to_f64();
+        let index = starts
+            .windows(2)
+            .position(|beat| offset < beat[1].to_f64())
+            .unwrap_or(starts.len() - 2);
+
+        (barline + starts[index], starts[index + 1] - starts[index])
+    }
+
+    /// Apply `f` to the position of `x` within its beat, from 0 to 1.
+    fn map_beat(&self, x: f64, f: impl Fn(f64) -> f64) -> f64 {
+        let (start, length) = self.beat(x);
+        let (start, length) = (start.to_f64(), length.to_f64());
+        start + f((x - start) / length) * length
+    }
+
+    /// Returns the mean position of off-beat eighths within their beats,
+    /// if they're late enough to be swung.
+    fn swing(&self, onsets: impl Iterator<Item = f64>) -> Option<f64> {
+        if !self.meter.unit.length().is_dyadic()
+            || self.meter.groups().iter().any(|group| *group != 1)
+        {
+            return None;
+        }
+
+        let positions: Vec<_> = onsets
+            .map(|x| {
+                let (start, length) = self.beat(x);
+                (x - start.to_f64()) / length.to_f64()
+            })
+            .collect();
+
+        // Sixteenths and triplets also play between the beat and its swung off-beat
+        if positions.iter().any(|r| (0.15..0.4).contains(r)) {
+            return None;
+        }
+
+        let off_beats: Vec<_> = positions
+            .into_iter()
+            .filter(|r| (0.4..0.85).contains(r))
+            .collect();
+        if off_beats.len() < 2 {
+            return None;
+        }
+
+        let mean = off_beats.iter().sum::<f64>() / off_beats.len() as f64;
+        let spread = off_beats
+            .iter()
+            .map(|r| (r - mean).abs())
+            .fold(0., f64::max);
+        (mean > 0.56 && spread < 0.1).then_some(mean)
+    }
+
+    /// Returns the chosen division of each beat, by the start of the beat in whole notes.
+    fn grids(&self, points: &[Point]) -> Vec<(Fraction, u64)> {
+        let mut beats: Vec<(Fraction, Fraction, Vec<Point>)> = Vec::new();
+        for (x, weight) in points {
+            let (start, length) = self.beat(*x);
+            match beats.iter_mut().find(|(other, ..)| *other == start) {
+                Some((.., points)) => points.push((*x, *weight)),
+                None => beats.push((start, length, vec![(*x, *weight)])),
+            }
+        }
+
+        beats
+            .into_iter()
+            .map(|(start, length, points)| {
+                let division = self
+                    .divisions(length)
+                    .map(|division| {
+                        let step = (length / division).to_f64();
+                        let error: f64 = points
+                            .iter()
+                            .map(|(x, weight)| {
+                                let offset = x - start.to_f64();
+                                (offset - (offset / step).round() * step).abs() * weight
+                            })
+                            .sum();
+                        (division, error + DIVISION_COST * division as f64)
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(division, _)| division)
+                    .unwrap_or(1);
+
+                (start, division)
+            })
+            .collect()
+    }
+
+    /// Returns the divisions a beat of `length` can use.
+    fn divisions(&self, length: Fraction) -> impl Iterator<Item = u64> + '_ {
+        let max = self.max_division as u64;
+        (1..=max * 2).filter(move |division| {
+            if *division == 1 {
+                return true;
+            }
+
+            let is_straight = (length / *division).is_dyadic();
+            if is_straight {
+                // Beats of three units are straight in threes
+                *division <= max || (!length.is_dyadic() && *division <= max * 3 / 2)
+            } else {
+                self.triplets && *division <= max * 3 / 2 && division % 3 == 0
+            }
+        })
+    }
+
+    /// Returns the grid step of the beat containing `x`.
+    fn step(&self, grids: &[(Fraction, u64)], x: Fraction) -> Fraction {
+        let (start, length) = self.beat(x.to_f64());
+        let division = grids
+            .iter()
+            .find(|(beat, _)| *beat == start)
+            .map(|(_, division)| *division)
+            .unwrap_or(1);
+        length / division
+    }
+
+    /// Snap `x` in whole notes to the grid of its beat.
+    fn snap(&self, grids: &[(Fraction, u64)], x: f64) -> Fraction {
+        let (start, length) = self.beat(x);
+        let division = grids
+            .iter()
+            .find(|(beat, _)| *beat == start)
+            .map(|(_, division)| *division)
+            .unwrap_or(1);
+
+        let steps = ((x - start.to_f64()) / (length / division).to_f64())
+            .round()
+            .max(0.) as u64;
+        start + length / division * steps
+    }
+}
+
+/// Map a swung position `r` within a beat, with the off-beat at `p`, to straight eighths.
+fn unswing(r: f64, p: f64) -> f64 {
+    if r < p {
+        r * 0.5 / p
+    } else {
+        0.5 + (r - p) * 0.5 / (1. - p)
+    }
+}
+
+/// Map a straight position `r` within a beat to swung eighths with the off-beat at `p`.
+fn reswing(r: f64, p: f64) -> f64 {
+    if r < 0.5 {
+        r * p / 0.5
+    } else {
+        p + (r - 0.5) * (1. - p) / 0.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::Octave,
+        time::{DurationKind, Tuplet},
+        Pitch,
+    };
+
+    fn note(onset: u64, length: u64) -> PerformedNote {
+        PerformedNote::new(
+            MidiNote::new(Pitch::C, Octave::FOUR),
+            Ticks(onset),
+            Ticks(length),
+            100,
+        )
+    }
+
+    #[test]
+    fn it_chooses_triplets_for_triplet_onsets() {
+        let meter = TimeSignature::new(DurationKind::Quarter, 4);
+        let notes = [note(0, 150), note(165, 150), note(318, 160)];
+        let quantized = Quantizer::new(meter, 480).quantize(&notes);
+
+        assert_eq!(quantized.swing, None);
+        let durations: Vec<_> = quantized
+            .events(meter)
+            .into_iter()
+            .take(3)
+            .map(|event| event.duration())
+            .collect();
+        let triplet = Duration::from(DurationKind::Eigth).with_tuplet(Tuplet::TRIPLET);
+        assert_eq!(durations, [triplet; 3]);
+    }
+
+    #[test]
+    fn it_detects_swing() {
+        let meter = TimeSignature::new(DurationKind::Quarter, 4);
+        let notes: Vec<_> = (0..8)
+            .map(|i| note(i / 2 * 480 + (i % 2) * 320, 150))
+            .collect();
+        let quantized = Quantizer::new(meter, 480).quantize(&notes);
+
+        assert!((quantized.swing.unwrap() - 2.).abs() < 0.01);
+        for (i, note) in quantized.notes.iter().enumerate() {
+            assert_eq!(note.start, Fraction::new(i as u64, 8));
+            assert_eq!(note.onset, notes[i].onset);
+        }
+    }
+
+    #[test]
+    fn it_applies_strength_to_onsets() {
+        let meter = TimeSignature::new(DurationKind::Quarter, 4);
+        let quantized = Quantizer::new(meter, 480)
+            .with_strength(0.5)
+            .quantize(&[note(20, 460)]);
+
+        assert_eq!(quantized.notes[0].start, Fraction::ZERO);
+        assert_eq!(quantized.notes[0].onset, Ticks(10));
+    }
+}
@@ -0,0 +1,464 @@
+//! Tempo maps for converting between musical time and seconds
+//!
+//! ```
+//! use staff::time::tempo::{Position, TempoMap, Ticks};
+//! use staff::time::{DurationKind, TimeSignature};
+//!
+//! let mut map = TempoMap::new(480, 120.);
+//! map.set_meter(3, TimeSignature::new(DurationKind::Eigth, 6));
+//!
+//! // Two bars of 4/4 at 120 BPM take 4 seconds
+//! let ticks = map.ticks(Position::new(3, 1, 0));
+//! assert_eq!(ticks, Ticks(480 * 8));
+//! assert_eq!(map.seconds(ticks), 4.);
+//!
+//! // Bar 3 counts eighths
+//! assert_eq!(map.position(ticks + Ticks(240)), Position::new(3, 2, 0));
+//! ```
+
+use super::TimeSignature;
+use core::fmt;
+
+pub use super::Ticks;
+
+/// A musical position as a bar and beat, both starting at 1, and ticks into that beat.
+///
+/// Beats count the unit of the time signature, so 6/8 has 6 beats of an eighth.
+/// The length of a bar depends on its meter, so arithmetic on positions goes through a
+/// [`TempoMap`] with [`TempoMap::offset`] and [`TempoMap::ticks_between`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub bar: u32,
+    pub beat: u32,
+    pub tick: u32,
+}
+
+impl Position {
+    pub const fn new(bar: u32, beat: u32, tick: u32) -> Self {
+        Self { bar, beat, tick }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new(1, 1, 0)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.bar, self.beat, self.tick)
+    }
+}
+
+/// How the tempo moves from one change to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ramp {
+    /// Hold this tempo until the next change.
+    Step,
+    /// Change linearly in ticks to the next tempo, for an accelerando or ritardando.
+    Linear,
+}
+
+/// A tempo in quarter notes per minute starting at a tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TempoChange {
+    pub at: Ticks,
+    pub bpm: f64,
+    pub ramp: Ramp,
+}
+
+/// A time signature starting at a bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeterChange {
+    pub bar: u32,
+    pub meter: TimeSignature,
+}
+
+/// Map of tempo and time signature changes over a piece.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TempoMap {
+    ppq: u16,
+    tempos: Vec<TempoChange>,
+    meters: Vec<MeterChange>,
+}
+
+impl TempoMap {
+    /// Create a tempo map in 4/4 at a constant `bpm` with `ppq` ticks per quarter note.
+    ///
+    /// # Panics
+    /// Panics if `ppq` is zero or `bpm` isn't positive.
+    pub fn new(ppq: u16, bpm: f64) -> Self {
+        assert!(ppq > 0, "PPQ must be positive");
+        assert!(bpm > 0., "tempo must be positive");
+
+        Self {
+            ppq,
+            tempos: vec![TempoChange {
+                at: Ticks(0),
+                bpm,
+                ramp: Ramp::Step,
+            }],
+            meters: vec![MeterChange {
+                bar: 1,
+                meter: TimeSignature::new(super::DurationKind::Quarter, 4),
+            }],
+        }
+    }
+
+    pub fn ppq(&self) -> u16 {
+        self.ppq
+    }
+
+    pub fn tempos(&self) -> &[TempoChange] {
+        &self.tempos
+    }
+
+    pub fn meters(&self) -> &[MeterChange] {
+        &self.meters
+    }
+
+    /// Change to `bpm` at `at`, replacing any change at the same tick.
+    ///
+    /// # Panics
+    /// Panics if `bpm` isn't positive.
+    pub fn set_tempo(&mut self, at: Ticks, bpm: f64) {
+        self.insert_tempo(TempoChange {
+            at,
+            bpm,
+            ramp: Ramp::Step,
+        });
+    }
+
+    /// Ramp the tempo linearly from `from` to reach `bpm` at `to`,
+    /// starting from the tempo already playing at `from`.
+    /// ```
+    /// use staff::time::tempo::{TempoMap, Ticks};
+    ///
+    /// let mut map = TempoMap::new(480, 60.);
+    /// map.ramp_tempo(Ticks(0), Ticks(960), 120.);
+    ///
+    /// assert_eq!(map.tempo(Ticks(480)), 90.);
+    /// assert_eq!(map.tempo(Ticks(2000)), 120.);
+    ///
+    /// // Faster than a constant 60 BPM, slower than 120 BPM
+    /// let seconds = map.seconds(Ticks(960));
+    /// assert!(seconds > 1. && seconds < 2.);
+    /// assert!((map.ticks_at(seconds).0 as i64 - 960).abs() <= 1);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `to` isn't after `from` or `bpm` isn't positive.
+    pub fn ramp_tempo(&mut self, from: Ticks, to: Ticks, bpm: f64) {
+        assert!(to > from, "tempo ramp must end after it starts");
+
+        let start = self.tempo(from);
+        self.tempos
+            .retain(|change| change.at <= from || change.at > to);
+
+        self.insert_tempo(TempoChange {
+            at: from,
+            bpm: start,
+            ramp: Ramp::Linear,
+        });
+        self.insert_tempo(TempoChange {
+            at: to,
+            bpm,
+            ramp: Ramp::Step,
+        });
+    }
+
+    /// Insert a tempo `change`, replacing any change at the same tick.
+    ///
+    /// # Panics
+    /// Panics if the tempo isn't positive.
+    pub fn insert_tempo(&mut self, change: TempoChange) {
+        assert!(change.bpm > 0., "tempo must be positive");
+
+        match self
+            .tempos
+            .binary_search_by(|other| other.at.cmp(&change.at))
+        {
+            Ok(index) => self.tempos[index] = change,
+            Err(index) => self.tempos.insert(index, change),
+        }
+    }
+
+    /// Change the time signature to `meter` from `bar` onwards, replacing any change at `bar`.
+    ///
+    /// # Panics
+    /// Panics if `bar` is zero.
+    pub fn set_meter(&mut self, bar: u32, meter: TimeSignature) {
+        assert!(bar > 0, "bars start at 1");
+
+        let change = MeterChange { bar, meter };
+        match self.meters.binary_search_by(|other| other.bar.cmp(&bar)) {
+            Ok(index) => self.meters[index] = change,
+            Err(index) => self.meters.insert(index, change),
+        }
+    }
+
+    /// Returns the time signature of `bar`.
+    pub fn meter(&self, bar: u32) -> TimeSignature {
+        self.meters
+            .iter()
+            .rev()
+            .find(|change| change.bar <= bar)
+            .unwrap_or(&self.meters[0])
+            .meter
+    }
+
+    /// Returns the tempo in quarter notes per minute at `ticks`.
+    pub fn tempo(&self, ticks: Ticks) -> f64 {
+        let index = self.tempo_index(ticks);
+        let change = self.tempos[index];
+
+        match (change.ramp, self.tempos.get(index + 1)) {
+            (Ramp::Linear, Some(next)) => {
+                let progress = (ticks.0 - change.at.0) as f64 / (next.at.0 - change.at.0) as f64;
+                change.bpm + (next.bpm - change.bpm) * progress
+            }
+            _ => change.bpm,
+        }
+    }
+
+    /// Returns the time in seconds at `ticks`.
+    pub fn seconds(&self, ticks: Ticks) -> f64 {
+        let mut seconds = 0.;
+        for (index, change) in self.tempos.iter().enumerate() {
+            if change.at >= ticks {
+                break;
+            }
+
+            let end = self
+                .tempos
+                .get(index + 1)
+                .map(|next| next.at.min(ticks))
+                .unwrap_or(ticks);
+            seconds += self.segment_seconds(index, end);
+        }
+        seconds
+    }
+
+    /// Returns the nearest tick at `seconds`.
+    pub fn ticks_at(&self, seconds: f64) -> Ticks {
+        let mut elapsed = 0.;
+        for (index, change) in self.tempos.iter().enumerate() {
+            let segment = match self.tempos.get(index + 1) {
+                Some(next) => self.segment_seconds(index, next.at),
+                None => f64::INFINITY,
+            };
+
+            if elapsed + segment >= seconds {
+                let offset = self.segment_ticks(index, seconds - elapsed);
+                return Ticks(change.at.0 + offset.max(0.).round() as u64);
+            }
+            elapsed += segment;
+        }
+
+        Ticks(0)
+    }
+
+    /// Returns the sample at `ticks` at a `sample_rate` in Hz, rounded to the nearest sample.
+    pub fn sample(&self, ticks: Ticks, sample_rate: u32) -> u64 {
+        (self.seconds(ticks) * sample_rate as f64).round() as u64
+    }
+
+    /// Returns the nearest tick at `sample` at a `sample_rate` in Hz.
+    pub fn ticks_at_sample(&self, sample: u64, sample_rate: u32) -> Ticks {
+        self.ticks_at(sample as f64 / sample_rate as f64)
+    }
+
+    /// Returns the bar, beat and tick at `ticks`.
+    pub fn position(&self, ticks: Ticks) -> Position {
+        let mut bar = 1;
+        let mut start = 0;
+        for (index, change) in self.meters.iter().enumerate() {
+            let bar_ticks = self.bar_ticks(change.meter);
+
+            // Skip to the next change if `ticks` is after it
+            if let Some(next) = self.meters.get(index + 1) {
+                let end = start + (next.bar - change.bar) as u64 * bar_ticks;
+                if ticks.0 >= end {
+                    bar = next.bar;
+                    start = end;
+                    continue;
+                }
+            }
+
+            let offset = ticks.0 - start;
+            let beat_ticks = self.beat_ticks(change.meter);
+            let in_bar = offset % bar_ticks;
+            return Position {
+                bar: change.bar + (offset / bar_ticks) as u32,
+                beat: (in_bar / beat_ticks) as u32 + 1,
+                tick: (in_bar % beat_ticks) as u32,
+            };
+        }
+
+        Position::new(bar, 1, 0)
+    }
+
+    /// Returns the ticks at the bar, beat and tick of `position`.
+    ///
+    /// Beats and ticks past the end of a bar continue into the next bars.
+    pub fn ticks(&self, position: Position) -> Ticks {
+        let mut start = 0;
+        for (index, change) in self.meters.iter().enumerate() {
+            let bar_ticks = self.bar_ticks(change.meter);
+
+            match self.meters.get(index + 1) {
+                Some(next) if position.bar >= next.bar => {
+                    start += (next.bar - change.bar) as u64 * bar_ticks;
+                }
+                _ => {
+                    let bars = position.bar.saturating_sub(change.bar) as u64;
+                    let beats = position.beat.saturating_sub(1) as u64;
+                    return Ticks(
+                        start
+                            + bars * bar_ticks
+                            + beats * self.beat_ticks(change.meter)
+                            + position.tick as u64,
+                    );
+                }
+            }
+        }
+
+        Ticks(start)
+    }
+
+    /// Returns the position `ticks` after `position`, across any meter changes.
+    /// ```
+    /// use staff::time::tempo::{Position, TempoMap, Ticks};
+    /// use staff::time::{DurationKind, TimeSignature};
+    ///
+    /// let mut map = TempoMap::new(480, 120.);
+    /// map.set_meter(2, TimeSignature::new(DurationKind::Eigth, 6));
+    ///
+    /// // Two beats after the last beat of bar 1 is the second eighth of bar 2
+    /// let position = map.offset(Position::new(1, 4, 0), Ticks(480 + 240));
+    /// assert_eq!(position, Position::new(2, 2, 0));
+    /// ```
+    pub fn offset(&self, position: Position, ticks: Ticks) -> Position {
+        self.position(self.ticks(position) + ticks)
+    }
+
+    /// Returns the ticks from `from` to `to`, which are negative if `to` is before `from`.
+    /// ```
+    /// use staff::time::tempo::{Position, TempoMap};
+    /// use staff::time::{DurationKind, TimeSignature};
+    ///
+    /// let mut map = TempoMap::new(480, 120.);
+    /// map.set_meter(2, TimeSignature::new(DurationKind::Quarter, 3));
+    ///
+    /// let (from, to) = (Position::new(1, 3, 0), Position::new(3, 1, 0));
+    /// assert_eq!(map.ticks_between(from, to), 480 * 5);
+    /// assert_eq!(map.ticks_between(to, from), -480 * 5);
+    /// ```
+    pub fn ticks_between(&self, from: Position, to: Position) -> i64 {
+        self.ticks(to).0 as i64 - self.ticks(from).0 as i64
+    }
+
+    fn tempo_index(&self, ticks: Ticks) -> usize {
+        self.tempos
+            .partition_point(|change| change.at <= ticks)
+            .saturating_sub(1)
+    }
+
+    /// Returns the seconds from the change at `index` until `end`.
+    fn segment_seconds(&self, index: usize, end: Ticks) -> f64 {
+        let change = self.tempos[index];
+        let ticks = (end.0 - change.at.0) as f64;
+        let end_bpm = self.tempo(end).max(f64::MIN_POSITIVE);
+
+        if change.ramp == Ramp::Linear && end_bpm != change.bpm {
+            // Integrate 60 / (ppq * bpm) over a tempo that changes linearly in ticks
+            60. * ticks / (self.ppq as f64 * (end_bpm - change.bpm)) * (end_bpm / change.bpm).ln()
+        } else {
+            ticks * 60. / (self.ppq as f64 * change.bpm)
+        }
+    }
+
+    /// Returns the ticks from the change at `index` after `seconds`.
+    fn segment_ticks(&self, index: usize, seconds: f64) -> f64 {
+        let change = self.tempos[index];
+        let ppq = self.ppq as f64;
+
+        match (change.ramp, self.tempos.get(index + 1)) {
+            (Ramp::Linear, Some(next)) if next.bpm != change.bpm => {
+                let length = (next.at.0 - change.at.0) as f64;
+                let slope = (next.bpm - change.bpm) / length;
+                let bpm = change.bpm * (seconds * ppq * slope / 60.).exp();
+                (bpm - change.bpm) / slope
+            }
+            _ => seconds * ppq * change.bpm / 60.,
+        }
+    }
+
+    fn bar_ticks(&self, meter: TimeSignature) -> u64 {
+        Ticks::from_fraction(meter.measure(), self.ppq).0.max(1)
+    }
+
+    fn beat_ticks(&self, meter: TimeSignature) -> u64 {
+        Ticks::from_fraction(meter.unit.length(), self.ppq).0.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::DurationKind;
+
+    #[test]
+    fn it_converts_positions_across_meter_changes() {
+        let mut map = TempoMap::new(96, 100.);
+        map.set_meter(2, TimeSignature::new(DurationKind::Quarter, 3));
+        map.set_meter(4, "7/8".parse().unwrap());
+
+        for ticks in (0..96 * 40).step_by(7) {
+            let position = map.position(Ticks(ticks));
+            assert_eq!(map.ticks(position), Ticks(ticks));
+        }
+        assert_eq!(map.ticks(Position::new(4, 1, 0)), Ticks(96 * (4 + 3 + 3)));
+    }
+
+    #[test]
+    fn it_offsets_positions_across_meter_changes() {
+        let mut map = TempoMap::new(96, 100.);
+        map.set_meter(2, TimeSignature::new(DurationKind::Quarter, 3));
+        map.set_meter(4, "7/8".parse().unwrap());
+
+        let start = Position::new(1, 2, 10);
+        for ticks in (0..96 * 40).step_by(11) {
+            let position = map.offset(start, Ticks(ticks));
+            assert_eq!(map.ticks_between(start, position), ticks as i64);
+        }
+        assert_eq!(
+            map.offset(Position::new(3, 3, 0), Ticks(96 + 48)),
+            Position::new(4, 2, 0)
+        );
+    }
+
+    #[test]
+    fn it_converts_seconds_across_tempo_changes() {
+        let mut map = TempoMap::new(480, 120.);
+        map.set_tempo(Ticks(960), 60.);
+        map.ramp_tempo(Ticks(1920), Ticks(3840), 180.);
+
+        assert_eq!(map.seconds(Ticks(960)), 1.);
+        assert_eq!(map.seconds(Ticks(1920)), 3.);
+        for ticks in (0..6000).step_by(113) {
+            let seconds = map.seconds(Ticks(ticks));
+            assert_eq!(map.ticks_at(seconds), Ticks(ticks));
+        }
+        assert_eq!(
+            map.ticks_at_sample(map.sample(Ticks(4800), 48000), 48000),
+            Ticks(4800)
+        );
+    }
+}
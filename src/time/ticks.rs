@@ -0,0 +1,77 @@
+use super::Fraction;
+use crate::midi::MidiNote;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// An absolute musical time in ticks from the start,
+/// at a resolution of a number of ticks per quarter note (PPQ).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ticks(pub u64);
+
+impl Ticks {
+    /// Convert a length in whole notes to ticks with `ppq` ticks per quarter note,
+    /// rounding down to the nearest tick.
+    /// ```
+    /// use staff::time::tempo::Ticks;
+    /// use staff::time::Fraction;
+    ///
+    /// assert_eq!(Ticks::from_fraction(Fraction::new(3, 8), 480), Ticks(720));
+    /// ```
+    pub fn from_fraction(length: Fraction, ppq: u16) -> Self {
+        Self((length * (ppq as u64 * 4)).floor())
+    }
+
+    /// Returns this length in whole notes with `ppq` ticks per quarter note.
+    pub fn to_fraction(self, ppq: u16) -> Fraction {
+        Fraction::new(self.0, ppq as u64 * 4)
+    }
+}
+
+impl Add for Ticks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Ticks {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Ticks {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Ticks {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+/// A note as it was played, with its onset and length in ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformedNote {
+    pub midi: MidiNote,
+    pub onset: Ticks,
+    pub length: Ticks,
+    pub velocity: u8,
+}
+
+impl PerformedNote {
+    pub fn new(midi: MidiNote, onset: Ticks, length: Ticks, velocity: u8) -> Self {
+        Self {
+            midi,
+            onset,
+            length,
+            velocity,
+        }
+    }
+}
@@ -0,0 +1,514 @@
+//! Tuning systems for calculating frequencies
+//!
+//! ```
+//! use staff::tuning::{ChainOfFifths, EqualTemperament, Tuning};
+//! use staff::midi::Octave;
+//! use staff::{midi, Natural, Note};
+//!
+//! let tuning = EqualTemperament::with_a4(415.);
+//! assert_eq!(tuning.frequency(midi!(A, 3)), 207.5);
+//!
+//! // G# and Ab differ in meantone
+//! let meantone = ChainOfFifths::quarter_comma_meantone();
+//! let g_sharp = meantone.note_frequency(Note::sharp(Natural::G), Octave::FOUR);
+//! let a_flat = meantone.note_frequency(Note::flat(Natural::A), Octave::FOUR);
+//! assert!(a_flat > g_sharp);
+//! ```
+
+pub mod scala;
+
+use crate::{
+    midi::{MidiNote, Octave},
+    note::Note,
+    Pitch,
+};
+
+/// A tuning system that calculates the frequency of notes.
+pub trait Tuning {
+    /// Returns the frequency of `midi` in Hz.
+    fn frequency(&self, midi: MidiNote) -> f64;
+
+    /// Returns the frequency of a spelled `note` in `octave` in Hz.
+    ///
+    /// By default this is the frequency of the `MidiNote` with the same pitch,
+    /// so enharmonic notes are tuned the same.
+    fn note_frequency(&self, note: Note, octave: Octave) -> f64 {
+        self.frequency(MidiNote::from_note(note, octave))
+    }
+
+    /// Returns the `MidiNote` closest to `frequency` in Hz and the deviation from it in cents,
+    /// or `None` if `frequency` isn't positive.
+    ///
+    /// Frequencies outside of the MIDI range are matched to the lowest or highest note.
+    fn nearest(&self, frequency: f64) -> Option<Deviation> {
+        if frequency.is_nan() || frequency <= 0. {
+            return None;
+        }
+
+        (0..=127)
+            .map(|byte| {
+                let note = MidiNote::from_byte(byte);
+                Deviation {
+                    note,
+                    cents: cents(self.frequency(note), frequency),
+                }
+            })
+            .min_by(|a, b| a.cents.abs().total_cmp(&b.cents.abs()))
+    }
+
+    /// Returns the index of the note in `notes` closest to `frequency` in Hz and the deviation
+    /// from it in cents, such as the closest string of a guitar.
+    /// ```
+    /// use staff::tuning::{EqualTemperament, Tuning};
+    /// use staff::{midi, midi::MidiNote};
+    ///
+    /// let strings = [midi!(E, 2), midi!(A, 2), midi!(D, 3), midi!(G, 3), midi!(B, 3), midi!(E, 4)];
+    /// let (idx, deviation) = EqualTemperament::default().closest(strings, 112.).unwrap();
+    ///
+    /// assert_eq!(idx, 1);
+    /// assert_eq!(deviation.note, midi!(A, 2));
+    /// assert!((deviation.cents - 31.).abs() < 0.5);
+    /// ```
+    fn closest<I>(&self, notes: I, frequency: f64) -> Option<(usize, Deviation)>
+    where
+        I: IntoIterator<Item = MidiNote>,
+        Self: Sized,
+    {
+        if frequency.is_nan() || frequency <= 0. {
+            return None;
+        }
+
+        notes
+            .into_iter()
+            .map(|note| Deviation {
+                note,
+                cents: cents(self.frequency(note), frequency),
+            })
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.cents.abs().total_cmp(&b.cents.abs()))
+    }
+}
+
+/// The distance of a frequency from a note in cents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Deviation {
+    pub note: MidiNote,
+    /// The signed distance from `note` in cents, positive if the frequency is sharp.
+    pub cents: f64,
+}
+
+impl<T: Tuning + ?Sized> Tuning for &T {
+    fn frequency(&self, midi: MidiNote) -> f64 {
+        (**self).frequency(midi)
+    }
+
+    fn note_frequency(&self, note: Note, octave: Octave) -> f64 {
+        (**self).note_frequency(note, octave)
+    }
+
+    fn nearest(&self, frequency: f64) -> Option<Deviation> {
+        (**self).nearest(frequency)
+    }
+}
+
+/// Twelve tone equal temperament tuned to a reference note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EqualTemperament {
+    pub reference: MidiNote,
+    /// The frequency of the reference note in Hz.
+    pub frequency: f64,
+}
+
+impl EqualTemperament {
+    pub const fn new(reference: MidiNote, frequency: f64) -> Self {
+        Self {
+            reference,
+            frequency,
+        }
+    }
+
+    /// Create a new `EqualTemperament` with A4 tuned to `frequency`.
+    pub const fn with_a4(frequency: f64) -> Self {
+        Self::new(MidiNote::new(Pitch::A, Octave::FOUR), frequency)
+    }
+}
+
+impl Default for EqualTemperament {
+    /// A4 tuned to 440 Hz.
+    fn default() -> Self {
+        Self::with_a4(440.)
+    }
+}
+
+impl Tuning for EqualTemperament {
+    fn frequency(&self, midi: MidiNote) -> f64 {
+        let semitones = midi.into_byte() as f64 - self.reference.into_byte() as f64;
+        self.frequency * cents_to_ratio(semitones * 100.)
+    }
+
+    /// ```
+    /// use staff::tuning::{EqualTemperament, Tuning};
+    /// use staff::midi;
+    ///
+    /// let deviation = EqualTemperament::with_a4(442.).nearest(440.).unwrap();
+    ///
+    /// assert_eq!(deviation.note, midi!(A, 4));
+    /// assert!((deviation.cents + 7.85).abs() < 0.01);
+    /// ```
+    fn nearest(&self, frequency: f64) -> Option<Deviation> {
+        if frequency.is_nan() || frequency <= 0. {
+            return None;
+        }
+
+        let semitones = cents(self.frequency, frequency) / 100.;
+        let byte = (self.reference.into_byte() as f64 + semitones)
+            .round()
+            .clamp(0., 127.);
+        let note = MidiNote::from_byte(byte as u8);
+
+        Some(Deviation {
+            note,
+            cents: cents(self.frequency(note), frequency),
+        })
+    }
+}
+
+/// Tuning with a fixed size in cents for each pitch class, such as a well temperament.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CentTable {
+    /// The cents of each pitch class above C.
+    pub cents: [f64; 12],
+    pub reference: MidiNote,
+    /// The frequency of the reference note in Hz.
+    pub frequency: f64,
+}
+
+impl CentTable {
+    /// Create a new `CentTable` with A4 tuned to 440 Hz.
+    pub const fn new(cents: [f64; 12]) -> Self {
+        Self {
+            cents,
+            reference: MidiNote::new(Pitch::A, Octave::FOUR),
+            frequency: 440.,
+        }
+    }
+
+    /// Werckmeister III well temperament.
+    pub const fn werckmeister_iii() -> Self {
+        Self::new([
+            0., 90.225, 192.18, 294.135, 390.225, 498.045, 588.27, 696.09, 792.18, 888.27, 996.09,
+            1092.18,
+        ])
+    }
+
+    /// Kirnberger III well temperament.
+    pub const fn kirnberger_iii() -> Self {
+        Self::new([
+            0., 90.225, 193.157, 294.135, 386.314, 498.045, 590.224, 696.578, 792.18, 889.735,
+            996.09, 1088.269,
+        ])
+    }
+
+    /// Tune `reference` to `frequency`.
+    pub const fn with_reference(mut self, reference: MidiNote, frequency: f64) -> Self {
+        self.reference = reference;
+        self.frequency = frequency;
+        self
+    }
+
+    fn cents(&self, midi: MidiNote) -> f64 {
+        (midi.octave().into_i8() as f64 + 1.) * 1200.
+            + self.cents[midi.pitch().into_byte() as usize]
+    }
+}
+
+impl Tuning for CentTable {
+    fn frequency(&self, midi: MidiNote) -> f64 {
+        self.frequency * cents_to_ratio(self.cents(midi) - self.cents(self.reference))
+    }
+}
+
+/// Tuning built from a chain of fifths of the same size, such as Pythagorean
+/// tuning or meantone.
+///
+/// Spelled notes are tuned by their position on the chain, so sharps and flats differ.
+/// MIDI notes are spelled between the flattest note of the chain and eleven fifths above it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainOfFifths {
+    /// The size of each fifth in cents.
+    pub fifth: f64,
+    /// The number of fifths from C to the flattest note used for MIDI notes,
+    /// clamped so the twelve notes from it can be spelled with at most two accidentals.
+    pub flattest: i8,
+    /// The frequency of A4 in Hz.
+    pub frequency: f64,
+}
+
+impl ChainOfFifths {
+    /// The range of `flattest` whose twelve notes can all be spelled.
+    const FLATTEST: (i8, i8) = (-15, 8);
+
+    /// Create a new `ChainOfFifths` from E♭ to G♯ with A4 tuned to 440 Hz.
+    pub const fn new(fifth: f64) -> Self {
+        Self {
+            fifth,
+            flattest: -3,
+            frequency: 440.,
+        }
+    }
+
+    /// Pythagorean tuning with pure fifths (3/2).
+    pub fn pythagorean() -> Self {
+        Self::new(ratio_to_cents(3. / 2.))
+    }
+
+    /// Quarter-comma meantone with pure major thirds (5/4).
+    pub fn quarter_comma_meantone() -> Self {
+        Self::new(ratio_to_cents(5.) / 4.)
+    }
+
+    /// Spell MIDI notes from `note` up to eleven fifths above it.
+    ///
+    /// Notes too sharp to spell eleven fifths above with at most a double sharp
+    /// are clamped to G♯, so every MIDI note keeps its own spelling.
+    /// ```
+    /// use staff::tuning::{ChainOfFifths, Tuning};
+    /// use staff::midi::Octave;
+    /// use staff::{midi, Natural, Note};
+    ///
+    /// let tuning = ChainOfFifths::pythagorean().with_flattest(Note::flat(Natural::A));
+    ///
+    /// let a_flat = tuning.note_frequency(Note::flat(Natural::A), Octave::FOUR);
+    /// assert_eq!(tuning.frequency(midi!(GSharp, 4)), a_flat);
+    /// ```
+    pub fn with_flattest(mut self, note: Note) -> Self {
+        self.flattest = note.fifths().clamp(Self::FLATTEST.0, Self::FLATTEST.1);
+        self
+    }
+
+    /// Tune A4 to `frequency`.
+    pub const fn with_a4(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Returns the cents of `note` in `octave` above C-1.
+    fn cents(&self, note: Note, octave: Octave) -> f64 {
+        let letter = Note::from(note.natural).fifths();
+        let natural = letter as f64 * self.fifth;
+        let natural = natural - (natural / 1200.).floor() * 1200.;
+
+        // A chromatic semitone is seven fifths up and four octaves down
+        let chromatic = 7. * self.fifth - 4800.;
+        (octave.into_i8() as f64 + 1.) * 1200.
+            + natural
+            + note.accidental.semitones() as f64 * chromatic
+    }
+}
+
+impl Tuning for ChainOfFifths {
+    fn frequency(&self, midi: MidiNote) -> f64 {
+        let pitch = midi.pitch();
+        let flattest = self.flattest.clamp(Self::FLATTEST.0, Self::FLATTEST.1);
+        let note = (flattest..flattest + 12)
+            .filter_map(Note::from_fifths)
+            .find(|note| Pitch::from(*note) == pitch)
+            .expect("twelve fifths spell every pitch");
+
+        // Keep the octave of the MIDI note for notes like B# and Cb
+        let natural = MidiNote::from_note(Note::from(note.natural), Octave::FOUR);
+        let offset = natural.into_byte() as i16 + note.accidental.semitones() as i16
+            - MidiNote::new(Pitch::C, Octave::FOUR).into_byte() as i16;
+        let octave = (midi.into_byte() as i16 - offset).div_euclid(12) - 1;
+
+        self.note_frequency(note, Octave::new_unchecked(octave as i8))
+    }
+
+    fn note_frequency(&self, note: Note, octave: Octave) -> f64 {
+        let a4 = self.cents(Note::A, Octave::FOUR);
+        self.frequency * cents_to_ratio(self.cents(note, octave) - a4)
+    }
+}
+
+/// Five-limit just intonation relative to a tonic.
+///
+/// The tonic is tuned to equal temperament with A4 at the reference frequency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JustIntonation {
+    pub tonic: Pitch,
+    /// The frequency of A4 in Hz.
+    pub frequency: f64,
+}
+
+impl JustIntonation {
+    /// The ratios of each semitone above the tonic.
+    pub const RATIOS: [(u8, u8); 12] = [
+        (1, 1),
+        (16, 15),
+        (9, 8),
+        (6, 5),
+        (5, 4),
+        (4, 3),
+        (45, 32),
+        (3, 2),
+        (8, 5),
+        (5, 3),
+        (9, 5),
+        (15, 8),
+    ];
+
+    /// Create a new `JustIntonation` with A4 at 440 Hz.
+    pub const fn new(tonic: Pitch) -> Self {
+        Self {
+            tonic,
+            frequency: 440.,
+        }
+    }
+}
+
+impl Tuning for JustIntonation {
+    /// ```
+    /// use staff::tuning::{JustIntonation, Tuning};
+    /// use staff::{midi, Pitch};
+    ///
+    /// let tuning = JustIntonation::new(Pitch::C);
+    /// let ratio = tuning.frequency(midi!(E, 4)) / tuning.frequency(midi!(C, 4));
+    ///
+    /// assert!((ratio - 1.25).abs() < 1e-9);
+    /// ```
+    fn frequency(&self, midi: MidiNote) -> f64 {
+        let byte = midi.into_byte() as i16;
+        let above = (byte - self.tonic.into_byte() as i16).rem_euclid(12);
+
+        // The tonic below can be lower than MIDI note 0, so it's found in semitones from A4
+        let tonic = byte - above - 69;
+        let tonic = self.frequency * cents_to_ratio(tonic as f64 * 100.);
+
+        let (numerator, denominator) = Self::RATIOS[above as usize];
+        tonic * numerator as f64 / denominator as f64
+    }
+}
+
+/// Convert a frequency ratio to cents.
+/// ```
+/// use staff::tuning::ratio_to_cents;
+///
+/// assert_eq!(ratio_to_cents(2.), 1200.);
+/// ```
+pub fn ratio_to_cents(ratio: f64) -> f64 {
+    1200. * ratio.log2()
+}
+
+/// Returns the distance from the frequency `from` to `to` in cents.
+/// ```
+/// use staff::tuning::cents;
+///
+/// assert_eq!(cents(440., 880.), 1200.);
+/// ```
+pub fn cents(from: f64, to: f64) -> f64 {
+    ratio_to_cents(to / from)
+}
+
+/// Convert cents to a frequency ratio.
+/// ```
+/// use staff::tuning::cents_to_ratio;
+///
+/// assert_eq!(cents_to_ratio(-1200.), 0.5);
+/// ```
+pub fn cents_to_ratio(cents: f64) -> f64 {
+    2f64.powf(cents / 1200.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Natural;
+
+    #[test]
+    fn it_tunes_pure_fifths() {
+        let tuning = ChainOfFifths::pythagorean();
+        let c = tuning.note_frequency(Note::C, Octave::FOUR);
+        let g = tuning.note_frequency(Note::G, Octave::FOUR);
+
+        assert!((g / c - 1.5).abs() < 1e-9);
+        assert_eq!(
+            tuning.frequency(MidiNote::new(Pitch::A, Octave::FOUR)),
+            440.
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_octave_of_midi_notes() {
+        let tuning = ChainOfFifths::quarter_comma_meantone().with_flattest(Note::flat(Natural::C));
+        let b = tuning.frequency(MidiNote::new(Pitch::B, Octave::THREE));
+        let c = tuning.frequency(MidiNote::new(Pitch::C, Octave::FOUR));
+
+        assert!(b < c);
+        assert!(c / b < 1.1);
+    }
+
+    #[test]
+    fn it_spells_every_pitch_from_any_flattest_note() {
+        let pythagorean = ChainOfFifths::pythagorean();
+        for flattest in [
+            Note::double_sharp(Natural::F),
+            Note::double_flat(Natural::F),
+        ] {
+            let tuning = pythagorean.with_flattest(flattest);
+            let frequencies: Vec<_> = (60..72)
+                .map(|byte| tuning.frequency(MidiNote::from_byte(byte)))
+                .collect();
+            assert!(frequencies.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        // Too sharp to spell eleven fifths above it, so G# is the flattest note instead
+        let tuning = pythagorean.with_flattest(Note::double_sharp(Natural::F));
+        let g_sharp = MidiNote::new(Pitch::GSharp, Octave::FOUR);
+        assert_eq!(
+            tuning.frequency(g_sharp),
+            tuning.note_frequency(Note::sharp(Natural::G), Octave::FOUR)
+        );
+    }
+
+    #[test]
+    fn it_finds_the_nearest_note_in_any_tuning() {
+        let tuning = CentTable::werckmeister_iii();
+        let e = MidiNote::new(Pitch::E, Octave::FOUR);
+
+        let deviation = tuning.nearest(tuning.frequency(e) * 1.001).unwrap();
+        assert_eq!(deviation.note, e);
+        assert!((deviation.cents - ratio_to_cents(1.001)).abs() < 1e-9);
+
+        let equal = EqualTemperament::default();
+        assert_eq!(equal.nearest(1.).unwrap().note, MidiNote::from_byte(0));
+        assert_eq!(equal.nearest(0.), None);
+    }
+
+    #[test]
+    fn it_tunes_just_notes_below_the_lowest_tonic() {
+        let tuning = JustIntonation::new(Pitch::D);
+        let low = tuning.frequency(MidiNote::from_byte(0));
+        let high = tuning.frequency(MidiNote::from_byte(12));
+
+        // A just minor seventh above the D below, near the equal tempered 8.18 Hz
+        assert!((low - 8.26).abs() < 0.01);
+        assert!((high / low - 2.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_matches_equal_temperament_with_an_equal_table() {
+        let cents = core::array::from_fn(|idx| idx as f64 * 100.);
+        let table = CentTable::new(cents);
+
+        for byte in 0..=127 {
+            let midi = MidiNote::from_byte(byte);
+            let expected = EqualTemperament::default().frequency(midi);
+            assert!((table.frequency(midi) - expected).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//! Scala scale (`.scl`) and keyboard mapping (`.kbm`) files
+//!
+//! ```
+//! use staff::tuning::scala::{Kbm, Scl, TuningTable};
+//! use staff::tuning::Tuning;
+//! use staff::midi;
+//!
+//! let scl: Scl = "! pentatonic.scl
+//! !
+//! Just pentatonic
+//!  5
+//! !
+//!  9/8
+//!  5/4
+//!  3/2
+//!  5/3
+//!  2/1
+//! "
+//! .parse()
+//! .unwrap();
+//!
+//! let table = TuningTable::new(&scl, &Kbm::default()).unwrap();
+//! assert_eq!(table.frequency(midi!(A, 4)), 440.);
+//!
+//! // Keys map linearly from C4 at 1/1, so A4 is the 5/3 of the next octave
+//! // and A#4 starts the octave after that.
+//! let ratio = table.frequency(midi!(ASharp, 4)) / 440.;
+//! assert!((ratio - 6. / 5.).abs() < 1e-9);
+//! ```
+
+use super::{cents_to_ratio, ratio_to_cents, Tuning};
+use crate::midi::MidiNote;
+use std::{fmt, str::FromStr};
+
+/// A pitch of a Scala scale file relative to its first degree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SclPitch {
+    Cents(f64),
+    Ratio(u64, u64),
+}
+
+impl SclPitch {
+    /// Returns the size of this pitch in cents.
+    pub fn cents(self) -> f64 {
+        match self {
+            Self::Cents(cents) => cents,
+            Self::Ratio(numerator, denominator) => {
+                ratio_to_cents(numerator as f64 / denominator as f64)
+            }
+        }
+    }
+}
+
+impl FromStr for SclPitch {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Anything after the value is a comment
+        let value = s.split_whitespace().next().ok_or(())?;
+
+        if value.contains('.') {
+            value.parse().map(Self::Cents).map_err(|_| ())
+        } else {
+            let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+            let numerator = numerator.parse().map_err(|_| ())?;
+            let denominator = denominator.parse().map_err(|_| ())?;
+            if numerator == 0 || denominator == 0 {
+                return Err(());
+            }
+            Ok(Self::Ratio(numerator, denominator))
+        }
+    }
+}
+
+impl fmt::Display for SclPitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Cents need a decimal point, which Display leaves out of whole numbers
+            Self::Cents(cents) if cents.fract() == 0. => write!(f, "{}.0", cents),
+            Self::Cents(cents) => write!(f, "{}", cents),
+            Self::Ratio(numerator, denominator) => write!(f, "{}/{}", numerator, denominator),
+        }
+    }
+}
+
+/// A Scala scale file.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scl {
+    pub description: String,
+    /// The pitches of each degree after the first, ending with the period of the scale.
+    pub pitches: Vec<SclPitch>,
+}
+
+impl Scl {
+    /// Returns the number of degrees in this scale.
+    pub fn len(&self) -> usize {
+        self.pitches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pitches.is_empty()
+    }
+
+    /// Returns the cents of `degree` above the first degree,
+    /// repeating the scale every period.
+    pub fn cents(&self, degree: i32) -> f64 {
+        if self.pitches.is_empty() {
+            return 0.;
+        }
+
+        let len = self.pitches.len() as i32;
+        let period = self.pitches[self.pitches.len() - 1].cents();
+        let cents = match degree.rem_euclid(len) {
+            0 => 0.,
+            idx => self.pitches[idx as usize - 1].cents(),
+        };
+        degree.div_euclid(len) as f64 * period + cents
+    }
+}
+
+impl FromStr for Scl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = lines(s);
+
+        // The description is the first line that isn't a comment, even if it's empty
+        let (_, description) = lines.next().ok_or(Error::MissingLine("description"))?;
+        let mut lines = lines.filter(|(_, line)| !line.is_empty());
+        let (line, count) = lines.next().ok_or(Error::MissingLine("note count"))?;
+        let count: usize = parse_first(count).ok_or(Error::InvalidNumber { line })?;
+
+        let pitches = lines
+            .by_ref()
+            .take(count)
+            .map(|(line, pitch)| pitch.parse().map_err(|_| Error::InvalidPitch { line }))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if pitches.len() != count {
+            return Err(Error::CountMismatch {
+                expected: count,
+                found: pitches.len(),
+            });
+        }
+
+        Ok(Self {
+            description: description.to_owned(),
+            pitches,
+        })
+    }
+}
+
+impl fmt::Display for Scl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "!")?;
+        writeln!(f, "{}", self.description)?;
+        writeln!(f, " {}", self.pitches.len())?;
+        writeln!(f, "!")?;
+        for pitch in &self.pitches {
+            writeln!(f, " {}", pitch)?;
+        }
+        Ok(())
+    }
+}
+
+/// A Scala keyboard mapping file.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kbm {
+    /// The number of keys before the mapping repeats, or zero for a linear mapping.
+    pub size: usize,
+    pub first: MidiNote,
+    pub last: MidiNote,
+    /// The key mapped to the first degree of the scale.
+    pub middle: MidiNote,
+    pub reference: MidiNote,
+    /// The frequency of the reference key in Hz.
+    pub frequency: f64,
+    /// The degree of the scale used as the formal octave, or zero for the last degree.
+    pub octave_degree: usize,
+    /// The degree of each key in the pattern, or `None` for unmapped keys.
+    pub mapping: Vec<Option<usize>>,
+}
+
+impl Default for Kbm {
+    /// A linear mapping of every key from C4 with A4 tuned to 440 Hz.
+    fn default() -> Self {
+        Self {
+            size: 0,
+            first: MidiNote::from_byte(0),
+            last: MidiNote::from_byte(127),
+            middle: MidiNote::from_byte(60),
+            reference: MidiNote::from_byte(69),
+            frequency: 440.,
+            octave_degree: 0,
+            mapping: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Kbm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = lines(s).filter(|(_, line)| !line.is_empty());
+        let mut next = |name| {
+            let (line, value) = lines.next().ok_or(Error::MissingLine(name))?;
+            Ok::<_, Error>((line, value))
+        };
+
+        let number = |(line, value): (usize, &str)| -> Result<usize, Error> {
+            parse_first(value).ok_or(Error::InvalidNumber { line })
+        };
+        let note = |(line, value): (usize, &str)| -> Result<MidiNote, Error> {
+            parse_first(value)
+                .filter(|byte: &u8| *byte <= 127)
+                .map(MidiNote::from_byte)
+                .ok_or(Error::InvalidNumber { line })
+        };
+
+        let size = number(next("map size")?)?;
+        let first = note(next("first note")?)?;
+        let last = note(next("last note")?)?;
+        let middle = note(next("middle note")?)?;
+        let reference = note(next("reference note")?)?;
+        let (line, frequency) = next("reference frequency")?;
+        let frequency = parse_first(frequency)
+            .filter(|frequency: &f64| *frequency > 0.)
+            .ok_or(Error::InvalidNumber { line })?;
+        let octave_degree = number(next("octave degree")?)?;
+
+        // The size isn't trusted to allocate, so the mapping grows with the lines read
+        let mapping = lines
+            .take(size)
+            .map(|(line, degree)| match degree.split_whitespace().next() {
+                Some("x") => Ok(None),
+                _ => parse_first(degree)
+                    .map(Some)
+                    .ok_or(Error::InvalidNumber { line }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if mapping.len() != size {
+            return Err(Error::CountMismatch {
+                expected: size,
+                found: mapping.len(),
+            });
+        }
+
+        Ok(Self {
+            size,
+            first,
+            last,
+            middle,
+            reference,
+            frequency,
+            octave_degree,
+            mapping,
+        })
+    }
+}
+
+impl fmt::Display for Kbm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "! Size of map:")?;
+        writeln!(f, "{}", self.size)?;
+        writeln!(f, "! First MIDI note number to retune:")?;
+        writeln!(f, "{}", self.first.into_byte())?;
+        writeln!(f, "! Last MIDI note number to retune:")?;
+        writeln!(f, "{}", self.last.into_byte())?;
+        writeln!(
+            f,
+            "! Middle note where the first entry of the mapping is mapped to:"
+        )?;
+        writeln!(f, "{}", self.middle.into_byte())?;
+        writeln!(f, "! Reference note for which frequency is given:")?;
+        writeln!(f, "{}", self.reference.into_byte())?;
+        writeln!(f, "! Frequency to tune the above note to:")?;
+        writeln!(f, "{:?}", self.frequency)?;
+        writeln!(f, "! Scale degree to consider as formal octave:")?;
+        writeln!(f, "{}", self.octave_degree)?;
+        writeln!(f, "! Mapping:")?;
+        for degree in &self.mapping {
+            match degree {
+                Some(degree) => writeln!(f, "{}", degree)?,
+                None => writeln!(f, "x")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The frequency of every `MidiNote` from a Scala scale and keyboard mapping.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuningTable {
+    frequencies: [Option<f64>; 128],
+}
+
+impl TuningTable {
+    /// Create a new `TuningTable` by mapping the keys of `kbm` to the degrees of `scl`.
+    pub fn new(scl: &Scl, kbm: &Kbm) -> Result<Self, Error> {
+        if scl.is_empty() {
+            return Err(Error::EmptyScale);
+        }
+
+        let octave_degree = if kbm.octave_degree == 0 {
+            scl.len()
+        } else {
+            kbm.octave_degree
+        };
+        let period = scl.cents(octave_degree as i32);
+
+        let cents = |midi: MidiNote| {
+            let offset = midi.into_byte() as i32 - kbm.middle.into_byte() as i32;
+            if kbm.size == 0 {
+                return Some(scl.cents(offset));
+            }
+
+            // Keys past the end of a hand-built mapping are unmapped
+            let size = kbm.size as i64;
+            let offset = offset as i64;
+            let degree = kbm
+                .mapping
+                .get(offset.rem_euclid(size) as usize)
+                .copied()
+                .flatten()?;
+            Some(offset.div_euclid(size) as f64 * period + scl.cents(degree as i32))
+        };
+
+        let reference = cents(kbm.reference).ok_or(Error::UnmappedReference)?;
+        let mut frequencies = [None; 128];
+        for byte in kbm.first.into_byte()..=kbm.last.into_byte() {
+            frequencies[byte as usize] = cents(MidiNote::from_byte(byte))
+                .map(|cents| kbm.frequency * cents_to_ratio(cents - reference));
+        }
+
+        Ok(Self { frequencies })
+    }
+
+    /// Returns the frequency of `midi` in Hz, or `None` if it isn't mapped.
+    pub fn get(&self, midi: MidiNote) -> Option<f64> {
+        self.frequencies
+            .get(midi.into_byte() as usize)
+            .copied()
+            .flatten()
+    }
+}
+
+impl Tuning for TuningTable {
+    /// Returns the frequency of `midi` in Hz, or `NaN` if it isn't mapped.
+    fn frequency(&self, midi: MidiNote) -> f64 {
+        self.get(midi).unwrap_or(f64::NAN)
+    }
+}
+
+/// Error parsing a Scala file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The file ended before the named line.
+    MissingLine(&'static str),
+    InvalidNumber {
+        line: usize,
+    },
+    InvalidPitch {
+        line: usize,
+    },
+    CountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    EmptyScale,
+    UnmappedReference,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingLine(name) => write!(f, "Missing {} line", name),
+            Self::InvalidNumber { line } => write!(f, "Invalid number on line {}", line),
+            Self::InvalidPitch { line } => write!(
+                f,
+                "Invalid pitch on line {}, expected cents or a ratio",
+                line
+            ),
+            Self::CountMismatch { expected, found } => {
+                write!(f, "Expected {} pitches but found {}", expected, found)
+            }
+            Self::EmptyScale => f.write_str("Scale has no pitches"),
+            Self::UnmappedReference => f.write_str("Reference note is not mapped"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Returns the one-based line numbers and trimmed contents of each line that isn't a comment.
+fn lines(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.starts_with('!'))
+}
+
+fn parse_first<T: FromStr>(s: &str) -> Option<T> {
+    s.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEANTONE: &str = "! meanquar.scl
+!
+1/4-comma meantone scale. Pietro Aaron's temperament (1523)
+ 12
+!
+ 76.04900
+ 193.15686
+ 310.26471
+ 5/4
+ 503.42157
+ 579.47057
+ 696.57843
+ 25/16
+ 889.73529
+ 1006.84314
+ 1082.89214
+ 2/1
+";
+
+    #[test]
+    fn it_round_trips_scl_files() {
+        let scl: Scl = MEANTONE.parse().unwrap();
+        assert_eq!(scl.len(), 12);
+        assert_eq!(scl.pitches[3], SclPitch::Ratio(5, 4));
+
+        assert_eq!(scl.to_string().parse::<Scl>().unwrap(), scl);
+
+        let scl = Scl {
+            description: String::from("Tiny and huge cents"),
+            pitches: vec![
+                SclPitch::Cents(0.000001),
+                SclPitch::Cents(1e20),
+                SclPitch::Cents(1200.),
+            ],
+        };
+        assert_eq!(scl.to_string().parse::<Scl>().unwrap(), scl);
+    }
+
+    #[test]
+    fn it_round_trips_kbm_files() {
+        let kbm: Kbm = "
+! Black keys unmapped
+12
+0
+127
+60
+69
+440.0
+12
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(kbm.mapping[1], None);
+        assert_eq!(kbm.mapping[11], Some(6));
+        assert_eq!(kbm.to_string().parse::<Kbm>().unwrap(), kbm);
+    }
+
+    #[test]
+    fn it_maps_keys_to_degrees() {
+        let scl: Scl = MEANTONE.parse().unwrap();
+        let kbm = Kbm {
+            size: 12,
+            octave_degree: 12,
+            mapping: (0..12).map(Some).collect(),
+            ..Kbm::default()
+        };
+        let table = TuningTable::new(&scl, &kbm).unwrap();
+
+        let c4 = table.frequency(MidiNote::from_byte(60));
+        let e4 = table.frequency(MidiNote::from_byte(64));
+        assert!((e4 / c4 - 1.25).abs() < 1e-9);
+        assert_eq!(table.frequency(MidiNote::from_byte(69)), 440.);
+    }
+
+    #[test]
+    fn it_reports_errors_with_lines() {
+        let error = "!\nbroken\n 2\n 100.0\n abc\n".parse::<Scl>().unwrap_err();
+        assert_eq!(error, Error::InvalidPitch { line: 5 });
+
+        let error = "!\nshort\n 3\n 2/1\n".parse::<Scl>().unwrap_err();
+        assert_eq!(
+            error,
+            Error::CountMismatch {
+                expected: 3,
+                found: 1
+            }
+        );
+        let error = "99999999999999\n0\n127\n60\n69\n440\n0\n0\n"
+            .parse::<Kbm>()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            Error::CountMismatch {
+                expected: 99999999999999,
+                found: 1
+            }
+        );
+    }
+
+    #[test]
+    fn it_leaves_keys_past_a_short_mapping_unmapped() {
+        let scl: Scl = MEANTONE.parse().unwrap();
+        let kbm = Kbm {
+            size: 12,
+            mapping: vec![Some(0), Some(1)],
+            reference: MidiNote::from_byte(60),
+            ..Kbm::default()
+        };
+        let table = TuningTable::new(&scl, &kbm).unwrap();
+
+        assert!(table.get(MidiNote::from_byte(61)).is_some());
+        assert_eq!(table.get(MidiNote::from_byte(62)), None);
+    }
+}
@@ -88,9 +88,9 @@ impl Note {
                         }
                         "duration" => {
                             if let AttributeValue::Int(n) = attr.value {
-                                let kind = unsafe { mem::transmute((n >> 1) as u8) };
-                                let is_dotted = n & 1 == 1;
-                                duration = Some(Duration::new(kind, is_dotted));
+                                let kind = unsafe { mem::transmute((n >> 2) as u8) };
+                                let dots = (n & 3) as u8;
+                                duration = Some(Duration::new(kind, dots));
                             }
                         }
                         _ => todo!(),
@@ -103,7 +103,7 @@ impl Note {
             natural: natural.unwrap(),
             octave: octave.unwrap_or(Octave::FOUR),
             accidental,
-            duration: duration.unwrap_or_else(|| Duration::new(DurationKind::Quarter, false)),
+            duration: duration.unwrap_or_else(|| Duration::from(DurationKind::Quarter)),
         }
     }
 
@@ -60,10 +60,14 @@ impl Layout {
         let mut w = 20.;
 
         w += match self.duration.kind {
-            DurationKind::Eigth => 10.,
+            DurationKind::HundredTwentyEighth
+            | DurationKind::SixtyFourth
+            | DurationKind::ThirtySecond
+            | DurationKind::Sixteenth
+            | DurationKind::Eigth => 10.,
             DurationKind::Quarter => 20.,
             DurationKind::Half => 40.,
-            DurationKind::Whole => 80.,
+            DurationKind::Whole | DurationKind::Breve => 80.,
         };
 
         if let Some((_, size)) = self.accidental {
@@ -41,7 +41,11 @@ pub fn Note<'a>(
         )
     };
     let head_and_stem_elem = match duration.kind {
-        DurationKind::Eigth => {
+        DurationKind::Eigth
+        | DurationKind::Sixteenth
+        | DurationKind::ThirtySecond
+        | DurationKind::SixtyFourth
+        | DurationKind::HundredTwentyEighth => {
             let mut last_ref = last.borrow_mut();
             let half_stroke_width = stroke_width / 2.;
             let stem_x = note_x + head_size - stroke_width / 2.;
@@ -112,7 +116,7 @@ pub fn Note<'a>(
                 render_stem()
             }
         }
-        DurationKind::Whole => {
+        DurationKind::Whole | DurationKind::Breve => {
             last.borrow_mut().take();
             render!(
                 circle {